@@ -1,3 +1,12 @@
+use crate::hsm::{ConnectorKind, HashAlg};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tracing::level_filters::LevelFilter;
+use zeroize::Zeroizing;
+
 /// Application configuration constants
 
 /// Default authentication key ID
@@ -5,3 +14,223 @@ pub const DEFAULT_AUTH_KEY_ID: u16 = 1;
 
 /// Default signing key ID (secp256r1/ECDSA key stored in YubiHSM2)
 pub const DEFAULT_SIGNING_KEY_ID: u16 = 0xf35b;
+
+/// Reserved object ID for the ephemeral "scratch" demo key
+pub const SCRATCH_KEY_ID: u16 = 0xfffe;
+
+/// Label given to the scratch key so it is recognizable in other tools
+pub const SCRATCH_KEY_LABEL: &str = "hsm-demo scratch key (ephemeral)";
+
+/// Command line flag that starts the app in safe mode
+pub const SAFE_MODE_FLAG: &str = "--safe";
+
+/// Command line flag that makes Sign and Verify take the input as a
+/// precomputed digest instead of hashing it
+pub const PREHASHED_FLAG: &str = "--prehashed";
+
+/// Environment variable that enables safe mode when set to `1` or `true`
+pub const SAFE_MODE_ENV: &str = "HSM_DEMO_SAFE_MODE";
+
+/// Connection attempts made while the connector or device is unreachable
+pub const CONNECT_ATTEMPTS: u32 = 5;
+
+/// Wait before the first connection retry in milliseconds; doubled after each failure
+pub const CONNECT_RETRY_BASE_DELAY_MS: u64 = 250;
+
+/// Minutes without interaction before the session is closed; 0 disables the timeout
+pub const DEFAULT_IDLE_TIMEOUT_MINUTES: u64 = 5;
+
+/// Environment variable overriding the idle timeout in minutes (0 disables it)
+pub const IDLE_TIMEOUT_ENV: &str = "HSM_DEMO_IDLE_MINUTES";
+
+/// Seconds between session heartbeats; 0 disables them. The device closes
+/// sessions idle for 30 seconds, so staying below that also keeps it open
+pub const DEFAULT_HEARTBEAT_SECS: u64 = 20;
+
+/// Environment variable overriding the heartbeat interval in seconds (0 disables it)
+pub const HEARTBEAT_ENV: &str = "HSM_DEMO_HEARTBEAT_SECS";
+
+/// Log level used when the log level environment variable is unset or invalid
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::WARN;
+
+/// Environment variable setting the initial log level: off, error, warn, info, debug or trace
+pub const LOG_LEVEL_ENV: &str = "HSM_DEMO_LOG";
+
+/// Environment variable choosing what each logged sign or verify includes:
+/// full, redacted (the default) or minimal
+pub const LOG_REDACTION_ENV: &str = "HSM_DEMO_LOG_REDACTION";
+
+/// Freshness window for timestamped signatures when the max-age field is empty
+pub const DEFAULT_MAX_SIGNATURE_AGE_SECS: u64 = 300;
+
+/// Bytes requested from the device RNG when the length field is empty
+pub const DEFAULT_RANDOM_LEN: usize = 32;
+
+/// Number of signatures timed by the benchmark
+pub const BENCH_SIGN_ITERATIONS: usize = 20;
+
+/// File name prefix for exported diagnostics reports
+pub const REPORT_FILE_PREFIX: &str = "hsm-report";
+
+/// File name prefix for object listings written by "Export CSV" and "Export all as JSON"
+pub const CSV_FILE_PREFIX: &str = "hsm-objects";
+
+/// File name prefix for one object's metadata written by "Export JSON"
+pub const OBJECT_JSON_FILE_PREFIX: &str = "hsm-object";
+
+/// File name prefix for inventory snapshots
+pub const SNAPSHOT_FILE_PREFIX: &str = "hsm-inventory";
+
+/// File name (without extension) offered by "Save signature"
+pub const SIGNATURE_FILE_PREFIX: &str = "signature";
+
+/// File name prefix for objects written by "Export wrapped"; yubihsm-shell uses `.yhw`
+pub const WRAPPED_FILE_PREFIX: &str = "wrapped";
+
+/// File name prefix for objects written by "Save fetched" on the Opaque objects screen
+pub const OPAQUE_FILE_PREFIX: &str = "opaque";
+
+/// File name prefix for public keys written by "Export public key"
+pub const PUBLIC_KEY_FILE_PREFIX: &str = "public-key";
+
+/// File name prefix for certificates written by "Attest"
+pub const ATTESTATION_FILE_PREFIX: &str = "attestation";
+
+/// File written by "Export JWKS" when none is given
+pub const DEFAULT_JWKS_FILE: &str = "jwks.json";
+
+/// Directory used by "Export for openssl" when none is given
+pub const DEFAULT_OPENSSL_EXPORT_DIR: &str = "openssl-export";
+
+/// Maximum object label length accepted by the YubiHSM2, in UTF-8 bytes
+pub const MAX_LABEL_BYTES: usize = 40;
+
+/// Name of the settings file inside the platform config directory
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Keyring service under which the auto-connect password is stored
+pub const KEYRING_SERVICE: &str = "hsm-demo";
+
+/// Preferences kept across restarts, edited on the Settings screen. The
+/// password is never part of them. Fields missing from an older file take
+/// their defaults.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Connect through a yubihsm-connector over HTTP instead of USB
+    pub use_http: bool,
+    /// yubihsm-connector address as `host[:port]`; empty uses the default
+    pub connector_address: String,
+    pub auth_key_id: u16,
+    /// Key used for signing when none is picked or typed
+    pub signing_key_id: u16,
+    pub hash_alg: HashAlg,
+    /// Minutes without interaction before the session is closed; 0 disables the timeout
+    pub idle_timeout_minutes: u64,
+    /// Connect over HTTP at startup with the password from the system keyring.
+    /// Off unless the user turns it on
+    pub auto_connect: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            use_http: matches!(ConnectorKind::default(), ConnectorKind::Http { .. }),
+            connector_address: String::new(),
+            auth_key_id: DEFAULT_AUTH_KEY_ID,
+            signing_key_id: DEFAULT_SIGNING_KEY_ID,
+            hash_alg: HashAlg::default(),
+            idle_timeout_minutes: DEFAULT_IDLE_TIMEOUT_MINUTES,
+            auto_connect: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Location of the settings file, or None if the platform has no config directory.
+    pub fn path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "hsm-demo").map(|dirs| dirs.config_dir().join(SETTINGS_FILE))
+    }
+
+    /// Settings from the settings file, or the defaults if there is none. An
+    /// unreadable or corrupt file is logged and ignored rather than stopping the app.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+        let json = match fs::read_to_string(&path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "cannot read settings; using defaults");
+                return Self::default();
+            }
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            tracing::warn!(path = %path.display(), error = %e, "invalid settings file; using defaults");
+            Self::default()
+        })
+    }
+
+    /// Write the settings file, creating its directory if needed. Returns where
+    /// it was written.
+    pub fn save(&self) -> io::Result<PathBuf> {
+        let path =
+            Self::path().ok_or_else(|| io::Error::other("no config directory on this platform"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+/// Keyring entry for the password of auth key `auth_key_id`.
+fn keyring_entry(auth_key_id: u16) -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("auth-key-0x{:04x}", auth_key_id))
+}
+
+/// Password stored for auto-connect with auth key `auth_key_id`, if any. A
+/// keyring that cannot be reached is logged and treated as empty.
+pub fn stored_password(auth_key_id: u16) -> Option<Zeroizing<String>> {
+    match keyring_entry(auth_key_id).and_then(|entry| entry.get_password()) {
+        Ok(password) => Some(Zeroizing::new(password)),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            tracing::warn!(error = %e, "cannot read the password from the keyring");
+            None
+        }
+    }
+}
+
+/// Keep `password` in the system keyring for auto-connect with auth key `auth_key_id`.
+pub fn store_password(auth_key_id: u16, password: &str) -> io::Result<()> {
+    keyring_entry(auth_key_id)
+        .and_then(|entry| entry.set_password(password))
+        .map_err(io::Error::other)
+}
+
+/// Remove the auto-connect password of auth key `auth_key_id` from the keyring.
+pub fn forget_password(auth_key_id: u16) -> io::Result<()> {
+    match keyring_entry(auth_key_id).and_then(|entry| entry.delete_password()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(io::Error::other(e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn older_settings_file_leaves_auto_connect_off() {
+        let settings: Settings =
+            serde_json::from_str(r#"{"use_http": true, "connector_address": "10.0.0.5:12345"}"#)
+                .unwrap();
+
+        assert!(!settings.auto_connect);
+        assert_eq!(settings.connector_address, "10.0.0.5:12345");
+        assert_eq!(settings.auth_key_id, DEFAULT_AUTH_KEY_ID);
+    }
+}
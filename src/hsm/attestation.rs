@@ -0,0 +1,179 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult, device_error_kind};
+use super::offline::{Curve, verify_with_curve};
+use super::operations::{der_read_tlv, get_object_info, get_public_key};
+use super::spki::public_key_spki_der;
+use sha2::{Digest, Sha256};
+use yubihsm::asymmetric;
+use yubihsm::device::ErrorKind as DeviceErrorKind;
+use yubihsm::object::{Id, Type};
+
+/// DER tags found in an X.509 certificate
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_SEQUENCE: u8 = 0x30;
+
+/// AlgorithmIdentifier content for ecdsa-with-SHA256 (1.2.840.10045.4.3.2)
+const OID_ECDSA_WITH_SHA256: &[u8] = &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+
+/// Result of having `attesting_key_id` attest `key_id` and checking the certificate.
+#[derive(Clone, Debug)]
+pub struct AttestationOutcome {
+    pub key_id: Id,
+    pub attesting_key_id: Id,
+    /// X.509 certificate returned by the device, DER-encoded
+    pub certificate: Vec<u8>,
+    /// Hex SHA-256 of `certificate`
+    pub certificate_fingerprint: String,
+    /// The certificate's subject public key is the current public key of `key_id`
+    pub subject_key_matches: bool,
+    /// The certificate signature verifies under the attesting key's public key
+    pub signature_valid: bool,
+}
+
+/// The parts of an X.509 certificate needed to check its signature.
+struct CertificateParts<'a> {
+    /// Complete DER encoding of `tbsCertificate`, the signed bytes
+    tbs: &'a [u8],
+    /// Content of `signatureAlgorithm`
+    algorithm: &'a [u8],
+    /// DER ECDSA signature from `signatureValue`
+    signature: &'a [u8],
+}
+
+/// Split a DER certificate into its signed part, algorithm and signature.
+fn split_certificate(der: &[u8]) -> HsmResult<CertificateParts<'_>> {
+    let malformed = || HsmError::InvalidInput("Attestation certificate is malformed".to_string());
+
+    let (tag, body, _) = der_read_tlv(der).ok_or_else(malformed)?;
+    if tag != TAG_SEQUENCE {
+        return Err(malformed());
+    }
+
+    // Keep the tbsCertificate header: the signature covers the whole TLV
+    let (tag, _, after_tbs) = der_read_tlv(body).ok_or_else(malformed)?;
+    if tag != TAG_SEQUENCE {
+        return Err(malformed());
+    }
+    let tbs = &body[..body.len() - after_tbs.len()];
+
+    let (tag, algorithm, rest) = der_read_tlv(after_tbs).ok_or_else(malformed)?;
+    if tag != TAG_SEQUENCE {
+        return Err(malformed());
+    }
+
+    let (tag, bits, _) = der_read_tlv(rest).ok_or_else(malformed)?;
+    // The leading byte of a BIT STRING counts unused bits; signatures have none
+    let signature = match bits.split_first() {
+        Some((0, signature)) if tag == TAG_BIT_STRING => signature,
+        _ => return Err(malformed()),
+    };
+
+    Ok(CertificateParts {
+        tbs,
+        algorithm,
+        signature,
+    })
+}
+
+/// Have the device issue an X.509 attestation certificate (DER) for the
+/// asymmetric key `key_id`, signed by `attestation_key_id`. `None` uses the
+/// factory attestation key and certificate every device ships with; another
+/// attesting key needs a template certificate stored as an opaque object under
+/// its ID.
+pub fn sign_attestation_certificate(
+    client: &HsmClient,
+    key_id: Id,
+    attestation_key_id: Option<Id>,
+) -> HsmResult<Vec<u8>> {
+    // The device only says "not found"; name the actual problem instead
+    match get_object_info(client, key_id, Type::AsymmetricKey) {
+        Ok(_) => {}
+        Err(HsmError::ObjectNotFound { .. }) => {
+            return Err(HsmError::InvalidKey(format!(
+                "0x{:04x} is not an asymmetric key, so there is nothing to attest",
+                key_id
+            )));
+        }
+        Err(e) => return Err(e),
+    }
+
+    let attesting = match attestation_key_id {
+        Some(id) => format!("key 0x{:04x}", id),
+        None => "the factory attestation key".to_string(),
+    };
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::SigningFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.sign_attestation_certificate(key_id, attestation_key_id)
+        .map(|certificate| certificate.as_ref().to_vec())
+        .map_err(|e| match device_error_kind(&e) {
+            Some(DeviceErrorKind::ObjectNotFound) => HsmError::InvalidKey(format!(
+                "{} does not exist or has no template certificate (an opaque object with the same ID) ({:?})",
+                attesting, e
+            )),
+            Some(DeviceErrorKind::InsufficientPermissions) => HsmError::InvalidKey(format!(
+                "{} or this session's auth key lacks the sign-attestation-certificate capability ({:?})",
+                attesting, e
+            )),
+            _ => HsmError::from_client(e, |msg| {
+                HsmError::SigningFailed(format!(
+                    "Failed to attest key 0x{:04x} with {}: {}",
+                    key_id, attesting, msg
+                ))
+            }),
+        })
+}
+
+/// Have the device issue an attestation certificate for `key_id`, signed by
+/// `attesting_key_id`, then check on the host that it names the current public
+/// key of `key_id` and that its signature verifies under the attesting key.
+/// The attesting key needs the sign-attestation-certificate capability and a
+/// template certificate stored as an opaque object under the same ID.
+pub fn attest_key(
+    client: &HsmClient,
+    key_id: Id,
+    attesting_key_id: Id,
+) -> HsmResult<AttestationOutcome> {
+    let certificate = sign_attestation_certificate(client, key_id, Some(attesting_key_id))?;
+
+    let parts = split_certificate(&certificate)?;
+    if !parts.algorithm.starts_with(OID_ECDSA_WITH_SHA256) {
+        return Err(HsmError::InvalidKey(
+            "Only ecdsa-with-SHA256 attestation signatures can be checked here".to_string(),
+        ));
+    }
+
+    // The SPKI is embedded verbatim in tbsCertificate, so a byte search suffices
+    let subject_spki = public_key_spki_der(&get_public_key(client, key_id)?)?;
+    let subject_key_matches = parts
+        .tbs
+        .windows(subject_spki.len())
+        .any(|window| window == subject_spki.as_slice());
+
+    let attesting_key = get_public_key(client, attesting_key_id)?;
+    let curve = match attesting_key.algorithm {
+        asymmetric::Algorithm::EcP256 => Curve::P256,
+        asymmetric::Algorithm::EcP384 => Curve::P384,
+        asymmetric::Algorithm::EcK256 => Curve::K256,
+        other => {
+            return Err(HsmError::InvalidKey(format!(
+                "Attesting key 0x{:04x} uses {:?}; only P-256, P-384 and secp256k1 are supported",
+                attesting_key_id, other
+            )));
+        }
+    };
+    let signature_valid =
+        verify_with_curve(&attesting_key.bytes, parts.tbs, parts.signature, curve)?;
+
+    Ok(AttestationOutcome {
+        key_id,
+        attesting_key_id,
+        certificate_fingerprint: hex::encode(Sha256::digest(&certificate)),
+        certificate,
+        subject_key_matches,
+        signature_valid,
+    })
+}
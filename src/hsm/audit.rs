@@ -0,0 +1,151 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult};
+use yubihsm::audit::LogEntry;
+
+/// Default number of audit log entries returned per page
+pub const DEFAULT_AUDIT_PAGE_SIZE: usize = 16;
+
+/// Read every entry currently stored in the device audit log, oldest first.
+pub fn get_audit_log(client: &HsmClient) -> HsmResult<Vec<LogEntry>> {
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::AuditLogFailed(format!("Failed to lock client: {}", e)))?;
+
+    let log = hsm
+        .get_log_entries()
+        .map_err(|e| HsmError::from_client(e, HsmError::AuditLogFailed))?;
+
+    Ok(log.entries)
+}
+
+/// Mark every entry up to and including `index` as read, so the device may
+/// overwrite them. With the force-audit option enabled a full log blocks most
+/// commands until entries are acknowledged this way.
+pub fn set_log_index(client: &HsmClient, index: u16) -> HsmResult<()> {
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::AuditLogFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.set_log_index(index).map_err(|e| {
+        HsmError::from_client(e, |msg| {
+            HsmError::AuditLogFailed(format!("Failed to acknowledge log entries: {}", msg))
+        })
+    })
+}
+
+/// Incrementally pages through the device audit log.
+///
+/// The device keeps its log in a circular buffer and always returns every
+/// stored entry, so paging happens on the host: each call to `next_page`
+/// takes a freshly read log and yields entries that come after the last one
+/// seen. Item numbers are 16-bit counters that wrap around, so ordering uses
+/// serial number arithmetic instead of plain comparison.
+#[derive(Clone, Debug)]
+pub struct AuditLogPager {
+    page_size: usize,
+    last_item: Option<u16>,
+    remaining: usize,
+}
+
+impl AuditLogPager {
+    pub fn new(page_size: usize) -> Self {
+        Self {
+            page_size: page_size.max(1),
+            last_item: None,
+            remaining: 0,
+        }
+    }
+
+    /// Item number of the last entry returned, if any.
+    pub fn last_item(&self) -> Option<u16> {
+        self.last_item
+    }
+
+    /// Returns true if the last fetch saw more entries than fit in the page.
+    pub fn has_more(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// The next page of `log`, as read by `get_audit_log`: entries that come
+    /// after the last seen item, oldest first.
+    pub fn next_page(&mut self, log: Vec<LogEntry>) -> Vec<LogEntry> {
+        self.page_by(log, |entry| entry.item)
+    }
+
+    fn page_by<T>(&mut self, log: Vec<T>, item: impl Fn(&T) -> u16) -> Vec<T> {
+        let mut entries: Vec<T> = match self.last_item {
+            Some(last) => log
+                .into_iter()
+                .filter(|entry| is_after(item(entry), last))
+                .collect(),
+            None => log,
+        };
+
+        // Order oldest-first relative to the last seen item so a wrapped
+        // buffer (e.g. 0xfffe, 0xffff, 0x0000) stays in sequence
+        let anchor = self
+            .last_item
+            .or_else(|| entries.first().map(|entry| item(entry).wrapping_sub(1)))
+            .unwrap_or(0);
+        entries.sort_by_key(|entry| item(entry).wrapping_sub(anchor));
+
+        self.remaining = entries.len().saturating_sub(self.page_size);
+        entries.truncate(self.page_size);
+
+        if let Some(entry) = entries.last() {
+            self.last_item = Some(item(entry));
+        }
+
+        entries
+    }
+}
+
+/// Serial number comparison for wrapping 16-bit item counters (RFC 1982 style).
+fn is_after(item: u16, last: u16) -> bool {
+    (item.wrapping_sub(last) as i16) > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(pager: &mut AuditLogPager, log: &[u16]) -> Vec<u16> {
+        pager.page_by(log.to_vec(), |&item| item)
+    }
+
+    #[test]
+    fn pages_follow_the_last_seen_item() {
+        let log: Vec<u16> = (1..=5).collect();
+        let mut pager = AuditLogPager::new(2);
+
+        assert_eq!(page(&mut pager, &log), [1, 2]);
+        assert!(pager.has_more());
+        assert_eq!(page(&mut pager, &log), [3, 4]);
+        assert!(pager.has_more());
+        assert_eq!(page(&mut pager, &log), [5]);
+        assert!(!pager.has_more());
+        assert_eq!(pager.last_item(), Some(5));
+    }
+
+    #[test]
+    fn new_entries_show_up_on_the_next_page() {
+        let mut pager = AuditLogPager::new(DEFAULT_AUDIT_PAGE_SIZE);
+
+        assert_eq!(page(&mut pager, &[7, 8]), [7, 8]);
+        assert!(page(&mut pager, &[7, 8]).is_empty());
+        // The device overwrote item 7 and logged two more commands
+        assert_eq!(page(&mut pager, &[8, 9, 10]), [9, 10]);
+    }
+
+    #[test]
+    fn wrapped_item_numbers_stay_in_order() {
+        let mut pager = AuditLogPager::new(2);
+        let log = [0x0000, 0x0001, 0xfffe, 0xffff];
+
+        assert_eq!(page(&mut pager, &[0xfffe, 0xffff]), [0xfffe, 0xffff]);
+        assert_eq!(page(&mut pager, &log), [0x0000, 0x0001]);
+        assert!(!pager.has_more());
+    }
+}
@@ -1,19 +1,184 @@
 use super::error::{HsmError, HsmResult};
-use std::sync::{Arc, Mutex};
-use yubihsm::{Client, Connector, Credentials, UsbConfig};
+use super::key_cache::PublicKeyCache;
+use super::logging::HexId;
+use super::operations::get_object_info;
+use std::cell::Cell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, TryLockError};
+use std::time::{Duration, Instant};
+use tracing::instrument;
+use yubihsm::device::SerialNumber;
+use yubihsm::object::{Id, Type};
+use yubihsm::{Capability, Client, Connector, Credentials, Domain, authentication};
+use zeroize::Zeroizing;
+
+/// Address of a yubihsm-connector running on this machine with its defaults
+pub const DEFAULT_CONNECTOR_ADDR: &str = "127.0.0.1";
+/// Port yubihsm-connector listens on unless configured otherwise
+pub const DEFAULT_CONNECTOR_PORT: u16 = 12345;
+
+/// How to reach the device
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConnectorKind {
+    /// Directly over USB (requires the `usb` feature); `serial` picks one of
+    /// several plugged-in devices, `None` takes the first one found
+    Usb { serial: Option<SerialNumber> },
+    /// Through a yubihsm-connector HTTP service (requires the `http` feature)
+    Http { addr: String, port: u16 },
+}
+
+impl Default for ConnectorKind {
+    /// USB when compiled in, otherwise the local yubihsm-connector.
+    fn default() -> Self {
+        if cfg!(feature = "usb") {
+            ConnectorKind::Usb { serial: None }
+        } else {
+            ConnectorKind::Http {
+                addr: DEFAULT_CONNECTOR_ADDR.to_string(),
+                port: DEFAULT_CONNECTOR_PORT,
+            }
+        }
+    }
+}
+
+impl ConnectorKind {
+    /// HTTP connector from `host`, `host:port` or `http://host:port`; the port
+    /// defaults to `DEFAULT_CONNECTOR_PORT` and an empty input to the local connector.
+    pub fn http_from_address(input: &str) -> HsmResult<Self> {
+        let input = input.trim();
+        let input = input.strip_prefix("http://").unwrap_or(input);
+        let input = input.trim_end_matches('/');
+        if input.is_empty() {
+            return Ok(ConnectorKind::Http {
+                addr: DEFAULT_CONNECTOR_ADDR.to_string(),
+                port: DEFAULT_CONNECTOR_PORT,
+            });
+        }
+
+        let (addr, port) = match input.rsplit_once(':') {
+            Some((addr, port)) => {
+                let port = port.parse().map_err(|_| {
+                    HsmError::InvalidInput(format!("'{}' is not a valid port", port))
+                })?;
+                (addr, port)
+            }
+            None => (input, DEFAULT_CONNECTOR_PORT),
+        };
+        if addr.is_empty() {
+            return Err(HsmError::InvalidInput(
+                "Connector address is missing a host".to_string(),
+            ));
+        }
+
+        Ok(ConnectorKind::Http {
+            addr: addr.to_string(),
+            port,
+        })
+    }
+}
+
+impl fmt::Display for ConnectorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectorKind::Usb {
+                serial: Some(serial),
+            } => write!(f, "USB device {}", serial),
+            ConnectorKind::Usb { serial: None } => write!(f, "USB"),
+            ConnectorKind::Http { addr, port } => write!(f, "http://{}:{}", addr, port),
+        }
+    }
+}
+
+/// Key derivation `Credentials::from_password` applies to the auth password.
+/// The yubihsm crate hard-codes these (matching yubihsm-shell and the SDK), so
+/// they are documented here for display rather than configurable.
+pub const PASSWORD_KDF: &str = "PBKDF2-HMAC-SHA256";
+/// Salt used for the password derivation
+pub const PASSWORD_KDF_SALT: &str = "Yubico";
+/// PBKDF2 iteration count used for the password derivation
+pub const PASSWORD_KDF_ITERATIONS: u32 = 10_000;
+/// Derived bytes: a 16-byte encryption key followed by a 16-byte MAC key
+pub const PASSWORD_KDF_OUTPUT_LEN: usize = 32;
+
+/// How long connecting waits for the connector or device before giving up
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Configuration for HSM connection
 #[derive(Clone)]
 pub struct HsmConfig {
     pub auth_key_id: u16,
-    pub auth_password: String,
+    /// Kept for renegotiating the session; wiped from memory when dropped
+    pub auth_password: Zeroizing<String>,
+    pub connector: ConnectorKind,
+    /// Timeout for the connector's requests; a dead HTTP connector fails after
+    /// this long with `ConnectorTimedOut`
+    pub connect_timeout: Duration,
+    /// Safe mode: refuse every operation that creates, changes or deletes objects
+    pub destructive_disabled: bool,
 }
 
 impl Default for HsmConfig {
     fn default() -> Self {
         Self {
             auth_key_id: 1,
-            auth_password: "password".to_string(),
+            auth_password: Zeroizing::new("password".to_string()),
+            connector: ConnectorKind::default(),
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            destructive_disabled: false,
+        }
+    }
+}
+
+/// Retry policy for opening a session: only unreachable or timed-out
+/// connectors and devices are retried, with exponential backoff. Wrong
+/// credentials fail at once.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectRetry {
+    /// Total attempts, including the first
+    pub attempts: u32,
+    /// Wait before the second attempt; doubled before each further one
+    pub base_delay: Duration,
+}
+
+impl ConnectRetry {
+    /// Whether to try again after attempt `attempt` (1-based) failed with `error`.
+    pub fn should_retry(&self, attempt: u32, error: &HsmError) -> bool {
+        error.is_transient() && attempt < self.attempts
+    }
+
+    /// Wait after failed attempt `attempt` before the next one.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay * 2u32.saturating_pow(attempt.saturating_sub(1))
+    }
+}
+
+/// Renegotiate the session proactively once this many operations went through it.
+/// Sessions have a device-enforced message limit; staying well below it avoids
+/// hitting the limit in the middle of an operation.
+const SESSION_RENEGOTIATE_THRESHOLD: usize = 10_000;
+
+/// Payload of the heartbeat echo; the device sends it back unchanged
+const HEARTBEAT_MESSAGE: &[u8] = b"hsm-demo heartbeat";
+
+/// Health of the active session as last seen by the heartbeat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The last heartbeat was answered
+    Connected,
+    /// The last heartbeat failed, but the session may still be usable
+    Degraded,
+    /// The device is gone or no longer accepts the session
+    Lost,
+}
+
+impl ConnectionState {
+    pub fn label(self) -> &'static str {
+        match self {
+            ConnectionState::Connected => "Connected",
+            ConnectionState::Degraded => "Degraded",
+            ConnectionState::Lost => "Lost",
         }
     }
 }
@@ -21,55 +186,434 @@ impl Default for HsmConfig {
 /// HSM client wrapper that manages the connection to yubihsm2
 pub struct HsmClient {
     client: Arc<Mutex<Client>>,
+    /// Authentication key the session was opened with
+    auth_key_id: Id,
+    /// Safe mode: refuse every operation that creates, changes or deletes objects
+    destructive_disabled: bool,
+    /// Kept so the session can be renegotiated with the same credentials;
+    /// `None` for adopted sessions, whose credentials were never seen
+    config: Option<HsmConfig>,
+    /// Operations sent over the current session
+    messages: AtomicUsize,
 }
 
 impl HsmClient {
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(
+            auth_key_id = %HexId(config.auth_key_id),
+            connector = %config.connector,
+            timeout = ?config.connect_timeout,
+        ),
+        err(level = "warn", Display)
+    )]
     pub fn connect(config: HsmConfig) -> HsmResult<Self> {
-        // create usb connector
-        let serial_config = UsbConfig::default();
-        let connector = Connector::usb(&serial_config);
-        let credentials =
-            Credentials::from_password(config.auth_key_id, config.auth_password.as_bytes());
+        // Never send an empty or whitespace-only secret to the device
+        if config.auth_password.trim().is_empty() {
+            return Err(HsmError::InvalidInput(
+                "Authentication password cannot be empty".to_string(),
+            ));
+        }
 
-        // open client sesh
-        let client = Client::open(connector, credentials, true)
-            .map_err(|e| HsmError::AuthenticationFailed(format!("{:?}", e)))?;
+        let mut client = Self::adopt(
+            open_client(&config)?,
+            config.auth_key_id,
+            config.destructive_disabled,
+        );
+        client.config = Some(config);
+        Ok(client)
+    }
 
-        Ok(Self {
+    /// Wrap a session that was already opened elsewhere, e.g. by an embedding
+    /// application, so no password has to pass through this process.
+    /// `auth_key_id` must be the key `client` authenticated with. Adopted
+    /// sessions cannot be renegotiated; once the device message limit is hit
+    /// the owner has to hand over a fresh one.
+    pub fn adopt(client: Client, auth_key_id: Id, destructive_disabled: bool) -> Self {
+        Self {
             client: Arc::new(Mutex::new(client)),
-        })
+            auth_key_id,
+            destructive_disabled,
+            config: None,
+            messages: AtomicUsize::new(0),
+        }
+    }
+
+    /// Authentication key the session was opened with.
+    pub fn auth_key_id(&self) -> Id {
+        self.auth_key_id
     }
 
     /// with this we can call any yubihsm client method directly
     pub fn client(&self) -> Arc<Mutex<Client>> {
+        // A panic while the lock was held poisons it. The panic itself is reported
+        // by `with_session_retry`; later operations should not fail because of it
+        if self.client.is_poisoned() {
+            self.client.clear_poison();
+        }
+        self.messages.fetch_add(1, Ordering::Relaxed);
         self.client.clone()
     }
+
+    /// Fail with `AccessDenied` if the session was opened in safe mode.
+    /// Called by every mutating operation as a backstop behind the UI gating.
+    pub fn ensure_destructive_allowed(&self) -> HsmResult<()> {
+        if self.destructive_disabled {
+            return Err(HsmError::AccessDenied(
+                "destructive operations disabled in safe mode".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Number of operations sent over the current session.
+    pub fn message_count(&self) -> usize {
+        self.messages.load(Ordering::Relaxed)
+    }
+
+    /// Send an echo over the session to check that it is still alive. Returns
+    /// `None` without sending anything while another operation holds the
+    /// client, so the heartbeat never waits behind real work.
+    pub fn try_ping(&self) -> Option<HsmResult<()>> {
+        let hsm = match self.client.try_lock() {
+            Ok(hsm) => hsm,
+            Err(TryLockError::WouldBlock) => return None,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        };
+        self.messages.fetch_add(1, Ordering::Relaxed);
+
+        let result = hsm.echo(HEARTBEAT_MESSAGE).map(|_| ()).map_err(|e| {
+            HsmError::from_client(e, |msg| {
+                HsmError::ListingFailed(format!("Heartbeat echo failed: {}", msg))
+            })
+        });
+        Some(result)
+    }
+
+    /// Close the current session and open a fresh one with the stored credentials.
+    #[instrument(
+        level = "info",
+        skip_all,
+        fields(auth_key_id = %HexId(self.auth_key_id), messages = self.message_count()),
+        err(level = "warn", Display)
+    )]
+    pub fn renegotiate(&self) -> HsmResult<()> {
+        let config = self.config.as_ref().ok_or_else(|| {
+            HsmError::AuthenticationFailed(
+                "Adopted session cannot be renegotiated; open a new one".to_string(),
+            )
+        })?;
+
+        // The old client is replaced wholesale, so a poisoned lock is harmless here
+        let mut hsm = self.client.lock().unwrap_or_else(PoisonError::into_inner);
+
+        *hsm = open_client(config)?;
+        self.messages.store(0, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Run `op`, renegotiating the session first if it is close to the message
+    /// limit, and once more followed by a single retry if the device reports the
+    /// session expired anyway (idle timeout or limit reached). A panic inside
+    /// `op` is returned as `Internal`.
+    pub fn with_session_retry<T>(&self, op: impl Fn(&HsmClient) -> HsmResult<T>) -> HsmResult<T> {
+        if self.message_count() >= SESSION_RENEGOTIATE_THRESHOLD {
+            self.renegotiate()?;
+        }
+
+        retry_expired(|| op(self), || self.renegotiate())
+    }
 }
 
-impl Drop for HsmClient {
-    fn drop(&mut self) {
-        // The YubiHSM client will automatically close the session when dropped
+/// Run `op`; if it reports the session expired, `renegotiate` and run it once
+/// more. A panic inside `op` is returned as `Internal`.
+pub(super) fn retry_expired<T>(
+    op: impl Fn() -> HsmResult<T>,
+    renegotiate: impl FnOnce() -> HsmResult<()>,
+) -> HsmResult<T> {
+    match catch_panic(&op) {
+        Err(e) if e.is_session_expired() => {
+            renegotiate()?;
+            catch_panic(&op)
+        }
+        result => result,
     }
 }
 
+fn no_session() -> HsmError {
+    HsmError::AuthenticationFailed("No active HSM session. Please authenticate first.".into())
+}
+
+/// Run `op`, turning a panic into `HsmError::Internal` so it cannot take down
+/// the UI thread.
+fn catch_panic<T>(op: impl FnOnce() -> HsmResult<T>) -> HsmResult<T> {
+    panic::catch_unwind(AssertUnwindSafe(op)).unwrap_or_else(|payload| {
+        let msg = payload
+            .downcast_ref::<&str>()
+            .map(|msg| msg.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "operation panicked".to_string());
+        Err(HsmError::Internal(msg))
+    })
+}
+
+/// Build the connector for `kind` with requests timing out after `timeout`,
+/// or explain which feature is missing.
+fn connector(kind: &ConnectorKind, timeout: Duration) -> HsmResult<Connector> {
+    let timeout_ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+    match kind {
+        #[cfg(feature = "usb")]
+        ConnectorKind::Usb { serial } => Ok(Connector::usb(&yubihsm::UsbConfig {
+            serial: *serial,
+            timeout_ms,
+        })),
+        #[cfg(not(feature = "usb"))]
+        ConnectorKind::Usb { .. } => Err(HsmError::ConnectorUnavailable(
+            "USB support is not compiled in; rebuild with the `usb` feature".to_string(),
+        )),
+        #[cfg(feature = "http")]
+        ConnectorKind::Http { addr, port } => Ok(Connector::http(&yubihsm::HttpConfig {
+            addr: addr.clone(),
+            port: *port,
+            timeout_ms,
+        })),
+        #[cfg(not(feature = "http"))]
+        ConnectorKind::Http { .. } => Err(HsmError::ConnectorUnavailable(
+            "HTTP connector support is not compiled in; rebuild with the `http` feature"
+                .to_string(),
+        )),
+    }
+}
+
+/// Serial numbers of the YubiHSM2 devices currently plugged in over USB.
+#[cfg(feature = "usb")]
+pub fn list_usb_devices() -> HsmResult<Vec<SerialNumber>> {
+    yubihsm::connector::usb::Devices::serial_numbers().map_err(|e| {
+        HsmError::ConnectorUnavailable(format!("Failed to enumerate USB devices: {}", e))
+    })
+}
+
+/// Serial numbers of the YubiHSM2 devices currently plugged in over USB.
+#[cfg(not(feature = "usb"))]
+pub fn list_usb_devices() -> HsmResult<Vec<SerialNumber>> {
+    Err(HsmError::ConnectorUnavailable(
+        "USB support is not compiled in; rebuild with the `usb` feature".to_string(),
+    ))
+}
+
+/// Open an authenticated session over the configured connector.
+fn open_client(config: &HsmConfig) -> HsmResult<Client> {
+    let connector = connector(&config.connector, config.connect_timeout)?;
+    let credentials =
+        Credentials::from_password(config.auth_key_id, config.auth_password.as_bytes());
+
+    // open client sesh
+    Client::open(connector, credentials, true)
+        .map_err(|e| HsmError::from_connect(e, &config.connector))
+}
+
+/// What the authentication key behind the session is allowed to do.
+#[derive(Clone, Debug)]
+pub struct SessionInfo {
+    pub auth_key_id: Id,
+    pub capabilities: Capability,
+    pub delegated_capabilities: Capability,
+    pub domains: Domain,
+}
+
+impl SessionInfo {
+    /// Returns true if the auth key has every capability in `capability`.
+    pub fn can(&self, capability: Capability) -> bool {
+        self.capabilities.contains(capability)
+    }
+
+    /// Returns true if the auth key may create or delete any kind of object.
+    pub fn is_writable(&self) -> bool {
+        self.capabilities.intersects(
+            Capability::PUT_ASYMMETRIC_KEY
+                | Capability::GENERATE_ASYMMETRIC_KEY
+                | Capability::DELETE_ASYMMETRIC_KEY
+                | Capability::PUT_WRAP_KEY
+                | Capability::GENERATE_WRAP_KEY
+                | Capability::DELETE_WRAP_KEY
+                | Capability::PUT_HMAC_KEY
+                | Capability::GENERATE_HMAC_KEY
+                | Capability::DELETE_HMAC_KEY
+                | Capability::PUT_OPAQUE
+                | Capability::DELETE_OPAQUE,
+        )
+    }
+
+    /// Returns true if the auth key may delete objects of `object_type`.
+    pub fn can_delete(&self, object_type: Type) -> bool {
+        let capability = match object_type {
+            Type::AsymmetricKey => Capability::DELETE_ASYMMETRIC_KEY,
+            Type::AuthenticationKey => Capability::DELETE_AUTHENTICATION_KEY,
+            Type::HmacKey => Capability::DELETE_HMAC_KEY,
+            Type::Opaque => Capability::DELETE_OPAQUE,
+            Type::OtpAeadKey => Capability::DELETE_OTP_AEAD_KEY,
+            Type::Template => Capability::DELETE_TEMPLATE,
+            Type::WrapKey => Capability::DELETE_WRAP_KEY,
+        };
+        self.can(capability)
+    }
+}
+
+/// Fetch the capabilities of the authentication key the client logged in with.
+pub fn fetch_session_info(client: &HsmClient) -> HsmResult<SessionInfo> {
+    let auth_key_id = client.auth_key_id;
+    let info = get_object_info(client, auth_key_id, Type::AuthenticationKey)?;
+
+    Ok(SessionInfo {
+        auth_key_id,
+        capabilities: info.capabilities,
+        delegated_capabilities: info.delegated_capabilities,
+        domains: info.domains,
+    })
+}
+
+/// Replace the password of authentication key `key_id` in place, keeping its
+/// ID, capabilities and domains. When `key_id` is the session's own key,
+/// `old_password` must match the one the session was opened with. The session
+/// stays usable until closed but can no longer be renegotiated, so callers
+/// should disconnect and authenticate again with `new_password`.
+pub fn change_auth_password(
+    client: &HsmClient,
+    key_id: Id,
+    old_password: &str,
+    new_password: &str,
+) -> HsmResult<()> {
+    client.ensure_destructive_allowed()?;
+
+    if new_password.trim().is_empty() {
+        return Err(HsmError::InvalidInput(
+            "The new password cannot be empty".to_string(),
+        ));
+    }
+    if new_password == old_password {
+        return Err(HsmError::InvalidInput(
+            "The new password is the same as the current one".to_string(),
+        ));
+    }
+    if key_id == client.auth_key_id {
+        match &client.config {
+            Some(config) if config.auth_password.as_str() != old_password => {
+                return Err(HsmError::AccessDenied(
+                    "the current password is not the one this session was opened with".to_string(),
+                ));
+            }
+            Some(_) => {}
+            None => {
+                return Err(HsmError::PasswordChangeFailed(
+                    "this session was adopted, so its current password cannot be checked"
+                        .to_string(),
+                ));
+            }
+        }
+    }
+
+    let key = authentication::Key::derive_from_password(new_password.as_bytes());
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::PasswordChangeFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.change_authentication_key(key_id, key)
+        .map(|_| ())
+        .map_err(|e| {
+            match HsmError::from_client_object(
+                e,
+                key_id,
+                Type::AuthenticationKey,
+                HsmError::PasswordChangeFailed,
+            ) {
+                HsmError::InsufficientPermissions(msg) => HsmError::AccessDenied(format!(
+                    "auth key 0x{:04x} lacks the change-authentication-key capability ({})",
+                    key_id, msg
+                )),
+                other => other,
+            }
+        })
+}
+
 /// Manages an active logical session to the HSM (one set of credentials).
 /// Can be extended later to handle multiple named sessions.
 pub struct SessionManager {
-    active_client: Option<HsmClient>,
+    /// Shared so background tasks can keep using the session while the UI runs
+    active_client: Option<Arc<HsmClient>>,
+    /// Cached capabilities of the session's auth key
+    info: Option<SessionInfo>,
+    /// What the user said the session is for, e.g. "prod signer"
+    label: Option<String>,
+    /// Last time the session was used, for the idle timeout
+    last_activity: Cell<Instant>,
+    /// Public keys listed during this session; shared with background listings
+    public_keys: Arc<Mutex<PublicKeyCache>>,
+    /// Result of the last heartbeat
+    connection: ConnectionState,
 }
 
 impl SessionManager {
     pub fn new() -> Self {
         Self {
             active_client: None,
+            info: None,
+            label: None,
+            last_activity: Cell::new(Instant::now()),
+            public_keys: Arc::default(),
+            connection: ConnectionState::Connected,
         }
     }
 
-    /// Connect using the provided config and set it as the active session.
-    pub fn connect(&mut self, config: HsmConfig) -> HsmResult<()> {
-        let client = HsmClient::connect(config)?;
-        self.active_client = Some(client);
-        Ok(())
+    /// `adopt` a freshly connected client, noting what the session is for.
+    /// `label` is free-form; blank counts as none.
+    pub fn adopt_labeled(&mut self, client: HsmClient, label: Option<String>) {
+        self.adopt(client);
+        self.label = label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty());
+    }
+
+    /// Make an already-authenticated client the active session, replacing any
+    /// previous one. Lets callers create the session outside the GUI auth flow.
+    pub fn adopt(&mut self, client: HsmClient) {
+        // Capability info only drives UI gating; a failed lookup is not fatal
+        self.info = fetch_session_info(&client).ok();
+        tracing::info!(auth_key_id = %HexId(client.auth_key_id()), "session opened");
+        self.active_client = Some(Arc::new(client));
+        self.label = None;
+        // Another session may see other objects or the same IDs differently
+        self.public_keys = Arc::default();
+        self.connection = ConnectionState::Connected;
+        self.touch();
+    }
+
+    /// Record activity on the session so the idle timeout starts over.
+    pub fn touch(&self) {
+        self.last_activity.set(Instant::now());
+    }
+
+    /// Time since the session was last used.
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.get().elapsed()
+    }
+
+    /// Purpose label given at connect time, if any.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Cached capabilities of the auth key, if they could be fetched.
+    pub fn info(&self) -> Option<&SessionInfo> {
+        self.info.as_ref()
+    }
+
+    /// Re-fetch the auth key's info, e.g. after its capabilities were changed elsewhere.
+    pub fn refresh_info(&mut self) -> HsmResult<&SessionInfo> {
+        let info = fetch_session_info(self.active_client()?)?;
+        Ok(self.info.insert(info))
     }
 
     /// Returns true if there is an active authenticated session.
@@ -79,15 +623,227 @@ impl SessionManager {
 
     /// Get a reference to the active client, or an authentication error if none.
     pub fn active_client(&self) -> HsmResult<&HsmClient> {
-        self.active_client.as_ref().ok_or_else(|| {
-            HsmError::AuthenticationFailed(
-                "No active HSM session. Please authenticate first.".into(),
-            )
-        })
+        self.touch();
+        self.active_client.as_deref().ok_or_else(no_session)
+    }
+
+    /// The active client without counting as activity, for reads made while
+    /// rendering; the heartbeat re-renders, so touching here would keep an
+    /// idle session alive forever.
+    pub fn peek_client(&self) -> Option<&HsmClient> {
+        self.active_client.as_deref()
+    }
+
+    /// Shared handle to the active client for work done off the UI thread.
+    pub fn shared_client(&self) -> HsmResult<Arc<HsmClient>> {
+        self.touch();
+        self.active_client.clone().ok_or_else(no_session)
+    }
+
+    /// Shared handle to the active client for the heartbeat. Unlike
+    /// `shared_client` this does not count as activity, so the heartbeat does
+    /// not keep an idle session from timing out.
+    pub fn heartbeat_client(&self) -> Option<Arc<HsmClient>> {
+        self.active_client.clone()
+    }
+
+    /// Health of the session as last seen by the heartbeat.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection
+    }
+
+    pub fn set_connection_state(&mut self, state: ConnectionState) {
+        self.connection = state;
+    }
+
+    /// Run `op` on the active session. If the device reports the session
+    /// expired, it is reopened once with the credentials it was opened with
+    /// and `op` is retried; a second failure is returned as is. Fails if
+    /// there is no session.
+    pub fn with_reconnect<T>(&self, op: impl Fn(&HsmClient) -> HsmResult<T>) -> HsmResult<T> {
+        self.active_client()?.with_session_retry(op)
+    }
+
+    /// Whether `client` is still the active session, i.e. it was not
+    /// disconnected or replaced while a background task was using it.
+    pub fn is_active(&self, client: &Arc<HsmClient>) -> bool {
+        self.active_client
+            .as_ref()
+            .is_some_and(|active| Arc::ptr_eq(active, client))
+    }
+
+    /// Public keys already fetched in this session, for listings to reuse.
+    pub fn public_key_cache(&self) -> Arc<Mutex<PublicKeyCache>> {
+        self.public_keys.clone()
+    }
+
+    /// Forget the cached public key of object `id` of type `ty`, after it was
+    /// deleted or replaced.
+    pub fn forget_public_key(&self, id: Id, ty: Type) {
+        self.public_keys
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .invalidate(id, ty);
     }
 
     /// Disconnect the current session, if any.
     pub fn disconnect(&mut self) {
-        self.active_client = None;
+        if let Some(client) = self.active_client.take() {
+            tracing::info!(
+                auth_key_id = %HexId(client.auth_key_id()),
+                messages = client.message_count(),
+                "session closed"
+            );
+        }
+        self.info = None;
+        self.label = None;
+        self.public_keys = Arc::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hsm::error::device_error;
+    use yubihsm::device::ErrorKind as DeviceErrorKind;
+    use zeroize::Zeroize;
+
+    fn expired() -> HsmError {
+        HsmError::SessionExpired(device_error(DeviceErrorKind::InvalidSession))
+    }
+
+    #[test]
+    fn expired_session_is_renegotiated_and_retried_once() {
+        let attempts = Cell::new(0);
+        let renegotiations = Cell::new(0);
+        let result = retry_expired(
+            || {
+                attempts.set(attempts.get() + 1);
+                if renegotiations.get() == 0 {
+                    Err(expired())
+                } else {
+                    Ok("signed")
+                }
+            },
+            || {
+                renegotiations.set(renegotiations.get() + 1);
+                Ok(())
+            },
+        );
+
+        assert_eq!(result.unwrap(), "signed");
+        assert_eq!(attempts.get(), 2);
+        assert_eq!(renegotiations.get(), 1);
+    }
+
+    #[test]
+    fn second_expiry_is_returned() {
+        let attempts = Cell::new(0);
+        let result: HsmResult<()> = retry_expired(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(expired())
+            },
+            || Ok(()),
+        );
+
+        assert!(result.unwrap_err().is_session_expired());
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[test]
+    fn failed_renegotiation_skips_the_retry() {
+        let attempts = Cell::new(0);
+        let result: HsmResult<()> = retry_expired(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(expired())
+            },
+            || Err(HsmError::AuthenticationFailed("wrong password".into())),
+        );
+
+        assert!(matches!(result, Err(HsmError::AuthenticationFailed(_))));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let attempts = Cell::new(0);
+        let result: HsmResult<()> = retry_expired(
+            || {
+                attempts.set(attempts.get() + 1);
+                Err(HsmError::InvalidInput("empty message".into()))
+            },
+            || panic!("must not renegotiate"),
+        );
+
+        assert!(matches!(result, Err(HsmError::InvalidInput(_))));
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn connect_refuses_empty_password_before_reaching_the_device() {
+        for password in ["", "   ", "\t\n"] {
+            let config = HsmConfig {
+                auth_password: Zeroizing::new(password.to_string()),
+                ..HsmConfig::default()
+            };
+
+            assert!(matches!(
+                HsmClient::connect(config),
+                Err(HsmError::InvalidInput(msg)) if msg.contains("cannot be empty")
+            ));
+        }
+    }
+
+    #[test]
+    fn config_password_is_wiped_on_zeroize() {
+        let mut config = HsmConfig {
+            auth_password: Zeroizing::new("correct horse battery staple".to_string()),
+            ..HsmConfig::default()
+        };
+        // `Zeroizing` runs this on drop, so the password never outlives the config
+        let password: &mut Zeroizing<String> = &mut config.auth_password;
+        password.zeroize();
+
+        assert!(config.auth_password.is_empty());
+    }
+
+    #[test]
+    fn panic_is_returned_as_internal() {
+        let result: HsmResult<()> = retry_expired(|| panic!("boom"), || Ok(()));
+
+        assert!(matches!(result, Err(HsmError::Internal(msg)) if msg == "boom"));
+    }
+
+    #[test]
+    fn reading_session_state_is_not_activity() {
+        let session = SessionManager::new();
+        let idle_since = Instant::now() - Duration::from_secs(600);
+        session.last_activity.set(idle_since);
+
+        assert!(session.peek_client().is_none());
+        assert!(!session.is_authenticated());
+        assert!(session.label().is_none());
+        assert!(session.info().is_none());
+        let _ = session.connection_state();
+
+        assert_eq!(session.last_activity.get(), idle_since);
+        assert!(session.idle_for() >= Duration::from_secs(600));
+    }
+
+    #[test]
+    fn timed_out_connector_is_retried_but_wrong_credentials_are_not() {
+        let retry = ConnectRetry {
+            attempts: 3,
+            base_delay: Duration::from_millis(10),
+        };
+        let timed_out = HsmError::ConnectorTimedOut("http://127.0.0.1:12345".into());
+        let rejected = HsmError::AuthenticationFailed("wrong password".into());
+
+        assert!(retry.should_retry(1, &timed_out));
+        assert!(retry.should_retry(2, &timed_out));
+        assert!(!retry.should_retry(3, &timed_out));
+        assert!(!retry.should_retry(1, &rejected));
     }
 }
@@ -0,0 +1,382 @@
+use super::client::{HsmClient, fetch_session_info};
+use super::error::{HsmError, HsmResult};
+use super::export::write_file;
+use super::operations::{HashAlg, sign, verify};
+use super::sources::RandomSource;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use yubihsm::Algorithm;
+use yubihsm::object::Id;
+
+/// Message signed by the self-test and the benchmark; fixed and non-secret
+const DIAGNOSTICS_MESSAGE: &[u8] = b"hsm-demo diagnostics";
+
+/// Identity of the device a report was produced on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceSummary {
+    pub serial: String,
+    pub firmware: String,
+}
+
+/// What the connected device reports about itself.
+#[derive(Clone, Debug)]
+pub struct DeviceInfo {
+    pub serial: String,
+    pub firmware: String,
+    /// Algorithms the firmware supports
+    pub algorithms: Vec<Algorithm>,
+    /// Audit log entries the device can hold
+    pub log_capacity: u8,
+    /// Audit log entries currently in use
+    pub log_used: u8,
+}
+
+/// Object storage left on the device. Every object takes one record and as
+/// many pages as its size needs.
+#[derive(Clone, Copy, Debug)]
+pub struct StorageInfo {
+    pub total_records: u16,
+    pub free_records: u16,
+    pub total_pages: u16,
+    pub free_pages: u16,
+    /// Bytes per page
+    pub page_size: u16,
+}
+
+/// Outcome of one self-test step.
+#[derive(Clone, Debug, Serialize)]
+pub struct SelfTestStep {
+    pub name: &'static str,
+    pub passed: bool,
+    pub duration_ms: f64,
+    /// Error message or short, non-secret result description
+    pub detail: String,
+}
+
+/// Result of `self_test`. Contains no key material or random bytes.
+#[derive(Clone, Debug, Serialize)]
+pub struct SelfTestReport {
+    /// RFC3339 UTC time the test started
+    pub started_at: String,
+    pub device: Option<DeviceSummary>,
+    pub steps: Vec<SelfTestStep>,
+    pub passed: bool,
+}
+
+/// Result of `bench_sign`. Timings only; signatures are discarded.
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchReport {
+    /// RFC3339 UTC time the benchmark started
+    pub started_at: String,
+    pub device: Option<DeviceSummary>,
+    pub key_id: Id,
+    pub iterations: usize,
+    /// Signatures that completed; less than `iterations` if one failed
+    pub completed: usize,
+    pub total_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub mean_ms: f64,
+    pub signs_per_sec: f64,
+    /// Per-signature timings in order
+    pub samples_ms: Vec<f64>,
+    /// Error that stopped the run early, if any
+    pub error: Option<String>,
+    pub passed: bool,
+}
+
+/// Either kind of diagnostics report, tagged so exported JSON is self-describing.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DiagnosticsReport {
+    SelfTest(SelfTestReport),
+    Benchmark(BenchReport),
+}
+
+fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Serial number, firmware version, supported algorithms and audit log usage
+/// of the connected device.
+pub fn device_info(client: &HsmClient) -> HsmResult<DeviceInfo> {
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ListingFailed(format!("Failed to lock client: {}", e)))?;
+
+    let info = hsm.device_info().map_err(|e| {
+        HsmError::from_client(e, |msg| {
+            HsmError::ListingFailed(format!("Failed to get device info: {}", msg))
+        })
+    })?;
+
+    Ok(DeviceInfo {
+        serial: info.serial_number.to_string(),
+        firmware: format!(
+            "{}.{}.{}",
+            info.major_version, info.minor_version, info.build_version
+        ),
+        algorithms: info.algorithms,
+        log_capacity: info.log_store_capacity,
+        log_used: info.log_store_used,
+    })
+}
+
+/// Free and total object records and storage pages of the connected device.
+pub fn storage_info(client: &HsmClient) -> HsmResult<StorageInfo> {
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ListingFailed(format!("Failed to lock client: {}", e)))?;
+
+    let info = hsm.get_storage_info().map_err(|e| {
+        HsmError::from_client(e, |msg| {
+            HsmError::ListingFailed(format!("Failed to get storage info: {}", msg))
+        })
+    })?;
+
+    Ok(StorageInfo {
+        total_records: info.total_records,
+        free_records: info.free_records,
+        total_pages: info.total_pages,
+        free_pages: info.free_pages,
+        page_size: info.page_size,
+    })
+}
+
+/// Serial number and firmware version of the connected device.
+pub fn device_summary(client: &HsmClient) -> HsmResult<DeviceSummary> {
+    device_info(client).map(|info| DeviceSummary {
+        serial: info.serial,
+        firmware: info.firmware,
+    })
+}
+
+/// Factory-reset the device: every object except the default auth key is
+/// erased and the audit log cleared. The session is gone afterwards, so the
+/// caller must drop `client` and authenticate again. Never retried, since a
+/// second attempt would run against a fresh device.
+pub fn reset_device(client: &HsmClient) -> HsmResult<()> {
+    client.ensure_destructive_allowed()?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ResetFailed(format!("Failed to lock client: {}", e)))?;
+
+    let result = hsm
+        .reset_device()
+        .map_err(|e| HsmError::from_client(e, HsmError::ResetFailed));
+    // Release the lock before the caller tears the session down
+    drop(hsm);
+    result
+}
+
+/// Run a series of non-destructive checks against the device: device info, echo,
+/// random generation, auth key lookup and a sign/verify round trip with `key_id`.
+/// Step failures are recorded in the report; only a lost device aborts the run.
+/// `rng` is normally `DeviceRandom` over the same client.
+pub fn self_test(
+    client: &HsmClient,
+    key_id: Id,
+    rng: &dyn RandomSource,
+) -> HsmResult<SelfTestReport> {
+    let started_at = now_rfc3339();
+    let mut steps = Vec::new();
+
+    let mut run = |name: &'static str, step: &dyn Fn() -> HsmResult<String>| -> HsmResult<()> {
+        let started = Instant::now();
+        let result = step();
+        let duration_ms = millis(started.elapsed());
+
+        match result {
+            Err(e) if e.is_device_disconnected() => Err(e),
+            result => {
+                let passed = result.is_ok();
+                steps.push(SelfTestStep {
+                    name,
+                    passed,
+                    duration_ms,
+                    detail: result.unwrap_or_else(|e| e.to_string()),
+                });
+                Ok(())
+            }
+        }
+    };
+
+    run("device_info", &|| {
+        device_summary(client).map(|device| format!("firmware {}", device.firmware))
+    })?;
+    run("echo", &|| {
+        let hsm_client = client.client();
+        let hsm = hsm_client
+            .lock()
+            .map_err(|e| HsmError::ListingFailed(format!("Failed to lock client: {}", e)))?;
+        let echoed = hsm
+            .echo(DIAGNOSTICS_MESSAGE)
+            .map_err(|e| HsmError::from_client(e, HsmError::ListingFailed))?;
+        if echoed == DIAGNOSTICS_MESSAGE {
+            Ok("device echoed the message unchanged".to_string())
+        } else {
+            Err(HsmError::ListingFailed(
+                "echo returned different bytes".to_string(),
+            ))
+        }
+    })?;
+    run("random", &|| check_random(rng))?;
+    run("auth_key_info", &|| {
+        fetch_session_info(client).map(|info| format!("auth key 0x{:04x}", info.auth_key_id))
+    })?;
+    run("sign_verify", &|| {
+        let signature = sign(client, key_id, DIAGNOSTICS_MESSAGE, HashAlg::Sha256)?;
+        if verify(
+            client,
+            key_id,
+            DIAGNOSTICS_MESSAGE,
+            &signature,
+            HashAlg::Sha256,
+        )? {
+            Ok(format!("key 0x{:04x} signature verified", key_id))
+        } else {
+            Err(HsmError::VerificationFailed(format!(
+                "signature by key 0x{:04x} did not verify",
+                key_id
+            )))
+        }
+    })?;
+
+    let device = device_summary(client).ok();
+    let passed = steps.iter().all(|step| step.passed);
+    Ok(SelfTestReport {
+        started_at,
+        device,
+        steps,
+        passed,
+    })
+}
+
+/// Self-test step asking `rng` for 32 bytes. Only the length is reported; the
+/// bytes themselves never leave this function.
+fn check_random(rng: &dyn RandomSource) -> HsmResult<String> {
+    let random = rng.random_bytes(32)?;
+    if random.len() == 32 {
+        Ok("received 32 random bytes".to_string())
+    } else {
+        Err(HsmError::ListingFailed(format!(
+            "expected 32 random bytes, got {}",
+            random.len()
+        )))
+    }
+}
+
+/// Sign a fixed message `iterations` times with `key_id` and record the timings.
+/// Stops at the first failure and reports how far it got.
+pub fn bench_sign(client: &HsmClient, key_id: Id, iterations: usize) -> HsmResult<BenchReport> {
+    if iterations == 0 {
+        return Err(HsmError::InvalidInput(
+            "Benchmark needs at least one iteration".to_string(),
+        ));
+    }
+
+    let started_at = now_rfc3339();
+    let device = device_summary(client).ok();
+
+    let mut samples_ms = Vec::with_capacity(iterations);
+    let mut error = None;
+    let run_started = Instant::now();
+    for _ in 0..iterations {
+        let started = Instant::now();
+        match sign(client, key_id, DIAGNOSTICS_MESSAGE, HashAlg::Sha256) {
+            Ok(_) => samples_ms.push(millis(started.elapsed())),
+            Err(e) if e.is_device_disconnected() => return Err(e),
+            Err(e) => {
+                error = Some(e.to_string());
+                break;
+            }
+        }
+    }
+    let total_ms = millis(run_started.elapsed());
+
+    let completed = samples_ms.len();
+    let min_ms = samples_ms.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_ms = samples_ms.iter().copied().fold(0.0, f64::max);
+    let mean_ms = if completed > 0 {
+        samples_ms.iter().sum::<f64>() / completed as f64
+    } else {
+        0.0
+    };
+
+    Ok(BenchReport {
+        started_at,
+        device,
+        key_id,
+        iterations,
+        completed,
+        total_ms,
+        min_ms: if completed > 0 { min_ms } else { 0.0 },
+        max_ms,
+        mean_ms,
+        signs_per_sec: if total_ms > 0.0 {
+            completed as f64 * 1000.0 / total_ms
+        } else {
+            0.0
+        },
+        samples_ms,
+        passed: error.is_none(),
+        error,
+    })
+}
+
+/// Write a report as pretty-printed JSON to `path`.
+pub fn write_report_json(report: &DiagnosticsReport, path: &Path) -> HsmResult<()> {
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| HsmError::ExportFailed(format!("Failed to serialize report: {}", e)))?;
+    write_file(path, json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hsm::sources::FixedRandom;
+
+    /// Returns fewer bytes than asked for.
+    struct ShortRandom;
+
+    impl RandomSource for ShortRandom {
+        fn random_bytes(&self, len: usize) -> HsmResult<Vec<u8>> {
+            FixedRandom(0).random_bytes(len / 2)
+        }
+    }
+
+    #[test]
+    fn fixed_random_counts_up_from_the_seed() {
+        assert_eq!(
+            FixedRandom(0xfe).random_bytes(4).unwrap(),
+            [0xfe, 0xff, 0x00, 0x01]
+        );
+    }
+
+    #[test]
+    fn random_step_reports_only_the_length() {
+        assert_eq!(
+            check_random(&FixedRandom(7)).unwrap(),
+            "received 32 random bytes"
+        );
+    }
+
+    #[test]
+    fn random_step_fails_on_short_output() {
+        let e = check_random(&ShortRandom).unwrap_err();
+
+        assert!(
+            matches!(e, HsmError::ListingFailed(msg) if msg == "expected 32 random bytes, got 16")
+        );
+    }
+}
@@ -1,4 +1,9 @@
+use super::client::ConnectorKind;
 use std::fmt;
+use yubihsm::asymmetric;
+use yubihsm::client::ErrorKind;
+use yubihsm::device::ErrorKind as DeviceErrorKind;
+use yubihsm::object::{Id, Type};
 
 /// Result type for HSM operations
 pub type HsmResult<T> = Result<T, HsmError>;
@@ -27,8 +32,229 @@ pub enum HsmError {
     /// Failed to get public key
     GetPublicKeyFailed(String),
 
+    /// Failed to read an opaque object back from the device
+    GetOpaqueFailed(String),
+
     /// Failed to delete object
     DeletionFailed(String),
+
+    /// The device refused or failed a factory reset
+    ResetFailed(String),
+
+    /// The password of an authentication key could not be changed
+    PasswordChangeFailed(String),
+
+    /// Failed to read the audit log
+    AuditLogFailed(String),
+
+    /// The audit log is full and force-audit is on, so the device refuses
+    /// further commands until entries are acknowledged
+    AuditLogFull(yubihsm::client::Error),
+
+    /// Failed to generate a key on the device
+    GenerationFailed(String),
+
+    /// Failed to import an object into the device
+    ImportFailed(String),
+
+    /// A wrapped blob could not be unwrapped: it was made under another wrap key
+    WrapKeyMismatch(String),
+
+    /// A wrapped blob is truncated or otherwise not a wrapped object
+    CorruptWrappedData(String),
+
+    /// Failed to write exported data to disk
+    ExportFailed(String),
+
+    /// The device went away (unplugged or transport closed) mid-session
+    DeviceDisconnected(yubihsm::client::Error),
+
+    /// The device no longer accepts the session, because it timed out or
+    /// exhausted its message limit; it must be renegotiated
+    SessionExpired(yubihsm::client::Error),
+
+    /// The auth key (or the key used) lacks a capability the command needs
+    InsufficientPermissions(yubihsm::client::Error),
+
+    /// The operation is not permitted in the current mode
+    AccessDenied(String),
+
+    /// The requested connector was left out of this build or cannot be used
+    ConnectorUnavailable(String),
+
+    /// The connector or device did not answer while connecting; trying again
+    /// may succeed
+    ConnectorUnreachable(String),
+
+    /// The connector or device did not answer within the connect timeout
+    ConnectorTimedOut(String),
+
+    /// An operation panicked; the session may be left in an inconsistent state
+    Internal(String),
+
+    /// An object of this type already occupies the ID a create/import targeted
+    ObjectExists {
+        id: Id,
+        ty: Type,
+        source: yubihsm::client::Error,
+    },
+
+    /// No object of this type with this ID is visible to the session
+    ObjectNotFound {
+        id: Id,
+        ty: Type,
+        source: yubihsm::client::Error,
+    },
+
+    /// The key's algorithm cannot be used for the requested operation
+    UnsupportedAlgorithm {
+        key_id: Id,
+        algorithm: asymmetric::Algorithm,
+        operation: &'static str,
+    },
+
+    /// The device refused the command with a response code that has no
+    /// variant of its own
+    Other(yubihsm::client::Error),
+}
+
+impl HsmError {
+    /// Map a yubihsm client error into an `HsmError`. Device response codes
+    /// callers react to get their own variant and any other code becomes
+    /// `Other`, all keeping the original error; a gone transport becomes
+    /// `DeviceDisconnected`, and `wrap` handles every other failure.
+    pub fn from_client(e: yubihsm::client::Error, wrap: impl FnOnce(String) -> HsmError) -> Self {
+        let msg = format!("{:?}", e);
+        if is_transport_error(&e, &msg) {
+            return HsmError::DeviceDisconnected(e);
+        }
+
+        match device_error_kind(&e) {
+            Some(DeviceErrorKind::InvalidSession) => HsmError::SessionExpired(e),
+            Some(DeviceErrorKind::LogFull) => HsmError::AuditLogFull(e),
+            Some(DeviceErrorKind::InsufficientPermissions) => HsmError::InsufficientPermissions(e),
+            // Older connectors only report the limit in the message
+            _ if is_session_limit_error(&msg) => HsmError::SessionExpired(e),
+            Some(_) => HsmError::Other(e),
+            None => wrap(msg),
+        }
+    }
+
+    /// Like `from_client`, for commands on object `id` of type `ty`: a missing
+    /// object becomes `ObjectNotFound`, and a duplicate ID on create or import
+    /// `ObjectExists` so callers can offer alternatives.
+    pub fn from_client_object(
+        e: yubihsm::client::Error,
+        id: Id,
+        ty: Type,
+        wrap: impl FnOnce(String) -> HsmError,
+    ) -> Self {
+        match device_error_kind(&e) {
+            Some(DeviceErrorKind::ObjectNotFound) => HsmError::ObjectNotFound { id, ty, source: e },
+            Some(DeviceErrorKind::ObjectExists) => HsmError::ObjectExists { id, ty, source: e },
+            _ => HsmError::from_client(e, wrap),
+        }
+    }
+
+    /// Map a failure to open a session: a connector or device that does not
+    /// answer in time becomes `ConnectorTimedOut`, one that cannot be reached
+    /// `ConnectorUnreachable`, several devices without a serial
+    /// `ConnectorUnavailable`, and only the rest `AuthenticationFailed`.
+    pub fn from_connect(e: yubihsm::client::Error, connector: &ConnectorKind) -> Self {
+        let msg = format!("{:?}", e);
+        if matches!(connector, ConnectorKind::Usb { serial: None })
+            && msg.to_ascii_lowercase().contains("multiple")
+        {
+            HsmError::ConnectorUnavailable(format!(
+                "several YubiHSM2 devices are attached; set the serial number of the one to use ({})",
+                msg
+            ))
+        } else if is_timeout_error(&msg) {
+            HsmError::ConnectorTimedOut(format!("{}: {}", connector, msg))
+        } else if is_transport_error(&e, &msg) || is_unreachable_error(&msg) {
+            HsmError::ConnectorUnreachable(format!("{}: {}", connector, msg))
+        } else {
+            HsmError::AuthenticationFailed(msg)
+        }
+    }
+
+    /// Returns true if the error means the device is no longer reachable.
+    pub fn is_device_disconnected(&self) -> bool {
+        matches!(self, HsmError::DeviceDisconnected(_))
+    }
+
+    /// Returns true if connecting failed for a reason that may go away on its
+    /// own, such as an unreachable or timed-out connector, as opposed to wrong
+    /// credentials or a missing connector.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            HsmError::ConnectorUnreachable(_) | HsmError::ConnectorTimedOut(_)
+        )
+    }
+
+    /// Returns true if the session must be renegotiated before retrying.
+    pub fn is_session_expired(&self) -> bool {
+        matches!(self, HsmError::SessionExpired(_))
+    }
+}
+
+/// Response code the device answered a command with, if it got that far.
+pub fn device_error_kind(e: &yubihsm::client::Error) -> Option<DeviceErrorKind> {
+    match e.kind() {
+        ErrorKind::DeviceError { kind } => Some(kind),
+        _ => None,
+    }
+}
+
+/// A client error carrying the response code `kind`, as if the device had
+/// answered a command with it.
+#[cfg(test)]
+pub(crate) fn device_error(kind: DeviceErrorKind) -> yubihsm::client::Error {
+    ErrorKind::DeviceError { kind }.into()
+}
+
+/// Transport failures surface either as a closed session or as USB/IO errors
+/// bubbled up from the connector, which the client only exposes in its message.
+fn is_transport_error(e: &yubihsm::client::Error, msg: &str) -> bool {
+    if matches!(e.kind(), ErrorKind::ClosedError) {
+        return true;
+    }
+
+    const MARKERS: &[&str] = &[
+        "UsbError",
+        "IoError",
+        "NoDevice",
+        "NO_DEVICE",
+        "No such device",
+    ];
+    MARKERS.iter().any(|marker| msg.contains(marker))
+}
+
+/// Connection attempts that never reached the device, e.g. nothing listening
+/// at the connector address or a USB device held by another process.
+fn is_unreachable_error(msg: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "ConnectionFailed",
+        "AddrInvalid",
+        "DeviceBusy",
+        "Connection refused",
+    ];
+    MARKERS.iter().any(|marker| msg.contains(marker))
+}
+
+/// Connection attempts the connector or device left unanswered until the
+/// configured timeout ran out.
+fn is_timeout_error(msg: &str) -> bool {
+    const MARKERS: &[&str] = &["Timeout", "timed out", "TimedOut"];
+    MARKERS.iter().any(|marker| msg.contains(marker))
+}
+
+/// Once a session's message counter is exhausted the device stops accepting
+/// commands on it, which some connectors report without a response code.
+fn is_session_limit_error(msg: &str) -> bool {
+    const MARKERS: &[&str] = &["SessionLimit", "MessageLimit"];
+    MARKERS.iter().any(|marker| msg.contains(marker))
 }
 
 impl fmt::Display for HsmError {
@@ -41,9 +267,148 @@ impl fmt::Display for HsmError {
             HsmError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
             HsmError::ListingFailed(msg) => write!(f, "Listing failed: {}", msg),
             HsmError::GetPublicKeyFailed(msg) => write!(f, "Failed to get public key: {}", msg),
+            HsmError::GetOpaqueFailed(msg) => write!(f, "Failed to read opaque object: {}", msg),
             HsmError::DeletionFailed(msg) => write!(f, "Deletion failed: {}", msg),
+            HsmError::ResetFailed(msg) => write!(f, "Device reset failed: {}", msg),
+            HsmError::PasswordChangeFailed(msg) => write!(f, "Password change failed: {}", msg),
+            HsmError::AuditLogFailed(msg) => write!(f, "Audit log read failed: {}", msg),
+            HsmError::AuditLogFull(msg) => write!(
+                f,
+                "Audit log full: the device refuses commands until entries are acknowledged: {}",
+                msg
+            ),
+            HsmError::GenerationFailed(msg) => write!(f, "Key generation failed: {}", msg),
+            HsmError::ImportFailed(msg) => write!(f, "Import failed: {}", msg),
+            HsmError::WrapKeyMismatch(msg) => write!(
+                f,
+                "The wrapped object does not open under this wrap key; import it with the key it was exported under: {}",
+                msg
+            ),
+            HsmError::CorruptWrappedData(msg) => {
+                write!(f, "The file is not a valid wrapped object: {}", msg)
+            }
+            HsmError::ExportFailed(msg) => write!(f, "Export failed: {}", msg),
+            HsmError::DeviceDisconnected(msg) => write!(f, "Device disconnected: {}", msg),
+            HsmError::SessionExpired(msg) => {
+                write!(f, "Session expired or hit its message limit: {}", msg)
+            }
+            HsmError::InsufficientPermissions(msg) => write!(
+                f,
+                "Insufficient permissions: the key lacks a capability this command needs: {}",
+                msg
+            ),
+            HsmError::AccessDenied(msg) => write!(f, "Access denied: {}", msg),
+            HsmError::ConnectorUnavailable(msg) => write!(f, "Connector unavailable: {}", msg),
+            HsmError::ConnectorUnreachable(msg) => write!(f, "Cannot reach {}", msg),
+            HsmError::ConnectorTimedOut(msg) => write!(f, "Connector timed out: {}", msg),
+            HsmError::ObjectExists { id, ty, .. } => {
+                write!(f, "A {:?} already exists with ID 0x{:04x}", ty, id)
+            }
+            HsmError::ObjectNotFound { id, ty, .. } => {
+                write!(
+                    f,
+                    "No {:?} with ID 0x{:04x} is visible to this session",
+                    ty, id
+                )
+            }
+            HsmError::UnsupportedAlgorithm {
+                key_id,
+                algorithm,
+                operation,
+            } => write!(
+                f,
+                "Unsupported algorithm: key 0x{:04x} is {:?}, which cannot be used for {}",
+                key_id, algorithm, operation
+            ),
+            HsmError::Internal(msg) => write!(
+                f,
+                "An internal error occurred; the session may need reconnecting: {}",
+                msg
+            ),
+            HsmError::Other(e) => write!(f, "The device refused the command: {}", e),
         }
     }
 }
 
-impl std::error::Error for HsmError {}
+impl std::error::Error for HsmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HsmError::DeviceDisconnected(e)
+            | HsmError::SessionExpired(e)
+            | HsmError::InsufficientPermissions(e)
+            | HsmError::AuditLogFull(e)
+            | HsmError::ObjectExists { source: e, .. }
+            | HsmError::ObjectNotFound { source: e, .. }
+            | HsmError::Other(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    fn wrap_unused(msg: String) -> HsmError {
+        panic!("device response codes must not reach `wrap`: {}", msg)
+    }
+
+    #[test]
+    fn known_response_codes_keep_the_device_error() {
+        let e = HsmError::from_client(device_error(DeviceErrorKind::InvalidSession), wrap_unused);
+        assert!(e.is_session_expired());
+        assert!(matches!(
+            e,
+            HsmError::SessionExpired(ref source)
+                if device_error_kind(source) == Some(DeviceErrorKind::InvalidSession)
+        ));
+
+        let e = HsmError::from_client(
+            device_error(DeviceErrorKind::InsufficientPermissions),
+            wrap_unused,
+        );
+        assert!(matches!(e, HsmError::InsufficientPermissions(_)));
+        assert!(e.source().is_some());
+    }
+
+    #[test]
+    fn object_codes_name_the_object_and_keep_the_device_error() {
+        let e = HsmError::from_client_object(
+            device_error(DeviceErrorKind::ObjectExists),
+            0x0010,
+            Type::Opaque,
+            wrap_unused,
+        );
+        match e {
+            HsmError::ObjectExists { id, ty, ref source } => {
+                assert_eq!((id, ty), (0x0010, Type::Opaque));
+                assert_eq!(
+                    device_error_kind(source),
+                    Some(DeviceErrorKind::ObjectExists)
+                );
+            }
+            other => panic!("expected ObjectExists, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn other_response_codes_fall_back_to_other() {
+        let e = HsmError::from_client(device_error(DeviceErrorKind::InvalidData), wrap_unused);
+        assert!(matches!(
+            e,
+            HsmError::Other(ref source)
+                if device_error_kind(source) == Some(DeviceErrorKind::InvalidData)
+        ));
+        assert!(e.source().is_some());
+    }
+
+    #[test]
+    fn string_variants_have_no_source() {
+        assert!(
+            HsmError::SigningFailed("bad digest".into())
+                .source()
+                .is_none()
+        );
+    }
+}
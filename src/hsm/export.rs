@@ -0,0 +1,223 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult};
+use super::names::{capability_names, domain_numbers};
+use super::operations::{ObjectSummary, get_object_info, get_public_key, to_der};
+use super::spki::{public_key_fingerprint, public_key_pem};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use yubihsm::object::{Id, Info, Type};
+
+/// File names written by `export_for_openssl`
+pub const OPENSSL_MESSAGE_FILE: &str = "message";
+pub const OPENSSL_SIGNATURE_FILE: &str = "sig.der";
+pub const OPENSSL_PUBLIC_KEY_FILE: &str = "pub.pem";
+pub const OPENSSL_README_FILE: &str = "README.txt";
+
+/// Write `contents` to `path`, mapping IO errors to `ExportFailed`.
+pub(crate) fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> HsmResult<()> {
+    fs::write(path, contents)
+        .map_err(|e| HsmError::ExportFailed(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Write `objects` to `path` as CSV with a header row: ID as 0x hex, type,
+/// algorithm, label, sequence and the full public key hex (empty for objects
+/// without one). Labels are quoted as needed. Returns the number of rows.
+pub fn write_objects_csv<'a>(
+    objects: impl IntoIterator<Item = &'a ObjectSummary>,
+    path: &Path,
+) -> HsmResult<usize> {
+    let failed = |e: csv::Error| {
+        HsmError::ExportFailed(format!("Failed to write {}: {}", path.display(), e))
+    };
+
+    let mut writer = csv::Writer::from_path(path).map_err(failed)?;
+    writer
+        .write_record([
+            "id",
+            "type",
+            "algorithm",
+            "label",
+            "sequence",
+            "public_key_hex",
+        ])
+        .map_err(failed)?;
+
+    let mut rows = 0;
+    for object in objects {
+        writer
+            .write_record([
+                format!("0x{:04x}", object.object_id),
+                format!("{:?}", object.object_type),
+                format!("{:?}", object.algorithm),
+                object.label.to_string(),
+                object.sequence.to_string(),
+                object.public_key_hex.clone().unwrap_or_default(),
+            ])
+            .map_err(failed)?;
+        rows += 1;
+    }
+
+    writer.flush().map_err(|e| {
+        HsmError::ExportFailed(format!("Failed to write {}: {}", path.display(), e))
+    })?;
+    Ok(rows)
+}
+
+/// Metadata of one object as exported to JSON. IDs are 0x hex, capabilities
+/// use their yubihsm-shell names and enum-valued fields are stored by name, so
+/// exports are readable and diff cleanly across device states.
+#[derive(Clone, Debug, Serialize)]
+pub struct ObjectJson {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub algorithm: String,
+    pub label: String,
+    pub sequence: u8,
+    pub origin: String,
+    pub capabilities: Vec<&'static str>,
+    pub delegated_capabilities: Vec<&'static str>,
+    /// Domain numbers (1-16)
+    pub domains: Vec<String>,
+    /// Public key for asymmetric keys, as listed
+    pub public_key_hex: Option<String>,
+    /// Hex SHA-256 of the SPKI public key for asymmetric keys
+    pub fingerprint: Option<String>,
+}
+
+impl From<&Info> for ObjectJson {
+    fn from(info: &Info) -> Self {
+        Self {
+            id: format!("0x{:04x}", info.object_id),
+            object_type: format!("{:?}", info.object_type),
+            algorithm: format!("{:?}", info.algorithm),
+            label: info.label.to_string(),
+            sequence: info.sequence,
+            origin: format!("{:?}", info.origin),
+            capabilities: capability_names(info.capabilities),
+            delegated_capabilities: capability_names(info.delegated_capabilities),
+            domains: domain_numbers(info.domains)
+                .iter()
+                .map(|number| number.to_string())
+                .collect(),
+            public_key_hex: None,
+            fingerprint: None,
+        }
+    }
+}
+
+impl From<&ObjectSummary> for ObjectJson {
+    fn from(summary: &ObjectSummary) -> Self {
+        Self {
+            id: format!("0x{:04x}", summary.object_id),
+            object_type: format!("{:?}", summary.object_type),
+            algorithm: format!("{:?}", summary.algorithm),
+            label: summary.label.to_string(),
+            sequence: summary.sequence,
+            origin: format!("{:?}", summary.origin),
+            capabilities: capability_names(summary.capabilities),
+            delegated_capabilities: capability_names(summary.delegated_capabilities),
+            domains: domain_numbers(summary.domains)
+                .iter()
+                .map(|number| number.to_string())
+                .collect(),
+            public_key_hex: summary.public_key_hex.clone(),
+            fingerprint: summary.fingerprint_hex.clone(),
+        }
+    }
+}
+
+fn to_json(value: &impl Serialize) -> HsmResult<String> {
+    serde_json::to_string_pretty(value)
+        .map_err(|e| HsmError::ExportFailed(format!("Failed to serialize objects: {}", e)))
+}
+
+/// Current metadata of one object as pretty-printed JSON, fetched from the
+/// device; asymmetric keys include their public key and fingerprint.
+pub fn object_info_json(client: &HsmClient, object_id: Id, object_type: Type) -> HsmResult<String> {
+    let info = get_object_info(client, object_id, object_type)?;
+    let mut object = ObjectJson::from(&info);
+    if object_type == Type::AsymmetricKey {
+        let public_key = get_public_key(client, object_id)?;
+        object.public_key_hex = Some(hex::encode(&public_key.bytes));
+        object.fingerprint = public_key_fingerprint(&public_key).ok();
+    }
+    to_json(&object)
+}
+
+/// Write `objects` to `path` as a pretty-printed JSON array in the shape of
+/// `object_info_json`. Returns the number of objects written.
+pub fn write_objects_json<'a>(
+    objects: impl IntoIterator<Item = &'a ObjectSummary>,
+    path: &Path,
+) -> HsmResult<usize> {
+    let objects: Vec<ObjectJson> = objects.into_iter().map(ObjectJson::from).collect();
+    write_file(path, to_json(&objects)?)?;
+    Ok(objects.len())
+}
+
+/// Read `path`, mapping IO errors and an empty file to `InvalidInput`.
+pub(crate) fn read_file(path: &Path) -> HsmResult<Vec<u8>> {
+    let contents = fs::read(path)
+        .map_err(|e| HsmError::InvalidInput(format!("Failed to read {}: {}", path.display(), e)))?;
+    if contents.is_empty() {
+        return Err(HsmError::InvalidInput(format!(
+            "{} is empty",
+            path.display()
+        )));
+    }
+    Ok(contents)
+}
+
+/// Read a signature file produced elsewhere. Fails with `InvalidInput` if the
+/// file cannot be read or is empty; the encoding is left to the verifier.
+pub fn read_signature(path: &Path) -> HsmResult<Vec<u8>> {
+    read_file(path)
+}
+
+/// PEM `PUBLIC KEY` (SubjectPublicKeyInfo) of the asymmetric key `key_id`.
+/// Fails with `InvalidKey` if the device returns a key whose length does not
+/// fit its algorithm.
+pub fn export_public_key_pem(client: &HsmClient, key_id: u16) -> HsmResult<String> {
+    public_key_pem(&get_public_key(client, key_id)?)
+}
+
+/// Write the signed message, the DER signature and the PEM public key of
+/// `key_id` into `dir`, plus a README with the `openssl dgst` command that
+/// verifies them. Returns the verification command.
+pub fn export_for_openssl(
+    client: &HsmClient,
+    key_id: u16,
+    message: &[u8],
+    signature: &[u8],
+    dir: &Path,
+) -> HsmResult<String> {
+    let public_key = get_public_key(client, key_id)?;
+    // Normalize to DER in case the signature was provided as raw r || s
+    let signature_der = to_der(signature, public_key.algorithm)?;
+    let pem = public_key_pem(&public_key)?;
+
+    fs::create_dir_all(dir).map_err(|e| {
+        HsmError::ExportFailed(format!("Failed to create {}: {}", dir.display(), e))
+    })?;
+
+    let path = |name: &str| -> PathBuf { dir.join(name) };
+    write_file(&path(OPENSSL_MESSAGE_FILE), message)?;
+    write_file(&path(OPENSSL_SIGNATURE_FILE), &signature_der)?;
+    write_file(&path(OPENSSL_PUBLIC_KEY_FILE), pem)?;
+
+    let command = format!(
+        "openssl dgst -sha256 -verify {} -signature {} {}",
+        OPENSSL_PUBLIC_KEY_FILE, OPENSSL_SIGNATURE_FILE, OPENSSL_MESSAGE_FILE
+    );
+    write_file(
+        &path(OPENSSL_README_FILE),
+        format!(
+            "Signature by YubiHSM2 key 0x{:04x}. Verify from this directory with:\n\n{}\n",
+            key_id, command
+        ),
+    )?;
+
+    Ok(command)
+}
@@ -0,0 +1,61 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult};
+use super::operations::get_object_info;
+use yubihsm::client::ErrorKind;
+use yubihsm::object::{Id, Type};
+
+/// Fail with `InvalidKey` unless `key_id` names an HMAC key visible to the session.
+fn ensure_hmac_key(client: &HsmClient, key_id: Id) -> HsmResult<()> {
+    match get_object_info(client, key_id, Type::HmacKey) {
+        Ok(_) => Ok(()),
+        Err(e) if e.is_device_disconnected() || e.is_session_expired() => Err(e),
+        Err(_) => Err(HsmError::InvalidKey(format!(
+            "0x{:04x} is not an HMAC key; pick one from the HMAC key list",
+            key_id
+        ))),
+    }
+}
+
+/// HMAC tag of `data` computed on the device with HMAC key `key_id`.
+pub fn hmac_sign(client: &HsmClient, key_id: Id, data: &[u8]) -> HsmResult<Vec<u8>> {
+    ensure_hmac_key(client, key_id)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::SigningFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.sign_hmac(key_id, data)
+        .map(|tag| tag.into_vec())
+        .map_err(|e| {
+            HsmError::from_client_object(e, key_id, Type::HmacKey, HsmError::SigningFailed)
+        })
+}
+
+/// Check `tag` against `data` on the device. A mismatch is `Ok(false)`; only
+/// failures to run the check are errors.
+pub fn hmac_verify(client: &HsmClient, key_id: Id, data: &[u8], tag: &[u8]) -> HsmResult<bool> {
+    ensure_hmac_key(client, key_id)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::VerificationFailed(format!("Failed to lock client: {}", e)))?;
+
+    match hsm.verify_hmac(key_id, data, tag.to_vec()) {
+        Ok(()) => Ok(true),
+        // The client reports a tag the device rejected as a response error
+        Err(e)
+            if matches!(e.kind(), ErrorKind::ResponseError)
+                && format!("{:?}", e).contains("verification") =>
+        {
+            Ok(false)
+        }
+        Err(e) => Err(HsmError::from_client_object(
+            e,
+            key_id,
+            Type::HmacKey,
+            HsmError::VerificationFailed,
+        )),
+    }
+}
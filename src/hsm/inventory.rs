@@ -0,0 +1,186 @@
+use super::client::HsmClient;
+use super::diagnostics::{DeviceSummary, device_summary};
+use super::error::{HsmError, HsmResult};
+use super::export::write_file;
+use super::operations::{ObjectSummary, list_object_summaries};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Metadata of one object as recorded in a snapshot. Enum-valued fields are
+/// stored by name so snapshots stay readable and comparable across versions.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotObject {
+    pub object_id: u16,
+    pub object_type: String,
+    pub algorithm: String,
+    pub label: String,
+    pub capabilities: String,
+    /// Hex SHA-256 of the SPKI public key for asymmetric keys
+    pub fingerprint: Option<String>,
+}
+
+impl From<&ObjectSummary> for SnapshotObject {
+    fn from(summary: &ObjectSummary) -> Self {
+        Self {
+            object_id: summary.object_id,
+            object_type: format!("{:?}", summary.object_type),
+            algorithm: format!("{:?}", summary.algorithm),
+            label: summary.label.to_string(),
+            capabilities: format!("{:?}", summary.capabilities),
+            fingerprint: summary.fingerprint_hex.clone(),
+        }
+    }
+}
+
+/// Object inventory of one device at one point in time.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InventorySnapshot {
+    /// RFC3339 UTC time the listing was taken
+    pub taken_at: String,
+    pub device: Option<DeviceSummary>,
+    pub objects: Vec<SnapshotObject>,
+}
+
+/// How an object differs between a snapshot and the current device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InventoryChange {
+    /// Present on the device, missing from the snapshot
+    Added,
+    /// Present in the snapshot, missing from the device
+    Removed,
+    /// Present in both with different metadata or public key
+    Changed,
+}
+
+impl InventoryChange {
+    pub fn label(self) -> &'static str {
+        match self {
+            InventoryChange::Added => "added",
+            InventoryChange::Removed => "removed",
+            InventoryChange::Changed => "changed",
+        }
+    }
+}
+
+/// One row of an inventory diff.
+#[derive(Clone, Debug)]
+pub struct InventoryDiffEntry {
+    pub change: InventoryChange,
+    pub object_id: u16,
+    pub object_type: String,
+    pub label: String,
+    pub snapshot_fingerprint: Option<String>,
+    pub device_fingerprint: Option<String>,
+    /// Fields that differ, for `Changed` entries
+    pub differences: Vec<&'static str>,
+}
+
+/// List every object visible to the session and record it as a snapshot.
+pub fn take_snapshot(client: &HsmClient) -> HsmResult<InventorySnapshot> {
+    let taken_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let objects = list_object_summaries(client, &[])?
+        .iter()
+        .map(SnapshotObject::from)
+        .collect();
+
+    Ok(InventorySnapshot {
+        taken_at,
+        device: device_summary(client).ok(),
+        objects,
+    })
+}
+
+/// Write a snapshot as pretty-printed JSON to `path`.
+pub fn write_snapshot(snapshot: &InventorySnapshot, path: &Path) -> HsmResult<()> {
+    let json = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| HsmError::ExportFailed(format!("Failed to serialize snapshot: {}", e)))?;
+    write_file(path, json)
+}
+
+/// Read a snapshot written by `write_snapshot`.
+pub fn read_snapshot(path: &Path) -> HsmResult<InventorySnapshot> {
+    let json = fs::read_to_string(path)
+        .map_err(|e| HsmError::InvalidInput(format!("Failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_str(&json).map_err(|e| {
+        HsmError::InvalidInput(format!(
+            "{} is not an inventory snapshot: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+/// Compare `snapshot` against `current`, matching objects by ID and type.
+/// Sequence numbers are ignored since they differ between devices by design.
+pub fn diff_inventory(
+    snapshot: &InventorySnapshot,
+    current: &InventorySnapshot,
+) -> Vec<InventoryDiffEntry> {
+    let index = |snapshot: &InventorySnapshot| {
+        snapshot
+            .objects
+            .iter()
+            .map(|object| {
+                (
+                    (object.object_id, object.object_type.clone()),
+                    object.clone(),
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    let mut expected = index(snapshot);
+    let actual = index(current);
+
+    let mut diff = Vec::new();
+    for (key, device) in actual {
+        let Some(recorded) = expected.remove(&key) else {
+            diff.push(InventoryDiffEntry {
+                change: InventoryChange::Added,
+                object_id: device.object_id,
+                object_type: device.object_type,
+                label: device.label,
+                snapshot_fingerprint: None,
+                device_fingerprint: device.fingerprint,
+                differences: Vec::new(),
+            });
+            continue;
+        };
+
+        let differences: Vec<&'static str> = [
+            ("algorithm", recorded.algorithm != device.algorithm),
+            ("label", recorded.label != device.label),
+            ("capabilities", recorded.capabilities != device.capabilities),
+            ("fingerprint", recorded.fingerprint != device.fingerprint),
+        ]
+        .into_iter()
+        .filter_map(|(field, differs)| differs.then_some(field))
+        .collect();
+
+        if !differences.is_empty() {
+            diff.push(InventoryDiffEntry {
+                change: InventoryChange::Changed,
+                object_id: device.object_id,
+                object_type: device.object_type,
+                label: device.label,
+                snapshot_fingerprint: recorded.fingerprint,
+                device_fingerprint: device.fingerprint,
+                differences,
+            });
+        }
+    }
+
+    diff.extend(expected.into_values().map(|recorded| InventoryDiffEntry {
+        change: InventoryChange::Removed,
+        object_id: recorded.object_id,
+        object_type: recorded.object_type,
+        label: recorded.label,
+        snapshot_fingerprint: recorded.fingerprint,
+        device_fingerprint: None,
+        differences: Vec::new(),
+    }));
+    diff.sort_by(|a, b| (a.object_id, &a.object_type).cmp(&(b.object_id, &b.object_type)));
+    diff
+}
@@ -0,0 +1,199 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult};
+use super::export::write_file;
+use super::operations::list_object_summaries;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use serde_json::{Value, json};
+use std::path::Path;
+use yubihsm::object::{Filter, Id, Type};
+use yubihsm::{Algorithm, Capability, asymmetric};
+
+/// Public exponent used by YubiHSM2 generated RSA keys
+const RSA_PUBLIC_EXPONENT: &[u8] = &[0x01, 0x00, 0x01];
+
+/// A signing key left out of the JWKS because JOSE has no name for its algorithm.
+#[derive(Clone, Debug)]
+pub struct SkippedKey {
+    pub key_id: Id,
+    pub algorithm: Algorithm,
+}
+
+/// JWKS document of the device's signing keys plus the keys that were skipped.
+#[derive(Clone, Debug)]
+pub struct JwksExport {
+    /// Pretty-printed `{ "keys": [...] }` document
+    pub json: String,
+    /// Number of keys in `json`
+    pub exported: usize,
+    pub skipped: Vec<SkippedKey>,
+}
+
+/// JWK (RFC 7517/7518/8037) for a public key as returned by the HSM, or None if
+/// the algorithm has no JOSE representation (P-224, Brainpool curves).
+fn public_key_jwk(key_id: Id, algorithm: asymmetric::Algorithm, bytes: &[u8]) -> Option<Value> {
+    use asymmetric::Algorithm::*;
+
+    let kid = format!("{:04x}", key_id);
+    let (crv, alg) = match algorithm {
+        EcP256 => ("P-256", "ES256"),
+        EcP384 => ("P-384", "ES384"),
+        EcP521 => ("P-521", "ES512"),
+        EcK256 => ("secp256k1", "ES256K"),
+        Ed25519 => {
+            return Some(json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(bytes),
+                "kid": kid,
+                "use": "sig",
+                "alg": "EdDSA",
+            }));
+        }
+        Rsa2048 | Rsa3072 | Rsa4096 => {
+            // HSM returns only the modulus; the exponent is always 65537
+            return Some(json!({
+                "kty": "RSA",
+                "n": URL_SAFE_NO_PAD.encode(bytes),
+                "e": URL_SAFE_NO_PAD.encode(RSA_PUBLIC_EXPONENT),
+                "kid": kid,
+                "use": "sig",
+            }));
+        }
+        _ => return None,
+    };
+
+    // HSM returns EC points as raw x || y of equal length
+    if bytes.is_empty() || bytes.len() % 2 != 0 {
+        return None;
+    }
+    let (x, y) = bytes.split_at(bytes.len() / 2);
+    Some(json!({
+        "kty": "EC",
+        "crv": crv,
+        "x": URL_SAFE_NO_PAD.encode(x),
+        "y": URL_SAFE_NO_PAD.encode(y),
+        "kid": kid,
+        "use": "sig",
+        "alg": alg,
+    }))
+}
+
+/// Export a JWKS of every asymmetric key with a signing capability, with `kid`
+/// set to the object ID in hex. Keys JOSE cannot describe are listed in `skipped`.
+pub fn export_jwks(client: &HsmClient) -> HsmResult<JwksExport> {
+    let mut keys = Vec::new();
+    let mut skipped = Vec::new();
+
+    for summary in list_object_summaries(client, &[Filter::Type(Type::AsymmetricKey)])? {
+        if !summary.capabilities.intersects(
+            Capability::SIGN_ECDSA
+                | Capability::SIGN_EDDSA
+                | Capability::SIGN_PKCS
+                | Capability::SIGN_PSS,
+        ) {
+            continue;
+        }
+
+        let jwk = match (summary.algorithm, summary.public_key_hex.as_deref()) {
+            (Algorithm::Asymmetric(algorithm), Some(public_key_hex)) => hex::decode(public_key_hex)
+                .ok()
+                .and_then(|bytes| public_key_jwk(summary.object_id, algorithm, &bytes)),
+            _ => None,
+        };
+        match jwk {
+            Some(jwk) => keys.push(jwk),
+            None => skipped.push(SkippedKey {
+                key_id: summary.object_id,
+                algorithm: summary.algorithm,
+            }),
+        }
+    }
+
+    Ok(JwksExport {
+        json: jwks_document(&keys)?,
+        exported: keys.len(),
+        skipped,
+    })
+}
+
+/// Pretty-printed JWKS document (`{ "keys": [...] }`) holding `keys`.
+fn jwks_document(keys: &[Value]) -> HsmResult<String> {
+    serde_json::to_string_pretty(&json!({ "keys": keys }))
+        .map_err(|e| HsmError::ExportFailed(format!("Failed to serialize JWKS: {}", e)))
+}
+
+/// Write the JWKS document of `export` to `path`.
+pub fn write_jwks(export: &JwksExport, path: &Path) -> HsmResult<()> {
+    write_file(path, &export.json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Device-style `x || y` public key of a fixed secret on the curve of `$curve`.
+    macro_rules! ec_public_key {
+        ($curve:ident, $scalar_len:expr) => {{
+            use $curve::elliptic_curve::sec1::ToEncodedPoint;
+            let public = $curve::SecretKey::from_slice(&[0x42; $scalar_len])
+                .unwrap()
+                .public_key();
+            (
+                public,
+                public.to_encoded_point(false).as_bytes()[1..].to_vec(),
+            )
+        }};
+    }
+
+    #[test]
+    fn jwks_round_trips_through_the_curve_libraries() {
+        let (p256_key, p256_bytes) = ec_public_key!(p256, 32);
+        let (p384_key, p384_bytes) = ec_public_key!(p384, 48);
+        let (k256_key, k256_bytes) = ec_public_key!(k256, 32);
+        let ed25519_key = ed25519_dalek::SigningKey::from_bytes(&[0x42; 32]).verifying_key();
+
+        let keys = [
+            (0x0001, asymmetric::Algorithm::EcP256, p256_bytes),
+            (0x0002, asymmetric::Algorithm::EcP384, p384_bytes),
+            (0x0003, asymmetric::Algorithm::EcK256, k256_bytes),
+            (
+                0x0004,
+                asymmetric::Algorithm::Ed25519,
+                ed25519_key.to_bytes().to_vec(),
+            ),
+        ]
+        .map(|(key_id, algorithm, bytes)| public_key_jwk(key_id, algorithm, &bytes).unwrap());
+        let document: Value = serde_json::from_str(&jwks_document(&keys).unwrap()).unwrap();
+
+        let jwks = document["keys"].as_array().unwrap();
+        assert_eq!(jwks.len(), 4);
+        for (jwk, kid) in jwks.iter().zip(["0001", "0002", "0003", "0004"]) {
+            assert_eq!(jwk["kid"], kid);
+            assert_eq!(jwk["use"], "sig");
+        }
+        let text = |jwk: &Value| jwk.to_string();
+        assert_eq!(
+            p256::PublicKey::from_jwk_str(&text(&jwks[0])).unwrap(),
+            p256_key
+        );
+        assert_eq!(
+            p384::PublicKey::from_jwk_str(&text(&jwks[1])).unwrap(),
+            p384_key
+        );
+        assert_eq!(
+            k256::PublicKey::from_jwk_str(&text(&jwks[2])).unwrap(),
+            k256_key
+        );
+        assert_eq!(jwks[3]["kty"], "OKP");
+        let x = URL_SAFE_NO_PAD
+            .decode(jwks[3]["x"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(x, ed25519_key.to_bytes());
+    }
+
+    #[test]
+    fn curves_without_a_jose_name_are_left_out() {
+        assert!(public_key_jwk(0x0005, asymmetric::Algorithm::EcP224, &[0x04; 56]).is_none());
+        assert!(public_key_jwk(0x0006, asymmetric::Algorithm::EcBp256, &[0x04; 64]).is_none());
+    }
+}
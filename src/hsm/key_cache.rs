@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use yubihsm::object::{Id, SequenceId, Type};
+
+/// Public key of a listed object, as the listing shows it.
+#[derive(Clone, Debug)]
+pub struct CachedPublicKey {
+    pub public_key_hex: String,
+    pub fingerprint_hex: Option<String>,
+}
+
+/// Public keys fetched by earlier listings. Entries are keyed by object ID,
+/// type and sequence: a key deleted and regenerated at the same ID gets a new
+/// sequence number, so it misses instead of showing the old key.
+#[derive(Debug, Default)]
+pub struct PublicKeyCache {
+    entries: HashMap<(Id, Type, SequenceId), CachedPublicKey>,
+}
+
+impl PublicKeyCache {
+    pub fn get(&self, id: Id, ty: Type, sequence: SequenceId) -> Option<&CachedPublicKey> {
+        self.entries.get(&(id, ty, sequence))
+    }
+
+    pub fn insert(&mut self, id: Id, ty: Type, sequence: SequenceId, key: CachedPublicKey) {
+        self.entries.insert((id, ty, sequence), key);
+    }
+
+    /// Drop whatever is cached for object `id` of type `ty`, at any sequence.
+    pub fn invalidate(&mut self, id: Id, ty: Type) {
+        self.entries
+            .retain(|(cached_id, cached_ty, _), _| (*cached_id, *cached_ty) != (id, ty));
+    }
+
+    /// Keep only the entries for `listed` objects, so deleted ones do not linger.
+    pub fn retain_listed(&mut self, listed: &[(Id, Type, SequenceId)]) {
+        self.entries.retain(|key, _| listed.contains(key));
+    }
+}
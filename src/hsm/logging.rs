@@ -0,0 +1,57 @@
+use std::fmt;
+use std::sync::OnceLock;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{Registry, reload};
+use yubihsm::object::Id;
+
+/// Levels offered by the log level picker, quietest first
+pub const LOG_LEVELS: &[LevelFilter] = &[
+    LevelFilter::OFF,
+    LevelFilter::ERROR,
+    LevelFilter::WARN,
+    LevelFilter::INFO,
+    LevelFilter::DEBUG,
+    LevelFilter::TRACE,
+];
+
+/// Handle to the installed level filter, so the level can change at runtime
+static LEVEL_HANDLE: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+/// Log to stderr at `level`. Each device operation is a span; when it closes
+/// its duration is logged, so slow USB round trips show up. Spans only record
+/// IDs, types, algorithms, labels and lengths, never passwords or key material.
+/// Calling this again has no effect.
+pub fn init_logging(level: LevelFilter) {
+    let (filter, handle) = reload::Layer::new(level);
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(std::io::stderr)
+                .with_span_events(FmtSpan::CLOSE),
+        )
+        .try_init();
+
+    if installed.is_ok() {
+        let _ = LEVEL_HANDLE.set(handle);
+    }
+}
+
+/// Change the level set by `init_logging`; does nothing if logging is not set up.
+pub fn set_log_level(level: LevelFilter) {
+    if let Some(handle) = LEVEL_HANDLE.get() {
+        // Only fails once the subscriber is gone, when nothing is logged anyway
+        let _ = handle.modify(|filter| *filter = level);
+    }
+}
+
+/// Object ID as it is shown everywhere else, e.g. `0x0001`, for log fields.
+pub(crate) struct HexId(pub Id);
+
+impl fmt::Display for HexId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:04x}", self.0)
+    }
+}
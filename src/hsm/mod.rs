@@ -1,10 +1,72 @@
+pub mod attestation;
+pub mod audit;
 pub mod client;
+pub mod diagnostics;
 pub mod error;
+pub mod export;
+pub mod hmac;
+pub mod inventory;
+pub mod jwks;
+pub mod key_cache;
+pub mod logging;
+pub mod names;
+pub mod offline;
+pub mod opaque;
 pub mod operations;
+pub mod outcome;
+pub mod redaction;
+pub mod sources;
+pub mod spki;
+pub mod wrap;
 
 // Re-export commonly used items
-pub use client::{HsmClient, HsmConfig, SessionManager};
+pub use attestation::{AttestationOutcome, attest_key, sign_attestation_certificate};
+pub use audit::{AuditLogPager, get_audit_log, set_log_index};
+pub use client::{
+    ConnectRetry, ConnectionState, ConnectorKind, DEFAULT_CONNECT_TIMEOUT, DEFAULT_CONNECTOR_ADDR,
+    DEFAULT_CONNECTOR_PORT, HsmClient, HsmConfig, PASSWORD_KDF, PASSWORD_KDF_ITERATIONS,
+    PASSWORD_KDF_OUTPUT_LEN, PASSWORD_KDF_SALT, SessionInfo, SessionManager, change_auth_password,
+    fetch_session_info, list_usb_devices,
+};
+pub use diagnostics::{
+    BenchReport, DeviceInfo, DeviceSummary, DiagnosticsReport, SelfTestReport, StorageInfo,
+    bench_sign, device_info, reset_device, self_test, storage_info, write_report_json,
+};
+pub use export::{
+    export_for_openssl, export_public_key_pem, object_info_json, read_signature, write_objects_csv,
+    write_objects_json,
+};
+pub use hmac::{hmac_sign, hmac_verify};
+pub use inventory::{
+    InventoryChange, InventoryDiffEntry, InventorySnapshot, diff_inventory, read_snapshot,
+    take_snapshot, write_snapshot,
+};
+pub use jwks::{JwksExport, SkippedKey, export_jwks, write_jwks};
+pub use key_cache::PublicKeyCache;
+pub use logging::{LOG_LEVELS, init_logging, set_log_level};
+pub use names::{
+    capability_names, domain_numbers, format_capabilities, format_domains, format_origin,
+    is_imported,
+};
+pub use offline::{Curve, find_recovery_id, recover_public_key, verify_with_curve};
+pub use opaque::{
+    CertificateSummary, MAX_OPAQUE_LEN, certificate_der, get_opaque, parse_certificate, put_opaque,
+};
 pub use operations::{
-    ObjectSummary, delete_object, get_object_info, get_public_key, list_object_summaries,
-    list_objects, sign, verify,
+    HashAlg, Listing, MAX_RANDOM_LEN, ObjectSummary, SignatureBreakdown, compare_signature,
+    delete_object, domain_from_number, ensure_in_domain, generate_asymmetric_key, get_object_info,
+    get_public_key, get_random, list_object_summaries, list_object_summaries_with_progress,
+    list_objects, normalize_low_s, parse_timestamped_payload, resolve_key_by_label, sign,
+    sign_batch, sign_jws, sign_prehashed, timestamped_payload, to_der, to_raw, verify,
+    verify_prehashed,
+};
+pub use outcome::{
+    FileSignOutcome, SignOutcome, SignatureEncoding, TimestampStatus, TimestampedVerifyResult,
+    VerifyOutcome, sign_file, sign_message, verify_message, verify_timestamped,
+};
+pub use redaction::{OperationRecord, RedactionLevel, log_operation};
+pub use sources::{Clock, DeviceRandom, SystemClock};
+pub use wrap::{
+    ImportResult, default_wrap_capabilities, default_wrap_delegated_capabilities, export_wrapped,
+    generate_wrap_key, import_wrapped, put_wrap_key,
 };
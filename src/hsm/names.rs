@@ -0,0 +1,129 @@
+use yubihsm::{Capability, Domain, object::Origin};
+
+/// Every capability with its yubihsm-shell name, in the device's bit order
+const CAPABILITY_NAMES: &[(Capability, &str)] = &[
+    (Capability::GET_OPAQUE, "get-opaque"),
+    (Capability::PUT_OPAQUE, "put-opaque"),
+    (Capability::PUT_AUTHENTICATION_KEY, "put-authentication-key"),
+    (Capability::PUT_ASYMMETRIC_KEY, "put-asymmetric-key"),
+    (
+        Capability::GENERATE_ASYMMETRIC_KEY,
+        "generate-asymmetric-key",
+    ),
+    (Capability::SIGN_PKCS, "sign-pkcs"),
+    (Capability::SIGN_PSS, "sign-pss"),
+    (Capability::SIGN_ECDSA, "sign-ecdsa"),
+    (Capability::SIGN_EDDSA, "sign-eddsa"),
+    (Capability::DECRYPT_PKCS, "decrypt-pkcs"),
+    (Capability::DECRYPT_OAEP, "decrypt-oaep"),
+    (Capability::DERIVE_ECDH, "derive-ecdh"),
+    (Capability::EXPORT_WRAPPED, "export-wrapped"),
+    (Capability::IMPORT_WRAPPED, "import-wrapped"),
+    (Capability::PUT_WRAP_KEY, "put-wrap-key"),
+    (Capability::GENERATE_WRAP_KEY, "generate-wrap-key"),
+    (Capability::EXPORTABLE_UNDER_WRAP, "exportable-under-wrap"),
+    (Capability::SET_OPTION, "set-option"),
+    (Capability::GET_OPTION, "get-option"),
+    (Capability::GET_PSEUDO_RANDOM, "get-pseudo-random"),
+    (Capability::PUT_HMAC_KEY, "put-mac-key"),
+    (Capability::GENERATE_HMAC_KEY, "generate-hmac-key"),
+    (Capability::SIGN_HMAC, "sign-hmac"),
+    (Capability::VERIFY_HMAC, "verify-hmac"),
+    (Capability::GET_LOG_ENTRIES, "get-log-entries"),
+    (Capability::SIGN_SSH_CERTIFICATE, "sign-ssh-certificate"),
+    (Capability::GET_TEMPLATE, "get-template"),
+    (Capability::PUT_TEMPLATE, "put-template"),
+    (Capability::RESET_DEVICE, "reset-device"),
+    (Capability::DECRYPT_OTP, "decrypt-otp"),
+    (Capability::CREATE_OTP_AEAD, "create-otp-aead"),
+    (Capability::RANDOMIZE_OTP_AEAD, "randomize-otp-aead"),
+    (
+        Capability::REWRAP_FROM_OTP_AEAD_KEY,
+        "rewrap-from-otp-aead-key",
+    ),
+    (Capability::REWRAP_TO_OTP_AEAD_KEY, "rewrap-to-otp-aead-key"),
+    (
+        Capability::SIGN_ATTESTATION_CERTIFICATE,
+        "sign-attestation-certificate",
+    ),
+    (Capability::PUT_OTP_AEAD_KEY, "put-otp-aead-key"),
+    (Capability::GENERATE_OTP_AEAD_KEY, "generate-otp-aead-key"),
+    (Capability::WRAP_DATA, "wrap-data"),
+    (Capability::UNWRAP_DATA, "unwrap-data"),
+    (Capability::DELETE_OPAQUE, "delete-opaque"),
+    (
+        Capability::DELETE_AUTHENTICATION_KEY,
+        "delete-authentication-key",
+    ),
+    (Capability::DELETE_ASYMMETRIC_KEY, "delete-asymmetric-key"),
+    (Capability::DELETE_WRAP_KEY, "delete-wrap-key"),
+    (Capability::DELETE_HMAC_KEY, "delete-hmac-key"),
+    (Capability::DELETE_TEMPLATE, "delete-template"),
+    (Capability::DELETE_OTP_AEAD_KEY, "delete-otp-aead-key"),
+    (
+        Capability::CHANGE_AUTHENTICATION_KEY,
+        "change-authentication-key",
+    ),
+];
+
+/// yubihsm-shell names of the capabilities set in `capabilities`.
+pub fn capability_names(capabilities: Capability) -> Vec<&'static str> {
+    CAPABILITY_NAMES
+        .iter()
+        .filter(|(capability, _)| capabilities.contains(*capability))
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Capabilities as a comma-separated list, e.g. "sign-ecdsa, export-wrapped".
+pub fn format_capabilities(capabilities: Capability) -> String {
+    let names = capability_names(capabilities);
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Numbers (1-16) of the domains set in `domains`.
+pub fn domain_numbers(domains: Domain) -> Vec<u8> {
+    (1..=16u8)
+        .filter(|number| domains.bits() & (1 << (number - 1)) != 0)
+        .collect()
+}
+
+/// Domains as e.g. "Domain 1, 3", or "no domains".
+pub fn format_domains(domains: Domain) -> String {
+    let numbers = domain_numbers(domains);
+    if numbers.is_empty() {
+        return "no domains".to_string();
+    }
+
+    let list = numbers
+        .iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("Domain {}", list)
+}
+
+/// Whether the key material came from outside a YubiHSM2 rather than being
+/// generated on one, directly or before it was wrapped.
+pub fn is_imported(origin: Origin) -> bool {
+    matches!(origin, Origin::Imported | Origin::WrappedImported)
+}
+
+/// Origin and exportability as e.g. "Imported, exportable-under-wrap".
+pub fn format_origin(origin: Origin, capabilities: Capability) -> String {
+    let origin = match origin {
+        Origin::Generated => "Generated on-device",
+        Origin::Imported => "Imported",
+        Origin::WrappedGenerated => "Generated on-device, restored from wrap",
+        Origin::WrappedImported => "Imported, restored from wrap",
+    };
+    if capabilities.contains(Capability::EXPORTABLE_UNDER_WRAP) {
+        format!("{}, exportable-under-wrap", origin)
+    } else {
+        origin.to_string()
+    }
+}
@@ -0,0 +1,214 @@
+use super::error::{HsmError, HsmResult};
+// Shared by all curves: p256/p384/k256 re-export the same ecdsa type
+use k256::ecdsa::RecoveryId;
+use sha2::{Digest, Sha256};
+use signature::hazmat::PrehashVerifier;
+
+/// Elliptic curves supported for host-side verification
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// NIST P-256 / secp256r1
+    P256,
+    /// NIST P-384 / secp384r1
+    P384,
+    /// secp256k1
+    K256,
+}
+
+impl Curve {
+    /// Size in bytes of a single field element (x, y, r or s).
+    fn field_len(self) -> usize {
+        match self {
+            Curve::P256 | Curve::K256 => 32,
+            Curve::P384 => 48,
+        }
+    }
+}
+
+/// Normalize a public key to an uncompressed SEC1 point, accepting either the
+/// raw `x || y` form returned by the HSM or an already uncompressed point.
+fn sec1_point(curve: Curve, public_key: &[u8]) -> HsmResult<Vec<u8>> {
+    let raw_len = curve.field_len() * 2;
+
+    if public_key.len() == raw_len {
+        let mut point = Vec::with_capacity(raw_len + 1);
+        point.push(0x04);
+        point.extend_from_slice(public_key);
+        Ok(point)
+    } else if public_key.len() == raw_len + 1 && public_key[0] == 0x04 {
+        Ok(public_key.to_vec())
+    } else {
+        Err(HsmError::InvalidKey(format!(
+            "Public key is {} bytes, which does not match {:?} (expected {} raw or {} SEC1 uncompressed)",
+            public_key.len(),
+            curve,
+            raw_len,
+            raw_len + 1
+        )))
+    }
+}
+
+/// Returns true if `signature` looks DER-encoded rather than raw `r || s` for the curve.
+fn is_der_signature(curve: Curve, signature: &[u8]) -> bool {
+    signature.len() != curve.field_len() * 2 && signature.first() == Some(&0x30)
+}
+
+/// Verify an ECDSA signature over `data` (SHA-256 prehash, as produced by `sign`)
+/// using a caller-supplied public key and curve, without asking the device which
+/// curve the key is on. Accepts DER or raw `r || s` signatures.
+pub fn verify_with_curve(
+    public_key: &[u8],
+    data: &[u8],
+    signature: &[u8],
+    curve: Curve,
+) -> HsmResult<bool> {
+    if data.is_empty() {
+        return Err(HsmError::InvalidInput("Data cannot be empty".to_string()));
+    }
+
+    let point = sec1_point(curve, public_key)?;
+    let digest = Sha256::digest(data);
+    let is_der = is_der_signature(curve, signature);
+
+    let invalid_key = |e: signature::Error| {
+        HsmError::InvalidKey(format!("Invalid {:?} public key: {}", curve, e))
+    };
+    let invalid_sig = |e: signature::Error| {
+        HsmError::InvalidInput(format!("Invalid {:?} signature: {}", curve, e))
+    };
+
+    let verified = match curve {
+        Curve::P256 => {
+            use p256::ecdsa::{Signature, VerifyingKey};
+            let key = VerifyingKey::from_sec1_bytes(&point).map_err(invalid_key)?;
+            let sig = if is_der {
+                Signature::from_der(signature)
+            } else {
+                Signature::from_slice(signature)
+            }
+            .map_err(invalid_sig)?;
+            key.verify_prehash(&digest, &sig).is_ok()
+        }
+        Curve::P384 => {
+            use p384::ecdsa::{Signature, VerifyingKey};
+            let key = VerifyingKey::from_sec1_bytes(&point).map_err(invalid_key)?;
+            let sig = if is_der {
+                Signature::from_der(signature)
+            } else {
+                Signature::from_slice(signature)
+            }
+            .map_err(invalid_sig)?;
+            key.verify_prehash(&digest, &sig).is_ok()
+        }
+        Curve::K256 => {
+            use k256::ecdsa::{Signature, VerifyingKey};
+            let key = VerifyingKey::from_sec1_bytes(&point).map_err(invalid_key)?;
+            let sig = if is_der {
+                Signature::from_der(signature)
+            } else {
+                Signature::from_slice(signature)
+            }
+            .map_err(invalid_sig)?;
+            key.verify_prehash(&digest, &sig).is_ok()
+        }
+    };
+
+    Ok(verified)
+}
+
+/// Recover the signer's public key from a signature over `data` (SHA-256 prehash,
+/// as produced by `sign`) that carries a trailing recovery id: `r || s || v` with
+/// `v` in 0..=3, or 27/28 as used by Ethereum. Returns the uncompressed SEC1 point.
+/// Note Ethereum itself hashes with Keccak-256, so its signatures only recover here
+/// if they were made over a SHA-256 digest.
+pub fn recover_public_key(
+    data: &[u8],
+    signature_with_recid: &[u8],
+    curve: Curve,
+) -> HsmResult<Vec<u8>> {
+    if data.is_empty() {
+        return Err(HsmError::InvalidInput("Data cannot be empty".to_string()));
+    }
+
+    let raw_len = curve.field_len() * 2;
+    if signature_with_recid.len() != raw_len + 1 {
+        return Err(HsmError::InvalidInput(format!(
+            "Recoverable {:?} signatures are {} bytes (r || s || v), got {}",
+            curve,
+            raw_len + 1,
+            signature_with_recid.len()
+        )));
+    }
+
+    let (signature, v) = signature_with_recid.split_at(raw_len);
+    // Ethereum offsets the recovery id by 27
+    let v = match v[0] {
+        27 | 28 => v[0] - 27,
+        v => v,
+    };
+    let recovery_id = RecoveryId::from_byte(v).ok_or_else(|| {
+        HsmError::InvalidInput(format!("Invalid recovery id {} (expected 0-3 or 27/28)", v))
+    })?;
+
+    let digest = Sha256::digest(data);
+    let invalid_sig = |e: signature::Error| {
+        HsmError::InvalidInput(format!("Invalid {:?} signature: {}", curve, e))
+    };
+    let not_recoverable = |e: signature::Error| {
+        HsmError::VerificationFailed(format!("Public key recovery failed: {}", e))
+    };
+
+    let point = match curve {
+        Curve::P256 => {
+            use p256::ecdsa::{Signature, VerifyingKey};
+            let sig = Signature::from_slice(signature).map_err(invalid_sig)?;
+            VerifyingKey::recover_from_prehash(&digest, &sig, recovery_id)
+                .map_err(not_recoverable)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec()
+        }
+        Curve::P384 => {
+            use p384::ecdsa::{Signature, VerifyingKey};
+            let sig = Signature::from_slice(signature).map_err(invalid_sig)?;
+            VerifyingKey::recover_from_prehash(&digest, &sig, recovery_id)
+                .map_err(not_recoverable)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec()
+        }
+        Curve::K256 => {
+            use k256::ecdsa::{Signature, VerifyingKey};
+            let sig = Signature::from_slice(signature).map_err(invalid_sig)?;
+            VerifyingKey::recover_from_prehash(&digest, &sig, recovery_id)
+                .map_err(not_recoverable)?
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec()
+        }
+    };
+
+    Ok(point)
+}
+
+/// Find the recovery id that makes a plain `r || s` signature over `data` recover
+/// to `public_key`, i.e. the `v` a recoverable encoding of it would carry.
+pub fn find_recovery_id(
+    data: &[u8],
+    signature: &[u8],
+    public_key: &[u8],
+    curve: Curve,
+) -> HsmResult<Option<u8>> {
+    let expected = sec1_point(curve, public_key)?;
+
+    let mut with_recid = signature.to_vec();
+    with_recid.push(0);
+    for v in 0..=3u8 {
+        *with_recid.last_mut().expect("recovery id was pushed") = v;
+        if recover_public_key(data, &with_recid, curve).is_ok_and(|point| point == expected) {
+            return Ok(Some(v));
+        }
+    }
+
+    Ok(None)
+}
@@ -0,0 +1,105 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult};
+use super::wrap::parse_label;
+use x509_parser::pem::parse_x509_pem;
+use x509_parser::prelude::{FromDer, X509Certificate};
+use yubihsm::object::{Id, Type};
+use yubihsm::{Capability, Domain, opaque};
+
+/// Largest opaque object the YubiHSM2 stores
+pub const MAX_OPAQUE_LEN: usize = 1968;
+
+/// Fields of an X.509 certificate worth showing next to the object.
+#[derive(Clone, Debug)]
+pub struct CertificateSummary {
+    pub subject: String,
+    pub issuer: String,
+    pub serial: String,
+    pub not_before: String,
+    pub not_after: String,
+    /// Whether the current time falls within the validity period
+    pub currently_valid: bool,
+}
+
+/// Store `data` as an opaque object. Pass `object_id` 0 to let the device pick one.
+pub fn put_opaque(
+    client: &HsmClient,
+    object_id: Id,
+    label: &str,
+    domains: Domain,
+    algorithm: opaque::Algorithm,
+    data: &[u8],
+) -> HsmResult<Id> {
+    client.ensure_destructive_allowed()?;
+
+    if data.is_empty() {
+        return Err(HsmError::InvalidInput(
+            "an opaque object cannot be empty".to_string(),
+        ));
+    }
+    if data.len() > MAX_OPAQUE_LEN {
+        return Err(HsmError::InvalidInput(format!(
+            "{} bytes is over the {}-byte limit for opaque objects",
+            data.len(),
+            MAX_OPAQUE_LEN
+        )));
+    }
+    let label = parse_label(label)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ImportFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.put_opaque(
+        object_id,
+        label,
+        domains,
+        Capability::empty(),
+        algorithm,
+        data.to_vec(),
+    )
+    .map_err(|e| HsmError::from_client_object(e, object_id, Type::Opaque, HsmError::ImportFailed))
+}
+
+/// Contents of the opaque object `object_id`.
+pub fn get_opaque(client: &HsmClient, object_id: Id) -> HsmResult<Vec<u8>> {
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::GetOpaqueFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.get_opaque(object_id).map_err(|e| {
+        HsmError::from_client_object(e, object_id, Type::Opaque, HsmError::GetOpaqueFailed)
+    })
+}
+
+/// DER bytes of a certificate given as DER or PEM. Fails with `InvalidInput`
+/// unless the result parses as an X.509 certificate.
+pub fn certificate_der(data: &[u8]) -> HsmResult<Vec<u8>> {
+    let der = if data.starts_with(b"-----BEGIN") {
+        let (_, pem) = parse_x509_pem(data)
+            .map_err(|e| HsmError::InvalidInput(format!("Invalid PEM certificate: {}", e)))?;
+        pem.contents
+    } else {
+        data.to_vec()
+    };
+    parse_certificate(&der)?;
+    Ok(der)
+}
+
+/// Subject, issuer and validity of a DER certificate.
+pub fn parse_certificate(der: &[u8]) -> HsmResult<CertificateSummary> {
+    let (_, cert) = X509Certificate::from_der(der)
+        .map_err(|e| HsmError::InvalidInput(format!("Not an X.509 certificate: {}", e)))?;
+    let validity = cert.validity();
+
+    Ok(CertificateSummary {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        serial: cert.raw_serial_as_string(),
+        not_before: validity.not_before.to_string(),
+        not_after: validity.not_after.to_string(),
+        currently_valid: validity.is_valid(),
+    })
+}
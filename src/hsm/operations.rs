@@ -1,113 +1,909 @@
 use super::client::HsmClient;
 use super::error::{HsmError, HsmResult};
+use super::key_cache::{CachedPublicKey, PublicKeyCache};
+use super::logging::HexId;
+use super::outcome::SignatureEncoding;
+use super::spki::public_key_fingerprint;
+use super::wrap::parse_label;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey as Ed25519VerifyingKey};
 use hex;
-use sha2::{Digest, Sha256};
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use signature::hazmat::PrehashVerifier;
 use std::fmt::Write as _;
-use yubihsm::Algorithm;
-use yubihsm::asymmetric::PublicKey;
-use yubihsm::object::{Id, Info, Label, SequenceId, Type};
+use std::io::{self, Read};
+use std::sync::{Mutex, PoisonError};
+use tracing::instrument;
+use yubihsm::asymmetric::{self, PublicKey};
+use yubihsm::object::{Filter, Id, Info, Label, Origin, SequenceId, Type};
+use yubihsm::{Algorithm, Capability, Client, Domain};
 
-/// sign data using an ECDSA key (secp256r1/P-256) stored in the HSM
-/// First hashes the data with SHA-256, then signs the hash
-pub fn sign(client: &HsmClient, key_id: u16, data: &[u8]) -> HsmResult<Vec<u8>> {
+/// Separator placed between the timestamp and the message in timestamped payloads
+pub const TIMESTAMP_SEPARATOR: &str = "|";
+
+/// Hash applied on the host before an ECDSA signature or verification.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HashAlg {
+    #[default]
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlg {
+    pub const ALL: [HashAlg; 3] = [HashAlg::Sha256, HashAlg::Sha384, HashAlg::Sha512];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlg::Sha256 => "SHA-256",
+            HashAlg::Sha384 => "SHA-384",
+            HashAlg::Sha512 => "SHA-512",
+        }
+    }
+
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlg::Sha256 => Sha256::digest(data).to_vec(),
+            HashAlg::Sha384 => Sha384::digest(data).to_vec(),
+            HashAlg::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+
+    /// Hash everything `reader` yields a block at a time, so large files are
+    /// never held in memory. Returns the digest and the number of bytes hashed.
+    pub fn digest_reader(self, reader: impl Read) -> io::Result<(Vec<u8>, u64)> {
+        fn drain<D: Digest>(mut hasher: D, mut reader: impl Read) -> io::Result<(Vec<u8>, u64)> {
+            let mut buffer = [0u8; 64 * 1024];
+            let mut total = 0u64;
+            loop {
+                let read = match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                hasher.update(&buffer[..read]);
+                total += read as u64;
+            }
+            Ok((hasher.finalize().to_vec(), total))
+        }
+
+        match self {
+            HashAlg::Sha256 => drain(Sha256::new(), reader),
+            HashAlg::Sha384 => drain(Sha384::new(), reader),
+            HashAlg::Sha512 => drain(Sha512::new(), reader),
+        }
+    }
+}
+
+/// Build the exact bytes signed in timestamp mode: `<RFC3339 UTC time>|<message>`.
+/// Deterministic for a given timestamp so a verifier can rebuild the payload.
+pub fn timestamped_payload(timestamp: DateTime<Utc>, message: &[u8]) -> Vec<u8> {
+    let prefix = timestamp.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+    let mut payload = Vec::with_capacity(prefix.len() + TIMESTAMP_SEPARATOR.len() + message.len());
+    payload.extend_from_slice(prefix.as_bytes());
+    payload.extend_from_slice(TIMESTAMP_SEPARATOR.as_bytes());
+    payload.extend_from_slice(message);
+    payload
+}
+
+/// Split a payload built by `timestamped_payload` into its timestamp and message.
+pub fn parse_timestamped_payload(payload: &[u8]) -> HsmResult<(DateTime<Utc>, &[u8])> {
+    let separator = TIMESTAMP_SEPARATOR.as_bytes();
+    let position = payload
+        .windows(separator.len())
+        .position(|window| window == separator)
+        .ok_or_else(|| {
+            HsmError::InvalidInput(format!(
+                "Payload has no '{}' after the timestamp",
+                TIMESTAMP_SEPARATOR
+            ))
+        })?;
+
+    let prefix = std::str::from_utf8(&payload[..position])
+        .map_err(|_| HsmError::InvalidInput("Payload timestamp is not UTF-8".to_string()))?;
+    let timestamp = DateTime::parse_from_rfc3339(prefix)
+        .map_err(|e| {
+            HsmError::InvalidInput(format!("'{}' is not an RFC3339 timestamp: {}", prefix, e))
+        })?
+        .with_timezone(&Utc);
+
+    Ok((timestamp, &payload[position + separator.len()..]))
+}
+
+/// sign data using an ECDSA, Ed25519 or RSA key stored in the HSM;
+/// the algorithm is taken from the key's object info.
+/// ECDSA first hashes the data with `hash_alg`, then signs the hash; the hash
+/// must match the curve (SHA-256 for P-256, SHA-384 for P-384, ...).
+/// The device's Sign ECDSA command only takes a digest; there is no variant that
+/// hashes on the device (the yubihsm crate's `ecdsa::Signer` hashes on the host too),
+/// so host-side hashing is the only option and `verify` mirrors it.
+/// Ed25519 signs the raw message on the device and returns the 64-byte `R || S`;
+/// EdDSA fixes its own hash, so `hash_alg` is ignored.
+/// RSA keys produce a PKCS#1 v1.5 signature over SHA-256, as long as the modulus.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(key_id = %HexId(key_id), len = data.len(), hash = ?hash_alg),
+    err(level = "warn", Display)
+)]
+pub fn sign(client: &HsmClient, key_id: u16, data: &[u8], hash_alg: HashAlg) -> HsmResult<Vec<u8>> {
     if data.is_empty() {
         return Err(HsmError::InvalidInput("Data cannot be empty".to_string()));
     }
 
-    // Hash the data with SHA-256 first
-    let hash = Sha256::digest(data);
-    let hash_vec = hash.to_vec();
+    // Refuse auth/wrap keys and keys without the sign capability up front
+    let algorithm = ensure_signing_key(client, key_id)?;
+    ensure_hash_matches(key_id, algorithm, hash_alg)?;
 
     let hsm_client = client.client();
     let hsm = hsm_client
         .lock()
         .map_err(|e| HsmError::SigningFailed(format!("Failed to lock client: {}", e)))?;
 
+    sign_locked(&hsm, key_id, algorithm, data, hash_alg)
+}
+
+/// Sign each of `messages` with `key_id`, checking the key and taking the client
+/// lock once for the whole batch. The outer error means nothing was signed; a
+/// message the device refuses fails on its own and the rest are still signed.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(key_id = %HexId(key_id), count = messages.len(), hash = ?hash_alg),
+    err(level = "warn", Display)
+)]
+pub fn sign_batch(
+    client: &HsmClient,
+    key_id: u16,
+    messages: &[Vec<u8>],
+    hash_alg: HashAlg,
+) -> HsmResult<Vec<HsmResult<Vec<u8>>>> {
+    let algorithm = ensure_signing_key(client, key_id)?;
+    ensure_hash_matches(key_id, algorithm, hash_alg)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::SigningFailed(format!("Failed to lock client: {}", e)))?;
+
+    Ok(messages
+        .iter()
+        .map(|message| {
+            if message.is_empty() {
+                return Err(HsmError::InvalidInput("Data cannot be empty".to_string()));
+            }
+            sign_locked(&hsm, key_id, algorithm, message, hash_alg)
+        })
+        .collect())
+}
+
+/// Sign `data` on an already locked client with a key the caller has checked.
+fn sign_locked(
+    hsm: &Client,
+    key_id: u16,
+    algorithm: asymmetric::Algorithm,
+    data: &[u8],
+    hash_alg: HashAlg,
+) -> HsmResult<Vec<u8>> {
+    if algorithm == asymmetric::Algorithm::Ed25519 {
+        // EdDSA hashes internally, so the message goes to the device as is
+        let signature = hsm.sign_ed25519(key_id, data).map_err(|e| {
+            HsmError::from_client_object(e, key_id, Type::AsymmetricKey, HsmError::SigningFailed)
+        })?;
+        return Ok(signature.to_bytes().to_vec());
+    }
+
+    if is_rsa(algorithm) {
+        let signature = hsm.sign_rsa_pkcs1v15_sha256(key_id, data).map_err(|e| {
+            HsmError::from_client_object(e, key_id, Type::AsymmetricKey, HsmError::SigningFailed)
+        })?;
+        return Ok(signature.as_ref().to_vec());
+    }
+
+    // Hash the data first
+    let hash = hash_alg.digest(data);
+
     // Sign the hash using sign_ecdsa_prehash_raw
-    let signature = hsm
-        .sign_ecdsa_prehash_raw(key_id, hash_vec)
-        .map_err(|e| HsmError::SigningFailed(format!("{:?}", e)))?;
+    hsm.sign_ecdsa_prehash_raw(key_id, hash).map_err(|e| {
+        HsmError::from_client_object(e, key_id, Type::AsymmetricKey, HsmError::SigningFailed)
+    })
+}
+
+/// Sign a digest the caller already computed, without hashing it again. Meant for
+/// integrations that receive a digest instead of the message, where hashing here
+/// would silently produce a signature over the wrong value. The digest length
+/// must match the key's curve (e.g. 32 bytes for P-256, 48 for P-384).
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(key_id = %HexId(key_id), len = digest.len()),
+    err(level = "warn", Display)
+)]
+pub fn sign_prehashed(client: &HsmClient, key_id: u16, digest: &[u8]) -> HsmResult<Vec<u8>> {
+    let algorithm = ensure_signing_key(client, key_id)?;
+    ensure_digest_len(key_id, algorithm, digest, "signing a precomputed digest")?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::SigningFailed(format!("Failed to lock client: {}", e)))?;
 
-    Ok(signature)
+    hsm.sign_ecdsa_prehash_raw(key_id, digest.to_vec())
+        .map_err(|e| {
+            HsmError::from_client_object(e, key_id, Type::AsymmetricKey, HsmError::SigningFailed)
+        })
 }
 
-pub fn verify(client: &HsmClient, key_id: u16, data: &[u8], signature: &[u8]) -> HsmResult<bool> {
+/// Refuse a digest whose length does not fit the key's curve, and keys that do
+/// not sign digests at all.
+fn ensure_digest_len(
+    key_id: u16,
+    algorithm: asymmetric::Algorithm,
+    digest: &[u8],
+    operation: &'static str,
+) -> HsmResult<()> {
+    let expected_len = ecdsa_digest_len(algorithm).ok_or(HsmError::UnsupportedAlgorithm {
+        key_id,
+        algorithm,
+        operation,
+    })?;
+    if digest.len() != expected_len {
+        return Err(HsmError::InvalidInput(format!(
+            "Digest is {} bytes, but key 0x{:04x} ({:?}) expects {}",
+            digest.len(),
+            key_id,
+            algorithm,
+            expected_len
+        )));
+    }
+    Ok(())
+}
+
+/// Digest length matching an ECDSA curve's strength, or None for non-EC algorithms.
+fn ecdsa_digest_len(algorithm: asymmetric::Algorithm) -> Option<usize> {
+    use asymmetric::Algorithm::*;
+
+    match algorithm {
+        EcP224 => Some(28),
+        EcP256 | EcK256 | EcBp256 => Some(32),
+        EcP384 | EcBp384 => Some(48),
+        EcP521 | EcBp512 => Some(64),
+        _ => None,
+    }
+}
+
+/// Hash whose output matches an ECDSA curve's strength, or None for non-EC algorithms.
+fn curve_hash(algorithm: asymmetric::Algorithm) -> Option<HashAlg> {
+    use asymmetric::Algorithm::*;
+
+    match algorithm {
+        EcP224 | EcP256 | EcK256 | EcBp256 => Some(HashAlg::Sha256),
+        EcP384 | EcBp384 => Some(HashAlg::Sha384),
+        EcP521 | EcBp512 => Some(HashAlg::Sha512),
+        _ => None,
+    }
+}
+
+/// Refuse a hash that does not fit the key: ECDSA curves take the hash of their
+/// own size and the device's RSA signing is fixed to SHA-256.
+fn ensure_hash_matches(
+    key_id: u16,
+    algorithm: asymmetric::Algorithm,
+    hash_alg: HashAlg,
+) -> HsmResult<()> {
+    let expected = if is_rsa(algorithm) {
+        HashAlg::Sha256
+    } else if let Some(expected) = curve_hash(algorithm) {
+        expected
+    } else {
+        return Ok(());
+    };
+
+    if hash_alg != expected {
+        return Err(HsmError::InvalidInput(format!(
+            "{} does not match key 0x{:04x} ({:?}); use {}",
+            hash_alg.name(),
+            key_id,
+            algorithm,
+            expected.name()
+        )));
+    }
+    Ok(())
+}
+
+fn is_rsa(algorithm: asymmetric::Algorithm) -> bool {
+    use asymmetric::Algorithm::*;
+
+    matches!(algorithm, Rsa2048 | Rsa3072 | Rsa4096)
+}
+
+/// Check that `key_id` refers to an ECDSA, Ed25519 or RSA key with the matching
+/// sign capability, and return its algorithm.
+/// Mirrors the delete-time auth key protection: fail early with guidance rather than
+/// letting the device reject the command with an opaque error.
+fn ensure_signing_key(client: &HsmClient, key_id: u16) -> HsmResult<asymmetric::Algorithm> {
+    match get_object_info(client, key_id, Type::AsymmetricKey) {
+        Ok(info) => {
+            let Algorithm::Asymmetric(algorithm) = info.algorithm else {
+                return Err(HsmError::InvalidKey(format!(
+                    "Key 0x{:04x} reports non-asymmetric algorithm {:?}",
+                    key_id, info.algorithm
+                )));
+            };
+            let (capability, name) = if algorithm == asymmetric::Algorithm::Ed25519 {
+                (Capability::SIGN_EDDSA, "sign-eddsa")
+            } else if is_rsa(algorithm) {
+                (Capability::SIGN_PKCS, "sign-pkcs")
+            } else if ecdsa_digest_len(algorithm).is_some() {
+                (Capability::SIGN_ECDSA, "sign-ecdsa")
+            } else {
+                return Err(HsmError::UnsupportedAlgorithm {
+                    key_id,
+                    algorithm,
+                    operation: "signing",
+                });
+            };
+            if !info.capabilities.contains(capability) {
+                return Err(HsmError::InvalidKey(format!(
+                    "Key 0x{:04x} lacks the {} capability. Use an asymmetric key that is allowed to sign.",
+                    key_id, name
+                )));
+            }
+            Ok(algorithm)
+        }
+        Err(e) => Err(signing_key_lookup_error(key_id, e, |object_type| {
+            get_object_info(client, key_id, object_type).is_ok()
+        })),
+    }
+}
+
+/// Explain why looking up signing key `key_id` failed with `error`. A missing
+/// key gets a hint from `exists_as`, which says whether the ID names an object
+/// of another type; anything else, such as an expired session or a lost
+/// device, is returned unchanged for the caller's retry logic.
+fn signing_key_lookup_error(
+    key_id: u16,
+    error: HsmError,
+    exists_as: impl Fn(Type) -> bool,
+) -> HsmError {
+    if !matches!(error, HsmError::ObjectNotFound { .. }) {
+        return error;
+    }
+    if let Some(object_type) = [Type::AuthenticationKey, Type::WrapKey]
+        .into_iter()
+        .find(|&object_type| exists_as(object_type))
+    {
+        return HsmError::InvalidKey(format!(
+            "Object 0x{:04x} is a {:?}, not a signing key. Configure the ID of an asymmetric (ECDSA, Ed25519 or RSA) key instead.",
+            key_id, object_type
+        ));
+    }
+
+    HsmError::InvalidKey(format!(
+        "No asymmetric key with ID 0x{:04x} is visible to the current authentication key",
+        key_id
+    ))
+}
+
+/// Verify `signature` over `data` on the host with the public key of `key_id`,
+/// hashing ECDSA input with `hash_alg` the same way `sign` does.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        key_id = %HexId(key_id),
+        len = data.len(),
+        signature_len = signature.len(),
+        hash = ?hash_alg,
+    ),
+    err(level = "warn", Display)
+)]
+pub fn verify(
+    client: &HsmClient,
+    key_id: u16,
+    data: &[u8],
+    signature: &[u8],
+    hash_alg: HashAlg,
+) -> HsmResult<bool> {
     if data.is_empty() {
         return Err(HsmError::InvalidInput("Data cannot be empty".to_string()));
     }
 
-    // Hash the data with SHA-256 (same as during signing)
-    let hash = Sha256::digest(data);
-
     let hsm_client = client.client();
     let hsm = hsm_client
         .lock()
         .map_err(|e| HsmError::VerificationFailed(format!("Failed to lock client: {}", e)))?;
 
     // Get the public key from the HSM
-    let public_key = hsm
-        .get_public_key(key_id)
-        .map_err(|e| HsmError::InvalidKey(format!("Failed to get public key: {:?}", e)))?;
-
-    // Use p256 crate for ECDSA verification
-    use p256::ecdsa::{Signature as EcdsaSignature, VerifyingKey};
-    use signature::hazmat::PrehashVerifier;
-
-    // YubiHSM returns public key as raw bytes (64 bytes: x || y for P-256)
-    // We need to convert it to uncompressed SEC1 format (0x04 || x || y)
-    let pk_bytes = public_key.as_ref();
-
-    // Try to parse as uncompressed point first (if it's already 65 bytes with 0x04 prefix)
-    let verifying_key = if pk_bytes.len() == 65 && pk_bytes[0] == 0x04 {
-        VerifyingKey::from_sec1_bytes(pk_bytes)
-            .map_err(|e| HsmError::InvalidKey(format!("Invalid public key (SEC1): {}", e)))?
-    } else if pk_bytes.len() == 64 {
-        // If it's 64 bytes (raw x || y), add the 0x04 prefix
-        let mut uncompressed = vec![0x04];
-        uncompressed.extend_from_slice(pk_bytes);
-
-        VerifyingKey::from_sec1_bytes(&uncompressed)
-            .map_err(|e| HsmError::InvalidKey(format!("Invalid public key (raw): {}", e)))?
-    } else {
-        return Err(HsmError::InvalidKey(format!(
-            "Unexpected public key length: {} bytes (expected 64 or 65)",
-            pk_bytes.len()
-        )));
+    let public_key = hsm.get_public_key(key_id).map_err(|e| {
+        HsmError::from_client_object(e, key_id, Type::AsymmetricKey, |msg| {
+            HsmError::InvalidKey(format!("Failed to get public key: {}", msg))
+        })
+    })?;
+
+    ensure_hash_matches(key_id, public_key.algorithm, hash_alg)?;
+
+    // Ed25519 and RSA keys must not reach the P-256 point parsing below
+    if public_key.algorithm == asymmetric::Algorithm::Ed25519 {
+        return verify_ed25519(public_key.as_ref(), data, signature);
+    }
+    if is_rsa(public_key.algorithm) {
+        return verify_rsa(public_key.as_ref(), data, signature);
+    }
+
+    // Hash the data the same way as during signing
+    let hash = hash_alg.digest(data);
+
+    verify_ecdsa_digest(key_id, &public_key, &hash, signature, "verification")
+}
+
+/// Verify `signature` over a digest the caller already computed, without hashing
+/// it again; the counterpart of `sign_prehashed`.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(
+        key_id = %HexId(key_id),
+        len = digest.len(),
+        signature_len = signature.len(),
+    ),
+    err(level = "warn", Display)
+)]
+pub fn verify_prehashed(
+    client: &HsmClient,
+    key_id: u16,
+    digest: &[u8],
+    signature: &[u8],
+) -> HsmResult<bool> {
+    let public_key = get_public_key(client, key_id)?;
+    ensure_digest_len(
+        key_id,
+        public_key.algorithm,
+        digest,
+        "verifying a precomputed digest",
+    )?;
+
+    verify_ecdsa_digest(
+        key_id,
+        &public_key,
+        digest,
+        signature,
+        "verifying a precomputed digest",
+    )
+}
+
+/// Parse `$signature`, DER or fixed-width raw `r || s`, as a `$ty` ECDSA signature.
+macro_rules! parse_ecdsa {
+    ($ty:ty, $signature:expr) => {{
+        let signature: &[u8] = $signature;
+        // A raw r || s may start with the SEQUENCE tag too, so fall back to it
+        <$ty>::from_der(signature)
+            .or_else(|_| <$ty>::from_slice(signature))
+            .map_err(|_| {
+                HsmError::InvalidInput(format!(
+                    "Invalid ECDSA signature: {} bytes is neither DER nor raw r || s for this curve",
+                    signature.len()
+                ))
+            })
+    }};
+}
+
+/// Evaluate `$body` with `$sig` bound to `$signature` parsed as a signature of
+/// the ECDSA curve `$algorithm`. Only P-256, P-384 and secp256k1 have a
+/// host-side implementation; any other algorithm returns an error.
+macro_rules! with_ecdsa_signature {
+    ($algorithm:expr, $signature:expr, |$sig:ident| $body:expr) => {
+        match $algorithm {
+            asymmetric::Algorithm::EcP256 => {
+                let $sig = parse_ecdsa!(p256::ecdsa::Signature, $signature)?;
+                $body
+            }
+            asymmetric::Algorithm::EcP384 => {
+                let $sig = parse_ecdsa!(p384::ecdsa::Signature, $signature)?;
+                $body
+            }
+            asymmetric::Algorithm::EcK256 => {
+                let $sig = parse_ecdsa!(k256::ecdsa::Signature, $signature)?;
+                $body
+            }
+            algorithm => return Err(unsupported_curve(algorithm)),
+        }
     };
+}
 
-    // Parse the signature
-    // YubiHSM returns DER-encoded signature (typically 70 bytes, but can vary)
-    // p256::ecdsa::Signature::from_slice() expects raw format (64 bytes: r || s)
-    // So we need to handle DER format and convert to raw if needed
-    let sig = if signature.len() > 64 && signature[0] == 0x30 {
-        // DER format: starts with 0x30 (SEQUENCE tag) and is longer than 64 bytes
-        // DER structure: SEQUENCE { INTEGER r, INTEGER s }
-        // We'll use the ecdsa crate's DER parsing capability
-        EcdsaSignature::from_der(signature)
-            .map_err(|e| HsmError::InvalidInput(format!("Invalid DER signature format: {}", e)))?
-    } else if signature.len() == 64 {
-        // Raw format: r || s (32 bytes each)
-        EcdsaSignature::from_slice(signature)
-            .map_err(|e| HsmError::InvalidInput(format!("Invalid raw signature format: {}", e)))?
-    } else {
+/// Error for a host-side ECDSA operation on a curve the host cannot handle.
+fn unsupported_curve(algorithm: asymmetric::Algorithm) -> HsmError {
+    HsmError::InvalidInput(format!(
+        "{:?} signatures cannot be handled on the host; only P-256, P-384 and secp256k1 are supported",
+        algorithm
+    ))
+}
+
+/// Host-side check of an ECDSA signature over a digest, for each curve
+/// `with_ecdsa_signature!` parses.
+trait VerifyDigest {
+    /// Verify `self` over `digest` with the public key `point`, raw `x || y`
+    /// as the device returns it or SEC1 uncompressed.
+    fn verify_digest(&self, point: &[u8], digest: &[u8]) -> HsmResult<bool>;
+}
+
+macro_rules! impl_verify_digest {
+    ($($curve:ident => $name:literal),* $(,)?) => {$(
+        impl VerifyDigest for $curve::ecdsa::Signature {
+            fn verify_digest(&self, point: &[u8], digest: &[u8]) -> HsmResult<bool> {
+                let verifying_key =
+                    $curve::ecdsa::VerifyingKey::from_sec1_bytes(&sec1_uncompressed(point))
+                        .map_err(|e| {
+                            HsmError::InvalidKey(format!("Invalid {} public key: {}", $name, e))
+                        })?;
+                // The device signed the digest with sign_ecdsa_prehash_raw, so
+                // check it with verify_prehash rather than hashing again
+                Ok(verifying_key.verify_prehash(digest, self).is_ok())
+            }
+        }
+    )*};
+}
+
+impl_verify_digest!(p256 => "P-256", p384 => "P-384", k256 => "secp256k1");
+
+/// SEC1 uncompressed form `0x04 || x || y` of an EC public key. The device
+/// returns the bare `x || y`, which has even length; an odd-length key is
+/// taken to carry the prefix already.
+fn sec1_uncompressed(point: &[u8]) -> Vec<u8> {
+    if point.len() % 2 == 1 && point.first() == Some(&0x04) {
+        return point.to_vec();
+    }
+    let mut sec1 = Vec::with_capacity(point.len() + 1);
+    sec1.push(0x04);
+    sec1.extend_from_slice(point);
+    sec1
+}
+
+/// Check an ECDSA signature over `digest` with the device's public key.
+fn verify_ecdsa_digest(
+    key_id: u16,
+    public_key: &PublicKey,
+    digest: &[u8],
+    signature: &[u8],
+    operation: &'static str,
+) -> HsmResult<bool> {
+    let algorithm = public_key.algorithm;
+    if !matches!(
+        algorithm,
+        asymmetric::Algorithm::EcP256
+            | asymmetric::Algorithm::EcP384
+            | asymmetric::Algorithm::EcK256
+    ) {
+        return Err(HsmError::UnsupportedAlgorithm {
+            key_id,
+            algorithm,
+            operation,
+        });
+    }
+
+    Ok(with_ecdsa_signature!(algorithm, signature, |sig| sig
+        .verify_digest(public_key.as_ref(), digest)?))
+}
+
+/// Verify an Ed25519 signature over the raw message with the 32-byte public key.
+fn verify_ed25519(pk_bytes: &[u8], data: &[u8], signature: &[u8]) -> HsmResult<bool> {
+    let pk_bytes: &[u8; 32] = pk_bytes.try_into().map_err(|_| {
+        HsmError::InvalidKey(format!(
+            "Unexpected Ed25519 public key length: {} bytes (expected 32)",
+            pk_bytes.len()
+        ))
+    })?;
+    let verifying_key = Ed25519VerifyingKey::from_bytes(pk_bytes)
+        .map_err(|e| HsmError::InvalidKey(format!("Invalid Ed25519 public key: {}", e)))?;
+    let sig = Ed25519Signature::from_slice(signature).map_err(|_| {
+        HsmError::InvalidInput(format!(
+            "Ed25519 signatures are 64 bytes, got {}",
+            signature.len()
+        ))
+    })?;
+
+    Ok(verifying_key.verify_strict(data, &sig).is_ok())
+}
+
+/// Verify a PKCS#1 v1.5 SHA-256 signature with the RSA modulus returned by the HSM.
+fn verify_rsa(modulus: &[u8], data: &[u8], signature: &[u8]) -> HsmResult<bool> {
+    // The device only returns the modulus; its RSA keys always use e = 65537
+    let public_key = RsaPublicKey::new(BigUint::from_bytes_be(modulus), BigUint::from(65537u32))
+        .map_err(|e| HsmError::InvalidKey(format!("Invalid RSA public key: {}", e)))?;
+    if signature.len() != modulus.len() {
         return Err(HsmError::InvalidInput(format!(
-            "Invalid signature length: {} bytes (expected 64 for raw or >64 for DER)",
+            "RSA-{} signatures are {} bytes, got {}; is this an ECDSA or Ed25519 signature?",
+            modulus.len() * 8,
+            modulus.len(),
             signature.len()
         )));
+    }
+
+    let hash = Sha256::digest(data);
+    Ok(public_key
+        .verify(Pkcs1v15Sign::new::<Sha256>(), &hash, signature)
+        .is_ok())
+}
+
+/// Rewrite a signature made with an `algorithm` key so `s` is in the lower half
+/// of the curve order, as required by ecosystems that reject malleable
+/// signatures. Returns DER; a signature that is already low-S comes back
+/// re-encoded only. Curves without a host-side implementation are refused
+/// rather than normalized against the wrong order.
+pub fn normalize_low_s(signature: &[u8], algorithm: asymmetric::Algorithm) -> HsmResult<Vec<u8>> {
+    Ok(with_ecdsa_signature!(algorithm, signature, |sig| sig
+        .normalize_s()
+        .unwrap_or(sig)
+        .to_der()
+        .as_bytes()
+        .to_vec()))
+}
+
+/// Returns true if a signature made with an `algorithm` key has `s` in the upper
+/// half of the curve order. Curves without a host-side implementation are refused.
+pub fn is_high_s(signature: &[u8], algorithm: asymmetric::Algorithm) -> HsmResult<bool> {
+    // normalize_s only returns a new signature when s was in the upper half
+    Ok(with_ecdsa_signature!(algorithm, signature, |sig| sig
+        .normalize_s()
+        .is_some()))
+}
+
+/// Structure of an ECDSA signature, for comparing with what other verifiers expect.
+#[derive(Clone, Debug)]
+pub struct EcdsaComponents {
+    /// Encoding the signature came in
+    pub encoding: SignatureEncoding,
+    /// Total length of the signature as given, in bytes
+    pub len: usize,
+    /// Big-endian r component, padded to the curve's scalar size
+    pub r: Vec<u8>,
+    /// Big-endian s component, padded to the curve's scalar size
+    pub s: Vec<u8>,
+    /// Whether s is in the lower half of the curve order
+    pub is_low_s: bool,
+}
+
+/// Decode an ECDSA signature made with an `algorithm` key into `r` and `s`.
+/// None for curves without a host-side implementation (only P-256, P-384 and
+/// secp256k1 have one) or if the signature does not parse.
+pub fn ecdsa_components(
+    signature: &[u8],
+    algorithm: asymmetric::Algorithm,
+) -> Option<EcdsaComponents> {
+    let components = || -> HsmResult<EcdsaComponents> {
+        Ok(with_ecdsa_signature!(algorithm, signature, |sig| {
+            let (r, s) = sig.split_bytes();
+            EcdsaComponents {
+                // DER is tried first when parsing, so DER input re-encodes as itself
+                encoding: if sig.to_der().as_bytes() == signature {
+                    SignatureEncoding::Der
+                } else {
+                    SignatureEncoding::Raw
+                },
+                len: signature.len(),
+                r: r.to_vec(),
+                s: s.to_vec(),
+                // normalize_s only returns a new signature when s was in the upper half
+                is_low_s: sig.normalize_s().is_none(),
+            }
+        }))
+    };
+    components().ok()
+}
+
+/// DER `SEQUENCE { r, s }` form of a signature made with an `algorithm` key,
+/// whichever form the device returned.
+pub fn to_der(signature: &[u8], algorithm: asymmetric::Algorithm) -> HsmResult<Vec<u8>> {
+    Ok(with_ecdsa_signature!(algorithm, signature, |sig| sig
+        .to_der()
+        .as_bytes()
+        .to_vec()))
+}
+
+/// Fixed-width `r || s` form of a signature made with an `algorithm` key, given
+/// as DER or raw; each half is as long as the curve's scalar.
+pub fn to_raw(signature: &[u8], algorithm: asymmetric::Algorithm) -> HsmResult<Vec<u8>> {
+    Ok(with_ecdsa_signature!(algorithm, signature, |sig| sig
+        .to_bytes()
+        .to_vec()))
+}
+
+/// Split one DER tag-length-value off the front of `input`.
+/// Returns `(tag, value, rest)`, or None if the input is truncated or malformed.
+pub(super) fn der_read_tlv(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, mut input) = input.split_first()?;
+
+    let len = if first < 0x80 {
+        first as usize
+    } else {
+        // Long form: the low bits give the number of length bytes that follow
+        let count = (first & 0x7f) as usize;
+        if count == 0 || count > std::mem::size_of::<usize>() || input.len() < count {
+            return None;
+        }
+        let (len_bytes, rest) = input.split_at(count);
+        input = rest;
+        len_bytes
+            .iter()
+            .fold(0usize, |len, b| (len << 8) | *b as usize)
+    };
+
+    if input.len() < len {
+        return None;
+    }
+    let (value, rest) = input.split_at(len);
+    Some((tag, value, rest))
+}
+
+/// Educational breakdown of a device signature checked on the host.
+#[derive(Clone, Debug)]
+pub struct SignatureBreakdown {
+    /// Algorithm of the signing key
+    pub algorithm: asymmetric::Algorithm,
+    /// Hash matching the key's curve, applied before signing
+    pub hash_alg: HashAlg,
+    /// Digest that was sent to the device
+    pub digest: Vec<u8>,
+    /// DER-encoded signature as returned by the device
+    pub der: Vec<u8>,
+    /// Big-endian r component
+    pub r: Vec<u8>,
+    /// Big-endian s component
+    pub s: Vec<u8>,
+    /// Whether s is in the lower half of the curve order
+    pub is_low_s: bool,
+    /// Uncompressed SEC1 public key fetched from the device
+    pub public_key: Vec<u8>,
+    /// Crate that verified the signature on the host
+    pub verifier: &'static str,
+    /// Whether the host-side verification accepted the signature
+    pub verified: bool,
+}
+
+/// Sign `data` on the device with the hash matching the key's curve, then verify
+/// the result on the host with that curve's crate against the device-fetched
+/// public key and break the signature into its components.
+pub fn compare_signature(
+    client: &HsmClient,
+    key_id: u16,
+    data: &[u8],
+) -> HsmResult<SignatureBreakdown> {
+    let public_key = get_public_key(client, key_id)?;
+    let algorithm = public_key.algorithm;
+    let unsupported = HsmError::UnsupportedAlgorithm {
+        key_id,
+        algorithm,
+        operation: "comparing device and host ECDSA",
     };
+    let verifier = match algorithm {
+        asymmetric::Algorithm::EcP256 => "p256",
+        asymmetric::Algorithm::EcP384 => "p384",
+        asymmetric::Algorithm::EcK256 => "k256",
+        _ => return Err(unsupported),
+    };
+    let hash_alg = curve_hash(algorithm).ok_or(unsupported)?;
+
+    let der = sign(client, key_id, data, hash_alg)?;
+    let digest = hash_alg.digest(data);
+    let verified = verify_ecdsa_digest(key_id, &public_key, &digest, &der, "comparison")?;
+    let components = ecdsa_components(&der, algorithm).ok_or_else(|| {
+        HsmError::SigningFailed("The device returned a signature that does not parse".to_string())
+    })?;
+
+    let mut sec1 = Vec::with_capacity(public_key.as_ref().len() + 1);
+    sec1.push(0x04);
+    sec1.extend_from_slice(public_key.as_ref());
+
+    Ok(SignatureBreakdown {
+        algorithm,
+        hash_alg,
+        digest,
+        der,
+        r: components.r,
+        s: components.s,
+        is_low_s: components.is_low_s,
+        public_key: sec1,
+        verifier,
+        verified,
+    })
+}
 
-    // Verify the signature against the hash
-    // Since we used sign_ecdsa_prehash_raw, we need to use verify_prehash
-    match verifying_key.verify_prehash(&hash, &sig) {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
+/// A compact-serialized JWS and the JOSE algorithm it was signed with.
+#[derive(Clone, Debug)]
+pub struct SignedJws {
+    /// `alg` of the protected header, e.g. "ES256"
+    pub alg: &'static str,
+    pub token: String,
+}
+
+/// JOSE `alg` name for a key algorithm (RFC 7518, 8037 and 8812), or None if
+/// JWS has no name for it.
+fn jws_alg(algorithm: asymmetric::Algorithm) -> Option<&'static str> {
+    match algorithm {
+        asymmetric::Algorithm::EcP256 => Some("ES256"),
+        asymmetric::Algorithm::EcP384 => Some("ES384"),
+        asymmetric::Algorithm::EcK256 => Some("ES256K"),
+        asymmetric::Algorithm::Ed25519 => Some("EdDSA"),
+        _ => None,
     }
 }
 
+/// JWS signing input `base64url(header).base64url(payload)` for `alg`. The
+/// payload is arbitrary, so the header does not claim a `typ`.
+fn jws_signing_input(alg: &str, payload: &[u8]) -> String {
+    let header = format!(r#"{{"alg":"{}"}}"#, alg);
+    format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header),
+        URL_SAFE_NO_PAD.encode(payload)
+    )
+}
+
+/// Append the device's signature over `signing_input` to make the compact JWS.
+/// ECDSA signatures are converted to the raw `r || s` JWS requires; Ed25519
+/// ones already are `R || S`.
+fn finish_jws(
+    signing_input: String,
+    algorithm: asymmetric::Algorithm,
+    signature: &[u8],
+) -> HsmResult<String> {
+    let signature = if algorithm == asymmetric::Algorithm::Ed25519 {
+        signature.to_vec()
+    } else {
+        to_raw(signature, algorithm)?
+    };
+    Ok(format!(
+        "{}.{}",
+        signing_input,
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// Produce a compact-serialized JWS over `payload` with key `key_id` on the
+/// device. The `alg` follows the key: ES256, ES384, ES256K or EdDSA; other keys
+/// are refused.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(key_id = %HexId(key_id), len = payload.len()),
+    err(level = "warn", Display)
+)]
+pub fn sign_jws(client: &HsmClient, key_id: u16, payload: &[u8]) -> HsmResult<SignedJws> {
+    let algorithm = ensure_signing_key(client, key_id)?;
+    let alg = jws_alg(algorithm).ok_or(HsmError::UnsupportedAlgorithm {
+        key_id,
+        algorithm,
+        operation: "JWS signing",
+    })?;
+
+    let signing_input = jws_signing_input(alg, payload);
+    // Ed25519 ignores the hash; each ES* alg fixes the one matching its curve
+    let hash_alg = curve_hash(algorithm).unwrap_or_default();
+    // The key was checked above, so sign directly instead of through `sign`
+    let signature = {
+        let hsm_client = client.client();
+        let hsm = hsm_client
+            .lock()
+            .map_err(|e| HsmError::SigningFailed(format!("Failed to lock client: {}", e)))?;
+        sign_locked(&hsm, key_id, algorithm, signing_input.as_bytes(), hash_alg)?
+    };
+
+    Ok(SignedJws {
+        alg,
+        token: finish_jws(signing_input, algorithm, &signature)?,
+    })
+}
+
 /// List all objects visible to the current authentication key on the HSM.
 /// Returns a human-readable summary string that can be shown in the UI.
 pub fn list_objects(client: &HsmClient) -> HsmResult<String> {
-    let summaries = list_object_summaries(client)?;
+    let summaries = list_object_summaries(client, &[])?;
 
     if summaries.is_empty() {
         return Ok("No objects visible for the current authentication key.".to_string());
@@ -149,34 +945,139 @@ pub fn list_objects(client: &HsmClient) -> HsmResult<String> {
     Ok(out)
 }
 
+/// Largest number of random bytes `get_random` asks the device for at once.
+pub const MAX_RANDOM_LEN: usize = 2048;
+
+/// Read `len` bytes (1..=`MAX_RANDOM_LEN`) from the device's hardware RNG.
+#[instrument(level = "debug", skip_all, fields(len = len), err(level = "warn", Display))]
+pub fn get_random(client: &HsmClient, len: usize) -> HsmResult<Vec<u8>> {
+    if !(1..=MAX_RANDOM_LEN).contains(&len) {
+        return Err(HsmError::InvalidInput(format!(
+            "Random length must be between 1 and {} bytes, got {}",
+            MAX_RANDOM_LEN, len
+        )));
+    }
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ListingFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.get_pseudo_random(len)
+        .map_err(|e| HsmError::from_client(e, HsmError::ListingFailed))
+}
+
 /// Get detailed information about an object (using its ID and type).
+// Often a probe whose failure the caller expects, so failures are only debug
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(object_id = %HexId(object_id), ty = ?object_type),
+    err(level = "debug", Display)
+)]
 pub fn get_object_info(client: &HsmClient, object_id: Id, object_type: Type) -> HsmResult<Info> {
     let hsm_client = client.client();
     let hsm = hsm_client
         .lock()
         .map_err(|e| HsmError::ListingFailed(format!("Failed to lock client: {}", e)))?;
 
-    let info = hsm
-        .get_object_info(object_id, object_type)
-        .map_err(|e| HsmError::ListingFailed(format!("Failed to get object info: {:?}", e)))?;
+    let info = hsm.get_object_info(object_id, object_type).map_err(|e| {
+        HsmError::from_client_object(e, object_id, object_type, |msg| {
+            HsmError::ListingFailed(format!("Failed to get object info: {}", msg))
+        })
+    })?;
 
     Ok(info)
 }
 
+/// Domain mask for a domain number (1-16).
+pub fn domain_from_number(number: u8) -> HsmResult<Domain> {
+    if !(1..=16).contains(&number) {
+        return Err(HsmError::InvalidInput(format!(
+            "Domain {} does not exist (domains are 1-16)",
+            number
+        )));
+    }
+    Ok(Domain::from_bits_truncate(1 << (number - 1)))
+}
+
+/// Check that an object belongs to `domain` before operating on it. The device
+/// only checks the auth key's domains, so this enforces a narrower per-operation
+/// domain on top of that.
+pub fn ensure_in_domain(
+    client: &HsmClient,
+    object_id: Id,
+    object_type: Type,
+    domain: Domain,
+) -> HsmResult<()> {
+    let info = get_object_info(client, object_id, object_type)?;
+    if info.domains.contains(domain) {
+        return Ok(());
+    }
+
+    Err(HsmError::InvalidKey(format!(
+        "Object 0x{:04x} is not in the active domain {:?} (it belongs to {:?}). Pick another key or change the active domain.",
+        object_id, domain, info.domains
+    )))
+}
+
 /// Get the public key bytes/algorithm for an asymmetric key object id.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(key_id = %HexId(key_id)),
+    err(level = "warn", Display)
+)]
 pub fn get_public_key(client: &HsmClient, key_id: Id) -> HsmResult<PublicKey> {
     let hsm_client = client.client();
     let hsm = hsm_client
         .lock()
         .map_err(|e| HsmError::ListingFailed(format!("Failed to lock client: {}", e)))?;
 
-    let public_key = hsm
-        .get_public_key(key_id)
-        .map_err(|e| HsmError::GetPublicKeyFailed(format!("Failed to get public key: {:?}", e)))?;
+    let public_key = hsm.get_public_key(key_id).map_err(|e| {
+        HsmError::from_client_object(e, key_id, Type::AsymmetricKey, |msg| {
+            HsmError::GetPublicKeyFailed(format!("Failed to get public key: {}", msg))
+        })
+    })?;
 
     Ok(public_key)
 }
 
+/// Resolve the ID of the asymmetric key carrying the given label.
+/// Errors if no key or more than one key matches.
+#[instrument(level = "debug", skip_all, fields(label = %label), err(level = "warn", Display))]
+pub fn resolve_key_by_label(client: &HsmClient, label: &str) -> HsmResult<Id> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(HsmError::InvalidInput("Label cannot be empty".to_string()));
+    }
+
+    let matches: Vec<Id> = list_object_summaries(client, &[Filter::Type(Type::AsymmetricKey)])?
+        .into_iter()
+        .filter(|summary| {
+            summary.object_type == Type::AsymmetricKey && summary.label.to_string() == label
+        })
+        .map(|summary| summary.object_id)
+        .collect();
+
+    match matches.as_slice() {
+        [id] => Ok(*id),
+        [] => Err(HsmError::InvalidKey(format!(
+            "No asymmetric key labeled '{}' is visible to the current authentication key",
+            label
+        ))),
+        ids => Err(HsmError::InvalidKey(format!(
+            "Label '{}' is ambiguous: {} asymmetric keys match ({})",
+            label,
+            ids.len(),
+            ids.iter()
+                .map(|id| format!("0x{:04x}", id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
 /// Structured summary of an HSM object suitable for displaying in a table.
 #[derive(Clone, Debug)]
 pub struct ObjectSummary {
@@ -185,13 +1086,58 @@ pub struct ObjectSummary {
     pub algorithm: Algorithm,
     pub label: Label,
     pub sequence: SequenceId,
+    pub capabilities: Capability,
+    /// Capabilities objects imported or exported under this key may keep
+    pub delegated_capabilities: Capability,
+    pub domains: Domain,
+    /// Whether the object was generated on a device or imported, and if wrapped
+    pub origin: Origin,
     /// Hex-encoded public key bytes for asymmetric keys, if available.
     pub public_key_hex: Option<String>,
+    /// Hex SHA-256 of the SPKI DER public key for asymmetric keys, if available.
+    pub fingerprint_hex: Option<String>,
+}
+
+/// Generate an asymmetric key on the device. Pass `key_id` 0 to let the device pick one.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(key_id = %HexId(key_id), label = %label, algorithm = ?algorithm),
+    err(level = "warn", Display)
+)]
+pub fn generate_asymmetric_key(
+    client: &HsmClient,
+    key_id: Id,
+    label: &str,
+    domains: Domain,
+    capabilities: Capability,
+    algorithm: asymmetric::Algorithm,
+) -> HsmResult<Id> {
+    client.ensure_destructive_allowed()?;
+    let label = parse_label(label)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::GenerationFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.generate_asymmetric_key(key_id, label, domains, capabilities, algorithm)
+        .map_err(|e| {
+            HsmError::from_client_object(e, key_id, Type::AsymmetricKey, HsmError::GenerationFailed)
+        })
 }
 
 /// Delete an object from the HSM by ID and type.
 /// Note: This will NOT delete authentication keys for safety.
+#[instrument(
+    level = "info",
+    skip_all,
+    fields(object_id = %HexId(object_id), ty = ?object_type),
+    err(level = "warn", Display)
+)]
 pub fn delete_object(client: &HsmClient, object_id: Id, object_type: Type) -> HsmResult<()> {
+    client.ensure_destructive_allowed()?;
+
     if object_type == Type::AuthenticationKey {
         return Err(HsmError::InvalidInput(
             "Deleting authentication keys is not allowed".to_string(),
@@ -203,14 +1149,51 @@ pub fn delete_object(client: &HsmClient, object_id: Id, object_type: Type) -> Hs
         .lock()
         .map_err(|e| HsmError::DeletionFailed(format!("Failed to lock client: {}", e)))?;
 
-    hsm.delete_object(object_id, object_type)
-        .map_err(|e| HsmError::DeletionFailed(format!("Failed to delete object: {:?}", e)))?;
+    hsm.delete_object(object_id, object_type).map_err(|e| {
+        HsmError::from_client_object(e, object_id, object_type, |msg| {
+            HsmError::DeletionFailed(format!("Failed to delete object: {}", msg))
+        })
+    })?;
 
     Ok(())
 }
 
 /// List objects and return structured summaries that can be rendered in a table.
-pub fn list_object_summaries(client: &HsmClient) -> HsmResult<Vec<ObjectSummary>> {
+/// The device applies `filter` before anything is fetched per object, so only
+/// matching objects cost round trips; an empty filter lists every object.
+pub fn list_object_summaries(
+    client: &HsmClient,
+    filter: &[Filter],
+) -> HsmResult<Vec<ObjectSummary>> {
+    let cache = Mutex::new(PublicKeyCache::default());
+    list_object_summaries_with_progress(client, filter, &cache, |_, _| {})
+        .map(|listing| listing.objects)
+}
+
+/// Objects found by a listing, and how many public keys were reused from the cache.
+pub struct Listing {
+    pub objects: Vec<ObjectSummary>,
+    pub cached_public_keys: usize,
+}
+
+/// Like `list_object_summaries`, calling `progress(done, total)` after each
+/// object's details have been fetched so the UI can show how far along it is.
+/// Public keys whose object ID, type and sequence are in `cache` are reused
+/// instead of fetched; the others are fetched and added to it.
+#[instrument(
+    level = "debug",
+    skip_all,
+    fields(filters = filter.len()),
+    err(level = "warn", Display)
+)]
+pub fn list_object_summaries_with_progress(
+    client: &HsmClient,
+    filter: &[Filter],
+    cache: &Mutex<PublicKeyCache>,
+    mut progress: impl FnMut(usize, usize),
+) -> HsmResult<Listing> {
+    // Only a background listing uses the cache, so a poisoned lock just held stale keys
+    let mut cache = cache.lock().unwrap_or_else(PoisonError::into_inner);
     let hsm_client = client.client();
     let hsm = hsm_client
         .lock()
@@ -218,20 +1201,39 @@ pub fn list_object_summaries(client: &HsmClient) -> HsmResult<Vec<ObjectSummary>
 
     // Empty filter list = list all objects visible to this auth key
     let entries = hsm
-        .list_objects(&[])
-        .map_err(|e| HsmError::ListingFailed(format!("{:?}", e)))?;
+        .list_objects(filter)
+        .map_err(|e| HsmError::from_client(e, HsmError::ListingFailed))?;
     drop(hsm);
 
-    let mut summaries = Vec::new();
+    let total = entries.len();
+    let mut summaries = Vec::with_capacity(total);
+    let mut cached_public_keys = 0;
+    progress(0, total);
 
     for entry in entries {
         let info = get_object_info(client, entry.object_id, entry.object_type)?;
 
-        let public_key_hex = if info.object_type == Type::AsymmetricKey {
-            let public_key = get_public_key(client, info.object_id)?;
-            Some(hex::encode(&public_key.bytes))
+        let (public_key_hex, fingerprint_hex) = if info.object_type != Type::AsymmetricKey {
+            (None, None)
+        } else if let Some(cached) = cache.get(info.object_id, info.object_type, info.sequence) {
+            cached_public_keys += 1;
+            (
+                Some(cached.public_key_hex.clone()),
+                cached.fingerprint_hex.clone(),
+            )
         } else {
-            None
+            let public_key = get_public_key(client, info.object_id)?;
+            let fetched = CachedPublicKey {
+                public_key_hex: hex::encode(&public_key.bytes),
+                fingerprint_hex: public_key_fingerprint(&public_key).ok(),
+            };
+            cache.insert(
+                info.object_id,
+                info.object_type,
+                info.sequence,
+                fetched.clone(),
+            );
+            (Some(fetched.public_key_hex), fetched.fingerprint_hex)
         };
 
         summaries.push(ObjectSummary {
@@ -240,9 +1242,433 @@ pub fn list_object_summaries(client: &HsmClient) -> HsmResult<Vec<ObjectSummary>
             algorithm: info.algorithm,
             label: info.label,
             sequence: info.sequence,
+            capabilities: info.capabilities,
+            delegated_capabilities: info.delegated_capabilities,
+            domains: info.domains,
+            origin: info.origin,
             public_key_hex,
+            fingerprint_hex,
         });
+        progress(summaries.len(), total);
+    }
+
+    // A full listing shows every object left, so anything else was deleted
+    if filter.is_empty() {
+        let listed: Vec<_> = summaries
+            .iter()
+            .map(|summary| (summary.object_id, summary.object_type, summary.sequence))
+            .collect();
+        cache.retain_listed(&listed);
     }
 
-    Ok(summaries)
+    tracing::debug!(
+        objects = summaries.len(),
+        cached_public_keys,
+        "listed objects"
+    );
+    Ok(Listing {
+        objects: summaries,
+        cached_public_keys,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hsm::error::device_error;
+    use yubihsm::device::ErrorKind as DeviceErrorKind;
+
+    /// A fixed key per curve with two signatures over the same digest: a low-S
+    /// one and its high-S twin `(r, n - s)`. Both have `r` with the top bit set,
+    /// so the DER INTEGER carries a zero pad byte.
+    struct Vector {
+        algorithm: asymmetric::Algorithm,
+        /// Public key as the device returns it, `x || y`
+        public_key: &'static str,
+        digest: &'static str,
+        low_der: &'static str,
+        high_der: &'static str,
+        low_raw: &'static str,
+        high_raw: &'static str,
+    }
+
+    const VECTORS: [Vector; 3] = [
+        Vector {
+            algorithm: asymmetric::Algorithm::EcP256,
+            public_key: "3d04a7955fc96e7508b633aaa19f402520a731fd89604fd22a91d70512733b00\
+                f672a8ea62f8565489ef1d4781b1428db88f480d4b4efc7656e056792b3f70eb",
+            digest: "390e8c06ea4eab977d33746f47f67864e0da04cd1423432a2b23af3b057a2b94",
+            low_der: "3045022100e4397cdf47658102dac3a4dd87029771f95abb7656d658292ec0e4\
+                11940042f502200bffacf979e328dbbb388fcc301e41565884d9ccf67d496233\
+                176203e50c9459",
+            high_der: "3046022100e4397cdf47658102dac3a4dd87029771f95abb7656d658292ec0e4\
+                11940042f5022100f4005305861cd72544c77033cfe1bea9646220e0b09a5522\
+                c0a268bf175690f8",
+            low_raw: "e4397cdf47658102dac3a4dd87029771f95abb7656d658292ec0e411940042f5\
+                0bffacf979e328dbbb388fcc301e41565884d9ccf67d496233176203e50c9459",
+            high_raw: "e4397cdf47658102dac3a4dd87029771f95abb7656d658292ec0e411940042f5\
+                f4005305861cd72544c77033cfe1bea9646220e0b09a5522c0a268bf175690f8",
+        },
+        Vector {
+            algorithm: asymmetric::Algorithm::EcK256,
+            public_key: "2c0d50e3cc607d739a7e4cebb63de33b469824cfca0f6865f691309f077eed81\
+                6440383b0db047e932967b5c1f361c55c5203e259b0c82cf3ef54fdda4c5ba72",
+            digest: "390e8c06ea4eab977d33746f47f67864e0da04cd1423432a2b23af3b057a2b94",
+            low_der: "3045022100a157b22a7b4d4890fa8006dd6c02d3d8c8c123c5bbb7bfa8e4f6ea\
+                bdecd125dd02200358d2316a2cc22423f32e24b860b80b44ea6b4ea40bd05765\
+                9f700f6595ccd5",
+            high_der: "3046022100a157b22a7b4d4890fa8006dd6c02d3d8c8c123c5bbb7bfa8e4f6ea\
+                bdecd125dd022100fca72dce95d33ddbdc0cd1db479f47f375c471980b3ccfe4\
+                5a32ee7d6aa0746c",
+            low_raw: "a157b22a7b4d4890fa8006dd6c02d3d8c8c123c5bbb7bfa8e4f6eabdecd125dd\
+                0358d2316a2cc22423f32e24b860b80b44ea6b4ea40bd057659f700f6595ccd5",
+            high_raw: "a157b22a7b4d4890fa8006dd6c02d3d8c8c123c5bbb7bfa8e4f6eabdecd125dd\
+                fca72dce95d33ddbdc0cd1db479f47f375c471980b3ccfe45a32ee7d6aa0746c",
+        },
+        Vector {
+            algorithm: asymmetric::Algorithm::EcP384,
+            public_key: "fae4f79388277e3597dd11921c270a8bdd4249d584f7b74dcefbdb9d77abdd99\
+                f9e5a1a284b096b9949cd96ecbf36104552f03c5a5abeb0c8e91087105fcc9e9\
+                d8a6e9cd2a3027a2f71342b0ffb235b0f7f81d978316b305fda907bdebab4e88",
+            digest: "b3f3b4527a7b6de410893589ac42afaa4bb5bc2ca7d957e20d208132446dba3b\
+                bd16743a046151ed2da316b792ae02c8",
+            low_der: "3065023100e89839a217db2767d79d9afe83227c24ced67906d64a6cdd3e4023\
+                b148928037873a25272dde9040645c2d28c9ece78a0230027a0b174fd600820a\
+                d0c1c2db924b6d6c524cfde539052d0b7c8690fc0dbe119c84cc0976b42eaf60\
+                753b42e817e7a6",
+            high_der: "3066023100e89839a217db2767d79d9afe83227c24ced67906d64a6cdd3e4023\
+                b148928037873a25272dde9040645c2d28c9ece78a023100fd85f4e8b029ff7d\
+                f52f3e3d246db49293adb3021ac6fad2bbe6c6f0f8296fcdbb9541a8d1fc78cb\
+                8c76de27e4ad41cd",
+            low_raw: "e89839a217db2767d79d9afe83227c24ced67906d64a6cdd3e4023b148928037\
+                873a25272dde9040645c2d28c9ece78a027a0b174fd600820ad0c1c2db924b6d\
+                6c524cfde539052d0b7c8690fc0dbe119c84cc0976b42eaf60753b42e817e7a6",
+            high_raw: "e89839a217db2767d79d9afe83227c24ced67906d64a6cdd3e4023b148928037\
+                873a25272dde9040645c2d28c9ece78afd85f4e8b029ff7df52f3e3d246db492\
+                93adb3021ac6fad2bbe6c6f0f8296fcdbb9541a8d1fc78cb8c76de27e4ad41cd",
+        },
+    ];
+
+    fn bytes(hex_str: &str) -> Vec<u8> {
+        hex::decode(hex_str).unwrap()
+    }
+
+    fn verifies(vector: &Vector, signature: &[u8]) -> bool {
+        let public_key = PublicKey {
+            algorithm: vector.algorithm,
+            bytes: bytes(vector.public_key),
+        };
+        verify_ecdsa_digest(
+            0x0001,
+            &public_key,
+            &bytes(vector.digest),
+            signature,
+            "test",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn to_raw_pads_each_half_to_the_curve_scalar() {
+        for vector in &VECTORS {
+            let raw = to_raw(&bytes(vector.low_der), vector.algorithm).unwrap();
+            assert_eq!(raw, bytes(vector.low_raw), "{:?}", vector.algorithm);
+            let scalar_len = ecdsa_digest_len(vector.algorithm).unwrap();
+            assert_eq!(raw.len(), 2 * scalar_len, "{:?}", vector.algorithm);
+        }
+    }
+
+    #[test]
+    fn to_raw_keeps_raw_input() {
+        for vector in &VECTORS {
+            let raw = bytes(vector.high_raw);
+            assert_eq!(to_raw(&raw, vector.algorithm).unwrap(), raw);
+        }
+    }
+
+    #[test]
+    fn to_der_round_trips_raw() {
+        for vector in &VECTORS {
+            let der = to_der(&bytes(vector.low_raw), vector.algorithm).unwrap();
+            assert_eq!(der, bytes(vector.low_der), "{:?}", vector.algorithm);
+            let raw = to_raw(&der, vector.algorithm).unwrap();
+            assert_eq!(raw, bytes(vector.low_raw), "{:?}", vector.algorithm);
+
+            let der = to_der(&bytes(vector.high_raw), vector.algorithm).unwrap();
+            assert_eq!(der, bytes(vector.high_der), "{:?}", vector.algorithm);
+        }
+    }
+
+    #[test]
+    fn both_encodings_verify() {
+        for vector in &VECTORS {
+            assert!(
+                verifies(vector, &bytes(vector.low_der)),
+                "{:?}",
+                vector.algorithm
+            );
+            assert!(
+                verifies(vector, &bytes(vector.low_raw)),
+                "{:?}",
+                vector.algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn every_curve_detects_the_encoding_the_same_way() {
+        for vector in &VECTORS {
+            for (signature, encoding) in [
+                (vector.low_der, SignatureEncoding::Der),
+                (vector.high_der, SignatureEncoding::Der),
+                (vector.low_raw, SignatureEncoding::Raw),
+                (vector.high_raw, SignatureEncoding::Raw),
+            ] {
+                let signature = bytes(signature);
+                let components = ecdsa_components(&signature, vector.algorithm).unwrap();
+                assert_eq!(components.encoding, encoding, "{:?}", vector.algorithm);
+                assert_eq!(
+                    SignatureEncoding::detect(&signature),
+                    encoding,
+                    "{:?}",
+                    vector.algorithm
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn prefixed_public_key_verifies_too() {
+        for vector in &VECTORS {
+            let public_key = PublicKey {
+                algorithm: vector.algorithm,
+                bytes: sec1_uncompressed(&bytes(vector.public_key)),
+            };
+            assert_eq!(public_key.bytes.len() % 2, 1);
+            assert!(
+                verify_ecdsa_digest(
+                    0x0001,
+                    &public_key,
+                    &bytes(vector.digest),
+                    &bytes(vector.low_der),
+                    "test",
+                )
+                .unwrap(),
+                "{:?}",
+                vector.algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn high_s_vectors_are_detected() {
+        for vector in &VECTORS {
+            for signature in [vector.high_der, vector.high_raw] {
+                assert!(
+                    is_high_s(&bytes(signature), vector.algorithm).unwrap(),
+                    "{:?}",
+                    vector.algorithm
+                );
+            }
+            for signature in [vector.low_der, vector.low_raw] {
+                assert!(
+                    !is_high_s(&bytes(signature), vector.algorithm).unwrap(),
+                    "{:?}",
+                    vector.algorithm
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn components_report_high_s() {
+        for vector in &VECTORS {
+            let high = ecdsa_components(&bytes(vector.high_der), vector.algorithm).unwrap();
+            assert!(!high.is_low_s, "{:?}", vector.algorithm);
+            assert_eq!(high.encoding, SignatureEncoding::Der);
+            let low = ecdsa_components(&bytes(vector.low_raw), vector.algorithm).unwrap();
+            assert!(low.is_low_s, "{:?}", vector.algorithm);
+            assert_eq!(low.encoding, SignatureEncoding::Raw);
+        }
+    }
+
+    #[test]
+    fn high_s_check_refuses_other_algorithms() {
+        // An Ed25519 signature is 64 bytes and would pass for a raw P-256 one
+        let signature = bytes(VECTORS[0].high_raw);
+        assert!(is_high_s(&signature, asymmetric::Algorithm::Ed25519).is_err());
+        assert!(is_high_s(&signature, asymmetric::Algorithm::Rsa2048).is_err());
+    }
+
+    #[test]
+    fn normalized_signature_still_verifies() {
+        for vector in &VECTORS {
+            for signature in [vector.high_der, vector.high_raw] {
+                let normalized = normalize_low_s(&bytes(signature), vector.algorithm).unwrap();
+                assert_eq!(normalized, bytes(vector.low_der), "{:?}", vector.algorithm);
+                assert!(verifies(vector, &normalized), "{:?}", vector.algorithm);
+            }
+        }
+    }
+
+    #[test]
+    fn normalize_keeps_low_s_signature() {
+        for vector in &VECTORS {
+            let normalized = normalize_low_s(&bytes(vector.low_der), vector.algorithm).unwrap();
+            assert_eq!(normalized, bytes(vector.low_der), "{:?}", vector.algorithm);
+        }
+    }
+
+    #[test]
+    fn normalize_refuses_unknown_curve() {
+        let signature = bytes(VECTORS[0].high_der);
+        assert!(normalize_low_s(&signature, asymmetric::Algorithm::EcBp256).is_err());
+        assert!(normalize_low_s(&signature, asymmetric::Algorithm::Ed25519).is_err());
+    }
+
+    #[test]
+    fn conversion_refuses_curves_without_host_support() {
+        let signature = bytes(VECTORS[0].low_der);
+        for algorithm in [
+            asymmetric::Algorithm::Ed25519,
+            asymmetric::Algorithm::EcP521,
+        ] {
+            assert!(to_raw(&signature, algorithm).is_err());
+            assert!(to_der(&signature, algorithm).is_err());
+        }
+    }
+
+    /// Verify `token` with a standard JWS implementation and return its payload.
+    fn jsonwebtoken_verify(
+        token: &str,
+        alg: jsonwebtoken::Algorithm,
+        key: &jsonwebtoken::DecodingKey,
+    ) -> serde_json::Value {
+        let mut validation = jsonwebtoken::Validation::new(alg);
+        // The payload is arbitrary JSON, not a claims set
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        jsonwebtoken::decode::<serde_json::Value>(token, key, &validation)
+            .unwrap()
+            .claims
+    }
+
+    /// JWK-style `(x, y)` coordinates of an uncompressed SEC1 point.
+    fn ec_components(point: &[u8]) -> (String, String) {
+        let (x, y) = point[1..].split_at((point.len() - 1) / 2);
+        (URL_SAFE_NO_PAD.encode(x), URL_SAFE_NO_PAD.encode(y))
+    }
+
+    const JWS_PAYLOAD: &[u8] = br#"{"sub":"hsm-demo"}"#;
+
+    #[test]
+    fn jws_alg_follows_the_key() {
+        assert_eq!(jws_alg(asymmetric::Algorithm::EcP256), Some("ES256"));
+        assert_eq!(jws_alg(asymmetric::Algorithm::EcP384), Some("ES384"));
+        assert_eq!(jws_alg(asymmetric::Algorithm::EcK256), Some("ES256K"));
+        assert_eq!(jws_alg(asymmetric::Algorithm::Ed25519), Some("EdDSA"));
+        assert_eq!(jws_alg(asymmetric::Algorithm::Rsa2048), None);
+    }
+
+    #[test]
+    fn es256_jws_verifies_with_jsonwebtoken() {
+        use signature::Signer;
+
+        let key = p256::ecdsa::SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let signing_input = jws_signing_input("ES256", JWS_PAYLOAD);
+        // The device returns DER, which the JWS must carry as raw r || s
+        let device: p256::ecdsa::Signature = key.sign(signing_input.as_bytes());
+        let token = finish_jws(
+            signing_input,
+            asymmetric::Algorithm::EcP256,
+            device.to_der().as_bytes(),
+        )
+        .unwrap();
+
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.alg, jsonwebtoken::Algorithm::ES256);
+        assert_eq!(header.typ, None);
+        let (x, y) = ec_components(key.verifying_key().to_encoded_point(false).as_bytes());
+        let decoding_key = jsonwebtoken::DecodingKey::from_ec_components(&x, &y).unwrap();
+        let payload = jsonwebtoken_verify(&token, jsonwebtoken::Algorithm::ES256, &decoding_key);
+        assert_eq!(payload["sub"], "hsm-demo");
+    }
+
+    #[test]
+    fn es384_jws_verifies_with_jsonwebtoken() {
+        use signature::Signer;
+
+        let key = p384::ecdsa::SigningKey::from_slice(&[0x22; 48]).unwrap();
+        let signing_input = jws_signing_input("ES384", JWS_PAYLOAD);
+        let device: p384::ecdsa::Signature = key.sign(signing_input.as_bytes());
+        let token = finish_jws(
+            signing_input,
+            asymmetric::Algorithm::EcP384,
+            device.to_der().as_bytes(),
+        )
+        .unwrap();
+
+        let (x, y) = ec_components(key.verifying_key().to_encoded_point(false).as_bytes());
+        let decoding_key = jsonwebtoken::DecodingKey::from_ec_components(&x, &y).unwrap();
+        let payload = jsonwebtoken_verify(&token, jsonwebtoken::Algorithm::ES384, &decoding_key);
+        assert_eq!(payload["sub"], "hsm-demo");
+    }
+
+    #[test]
+    fn eddsa_jws_verifies_with_jsonwebtoken() {
+        use signature::Signer;
+
+        let key = ed25519_dalek::SigningKey::from_bytes(&[0x33; 32]);
+        let signing_input = jws_signing_input("EdDSA", JWS_PAYLOAD);
+        let device = key.sign(signing_input.as_bytes()).to_bytes();
+        let token = finish_jws(signing_input, asymmetric::Algorithm::Ed25519, &device).unwrap();
+
+        let x = URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes());
+        let decoding_key = jsonwebtoken::DecodingKey::from_ed_components(&x).unwrap();
+        let payload = jsonwebtoken_verify(&token, jsonwebtoken::Algorithm::EdDSA, &decoding_key);
+        assert_eq!(payload["sub"], "hsm-demo");
+    }
+
+    /// An expired session while looking up the signing key passes through
+    /// `signing_key_lookup_error` unchanged, so `retry_expired` renegotiates and
+    /// tries again instead of reporting a missing key. Covers the error mapping
+    /// and the retry, not a device round trip.
+    #[test]
+    fn expired_key_lookup_is_left_for_the_retry() {
+        let renegotiated = std::cell::Cell::new(false);
+        let result = super::super::client::retry_expired(
+            || {
+                if renegotiated.get() {
+                    return Ok(asymmetric::Algorithm::EcP256);
+                }
+                let expired =
+                    HsmError::SessionExpired(device_error(DeviceErrorKind::InvalidSession));
+                Err(signing_key_lookup_error(0x0001, expired, |_| false))
+            },
+            || {
+                renegotiated.set(true);
+                Ok(())
+            },
+        );
+
+        assert_eq!(result.unwrap(), asymmetric::Algorithm::EcP256);
+        assert!(renegotiated.get());
+    }
+
+    #[test]
+    fn missing_signing_key_names_what_the_id_is() {
+        let missing = || HsmError::ObjectNotFound {
+            id: 0x0002,
+            ty: Type::AsymmetricKey,
+            source: device_error(DeviceErrorKind::ObjectNotFound),
+        };
+
+        let wrap_key = signing_key_lookup_error(0x0002, missing(), |ty| ty == Type::WrapKey);
+        assert!(matches!(wrap_key, HsmError::InvalidKey(msg) if msg.contains("WrapKey")));
+
+        let nothing = signing_key_lookup_error(0x0002, missing(), |_| false);
+        assert!(
+            matches!(nothing, HsmError::InvalidKey(msg) if msg.starts_with("No asymmetric key"))
+        );
+    }
 }
@@ -0,0 +1,379 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult};
+use super::operations::{
+    EcdsaComponents, HashAlg, der_read_tlv, ecdsa_components, get_public_key, is_high_s,
+    parse_timestamped_payload, sign, sign_prehashed, timestamped_payload, to_raw, verify,
+    verify_prehashed,
+};
+use super::spki::public_key_fingerprint;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use yubihsm::asymmetric;
+use yubihsm::object::Id;
+
+/// Encoding of an ECDSA signature handed to a verifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureEncoding {
+    /// ASN.1 DER `SEQUENCE { r, s }`, as returned by the device
+    Der,
+    /// Fixed-width `r || s`, as used by JOSE/WebCrypto
+    Raw,
+}
+
+impl SignatureEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            SignatureEncoding::Der => "DER",
+            SignatureEncoding::Raw => "raw r||s",
+        }
+    }
+
+    /// Guess the form of `signature` the way signatures are parsed: DER is
+    /// tried first, so anything that is exactly one DER SEQUENCE counts as DER.
+    pub fn detect(signature: &[u8]) -> Self {
+        match der_read_tlv(signature) {
+            Some((0x30, _, rest)) if rest.is_empty() => SignatureEncoding::Der,
+            _ => SignatureEncoding::Raw,
+        }
+    }
+}
+
+/// Result of signing a message on the device, independent of how it is presented.
+#[derive(Clone, Debug)]
+pub struct SignOutcome {
+    pub key_id: Id,
+    /// Message as given by the caller
+    pub message: Vec<u8>,
+    /// Timestamp prefixed to the message, if timestamp mode was used
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Exact bytes that were hashed and signed
+    pub payload: Vec<u8>,
+    /// Signature returned by the device: DER for ECDSA, `R || S` for Ed25519,
+    /// PKCS#1 v1.5 for RSA
+    pub signature: Vec<u8>,
+    /// ECDSA `signature` as raw `r || s`, if it could be converted
+    pub signature_raw: Option<Vec<u8>>,
+    /// Algorithm of the signing key, if its public key could be fetched
+    pub algorithm: Option<asymmetric::Algorithm>,
+    /// `r`, `s` and encoding of an ECDSA `signature`, if the curve is known
+    pub ecdsa_components: Option<EcdsaComponents>,
+    /// Hash applied to `payload` before an ECDSA signature; None when the
+    /// payload is itself the digest
+    pub hash_alg: Option<HashAlg>,
+    /// SPKI fingerprint of the signing key at signing time, if it could be fetched
+    pub key_fingerprint: Option<String>,
+    /// Time spent on the device round trip
+    pub elapsed: Duration,
+}
+
+/// Result of verifying a signature, independent of how it is presented.
+#[derive(Clone, Debug)]
+pub struct VerifyOutcome {
+    pub key_id: Id,
+    /// Exact bytes the signature was checked against
+    pub payload: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub encoding: SignatureEncoding,
+    pub valid: bool,
+    /// The signature did not verify and the key now at `key_id` has a different
+    /// fingerprint than the one that made it, i.e. the key was replaced
+    pub key_changed: bool,
+    /// `s` is in the upper half of the curve order (non-canonical), whether or
+    /// not the signature verifies
+    pub high_s: bool,
+    /// Time spent fetching the public key and verifying
+    pub elapsed: Duration,
+}
+
+/// Result of signing a file's digest on the device.
+#[derive(Clone, Debug)]
+pub struct FileSignOutcome {
+    pub key_id: Id,
+    pub path: PathBuf,
+    /// Number of bytes hashed
+    pub size: u64,
+    pub hash_alg: HashAlg,
+    /// Digest of the file contents that was signed
+    pub digest: Vec<u8>,
+    /// DER ECDSA signature over `digest`
+    pub signature: Vec<u8>,
+    /// Time spent hashing and on the device round trip
+    pub elapsed: Duration,
+}
+
+/// Verdict on a timestamped signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampStatus {
+    /// Signature verifies and the timestamp is within the freshness window
+    Fresh,
+    /// Signature verifies but the timestamp is outside the freshness window
+    Stale,
+    /// Signature does not verify; the timestamp cannot be trusted
+    InvalidSignature,
+}
+
+/// Result of checking a timestamped payload's signature and age.
+#[derive(Clone, Debug)]
+pub struct TimestampedVerifyResult {
+    /// The signature check the verdict is based on
+    pub verify: VerifyOutcome,
+    pub status: TimestampStatus,
+    /// Time embedded in the payload
+    pub timestamp: DateTime<Utc>,
+    /// Time between `timestamp` and the check; negative if it lies in the future
+    pub age: TimeDelta,
+    pub max_age: Duration,
+}
+
+/// Classify the timestamped payload checked by `verify`. A timestamp ahead of
+/// `now` is fresh only within the same window, which absorbs clock skew but
+/// rejects post-dated payloads.
+fn timestamp_freshness(
+    verify: VerifyOutcome,
+    max_age: Duration,
+    now: DateTime<Utc>,
+) -> HsmResult<TimestampedVerifyResult> {
+    let (timestamp, _) = parse_timestamped_payload(&verify.payload)?;
+    let age = now - timestamp;
+    let window = TimeDelta::from_std(max_age).unwrap_or(TimeDelta::MAX);
+
+    let status = if !verify.valid {
+        TimestampStatus::InvalidSignature
+    } else if age.abs() <= window {
+        TimestampStatus::Fresh
+    } else {
+        TimestampStatus::Stale
+    };
+
+    Ok(TimestampedVerifyResult {
+        verify,
+        status,
+        timestamp,
+        age,
+        max_age,
+    })
+}
+
+/// Sign `message` with `key_id`, optionally prefixing `timestamp` to the payload.
+/// Without a `hash_alg` the message is taken as a precomputed ECDSA digest and
+/// signed as is.
+pub fn sign_message(
+    client: &HsmClient,
+    key_id: Id,
+    message: &[u8],
+    timestamp: Option<DateTime<Utc>>,
+    hash_alg: Option<HashAlg>,
+) -> HsmResult<SignOutcome> {
+    let payload = match timestamp {
+        Some(_) if hash_alg.is_none() => {
+            return Err(HsmError::InvalidInput(
+                "A precomputed digest cannot be prefixed with a timestamp".to_string(),
+            ));
+        }
+        Some(timestamp) => timestamped_payload(timestamp, message),
+        None => message.to_vec(),
+    };
+
+    let started = Instant::now();
+    let signature = match hash_alg {
+        Some(hash_alg) => sign(client, key_id, &payload, hash_alg)?,
+        None => sign_prehashed(client, key_id, &payload)?,
+    };
+    let elapsed = started.elapsed();
+
+    let public_key = get_public_key(client, key_id).ok();
+    let key_fingerprint = public_key
+        .as_ref()
+        .and_then(|public_key| public_key_fingerprint(public_key).ok());
+    let algorithm = public_key.map(|public_key| public_key.algorithm);
+    // Only ECDSA signatures have an r || s form; to_raw refuses the rest
+    let signature_raw = algorithm.and_then(|algorithm| to_raw(&signature, algorithm).ok());
+    let ecdsa_components = algorithm.and_then(|algorithm| ecdsa_components(&signature, algorithm));
+
+    Ok(SignOutcome {
+        key_id,
+        message: message.to_vec(),
+        timestamp,
+        payload,
+        signature,
+        signature_raw,
+        algorithm,
+        ecdsa_components,
+        hash_alg,
+        key_fingerprint,
+        elapsed,
+    })
+}
+
+/// Sign the file at `path` with the ECDSA key `key_id`. The file is hashed on
+/// the host while it is read, and only the digest goes to the device, so the
+/// hash must match the key's curve. Ed25519 and RSA keys sign whole messages on
+/// the device and are refused.
+pub fn sign_file(
+    client: &HsmClient,
+    key_id: Id,
+    path: &Path,
+    hash_alg: HashAlg,
+) -> HsmResult<FileSignOutcome> {
+    let started = Instant::now();
+    let file = File::open(path)
+        .map_err(|e| HsmError::InvalidInput(format!("Failed to open {}: {}", path.display(), e)))?;
+    let (digest, size) = hash_alg
+        .digest_reader(BufReader::new(file))
+        .map_err(|e| HsmError::InvalidInput(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let signature = sign_prehashed(client, key_id, &digest)?;
+
+    Ok(FileSignOutcome {
+        key_id,
+        path: path.to_path_buf(),
+        size,
+        hash_alg,
+        digest,
+        signature,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Verify `signature` over `payload` against the public key of `key_id`; without
+/// a `hash_alg` the payload is the digest itself, as in `sign_message`.
+/// `signed_fingerprint` is the key fingerprint recorded at signing time; on a
+/// mismatch it is compared with the current key to detect a replaced key.
+pub fn verify_message(
+    client: &HsmClient,
+    key_id: Id,
+    payload: &[u8],
+    signature: &[u8],
+    encoding: SignatureEncoding,
+    signed_fingerprint: Option<&str>,
+    hash_alg: Option<HashAlg>,
+) -> HsmResult<VerifyOutcome> {
+    let started = Instant::now();
+    let valid = match hash_alg {
+        Some(hash_alg) => verify(client, key_id, payload, signature, hash_alg)?,
+        None => verify_prehashed(client, key_id, payload, signature)?,
+    };
+    let elapsed = started.elapsed();
+
+    let key_changed = !valid
+        && signed_fingerprint.is_some_and(|signed| {
+            key_fingerprint(client, key_id).is_some_and(|current| current != signed)
+        });
+    // Low/high S is judged against the key's own curve order; is_high_s refuses
+    // Ed25519, RSA and curves the host cannot handle, which count as not high
+    let high_s = get_public_key(client, key_id)
+        .is_ok_and(|public_key| is_high_s(signature, public_key.algorithm).unwrap_or(false));
+
+    Ok(VerifyOutcome {
+        key_id,
+        payload: payload.to_vec(),
+        signature: signature.to_vec(),
+        encoding,
+        valid,
+        key_changed,
+        high_s,
+        elapsed,
+    })
+}
+
+/// Verify `signature` over a payload built by `timestamped_payload`, as
+/// `verify_message` does, and report whether its embedded timestamp is at
+/// most `max_age` away from `now`.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_timestamped(
+    client: &HsmClient,
+    key_id: Id,
+    payload: &[u8],
+    signature: &[u8],
+    encoding: SignatureEncoding,
+    signed_fingerprint: Option<&str>,
+    hash_alg: Option<HashAlg>,
+    max_age: Duration,
+    now: DateTime<Utc>,
+) -> HsmResult<TimestampedVerifyResult> {
+    // Reject a malformed payload before spending a device round trip on it
+    parse_timestamped_payload(payload)?;
+    let verify = verify_message(
+        client,
+        key_id,
+        payload,
+        signature,
+        encoding,
+        signed_fingerprint,
+        hash_alg,
+    )?;
+    timestamp_freshness(verify, max_age, now)
+}
+
+/// Fingerprint of the current public key of `key_id`, or None if unavailable.
+fn key_fingerprint(client: &HsmClient, key_id: Id) -> Option<String> {
+    get_public_key(client, key_id)
+        .and_then(|public_key| public_key_fingerprint(&public_key))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hsm::sources::{Clock, FixedClock};
+
+    fn clock() -> FixedClock {
+        FixedClock("2026-01-02T03:04:05Z".parse().unwrap())
+    }
+
+    #[test]
+    fn timestamped_payload_is_exact_under_a_fixed_clock() {
+        let payload = timestamped_payload(clock().now(), b"hello");
+
+        assert_eq!(payload, b"2026-01-02T03:04:05Z|hello");
+    }
+
+    /// Outcome of a signature check over `payload` that gave `valid`.
+    fn checked(payload: &[u8], valid: bool) -> VerifyOutcome {
+        VerifyOutcome {
+            key_id: 0x0001,
+            payload: payload.to_vec(),
+            signature: Vec::new(),
+            encoding: SignatureEncoding::Der,
+            valid,
+            key_changed: false,
+            high_s: false,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn freshness_follows_the_clock() {
+        let payload = timestamped_payload(clock().now(), b"hello");
+        let max_age = Duration::from_secs(300);
+        let at = |secs: i64| clock().now() + TimeDelta::seconds(secs);
+
+        let fresh = timestamp_freshness(checked(&payload, true), max_age, at(300)).unwrap();
+        assert_eq!(fresh.status, TimestampStatus::Fresh);
+        assert_eq!(fresh.age, TimeDelta::seconds(300));
+        assert!(fresh.verify.valid);
+
+        let stale = timestamp_freshness(checked(&payload, true), max_age, at(301)).unwrap();
+        assert_eq!(stale.status, TimestampStatus::Stale);
+
+        let post_dated = timestamp_freshness(checked(&payload, true), max_age, at(-301)).unwrap();
+        assert_eq!(post_dated.status, TimestampStatus::Stale);
+
+        let forged = timestamp_freshness(checked(&payload, false), max_age, at(0)).unwrap();
+        assert_eq!(forged.status, TimestampStatus::InvalidSignature);
+    }
+
+    #[test]
+    fn payload_without_a_timestamp_is_rejected() {
+        let result = timestamp_freshness(
+            checked(b"hello", true),
+            Duration::from_secs(300),
+            clock().now(),
+        );
+
+        assert!(matches!(result, Err(HsmError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Serialize;
+use yubihsm::object::Id;
+
+/// Target of the per-operation log events, so they can be filtered on their own
+const OPERATION_LOG_TARGET: &str = "hsm_demo::operations";
+
+/// How much of an operation record may be written to a log or support bundle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedactionLevel {
+    /// Everything, including input content; for private debugging only
+    Full,
+    /// Operation, key IDs, outcome and input length, but no input content
+    #[default]
+    Redacted,
+    /// Operation names and outcomes only
+    Minimal,
+}
+
+impl RedactionLevel {
+    /// Parse `full`, `redacted` or `minimal` (case-insensitive).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "full" => Some(RedactionLevel::Full),
+            "redacted" => Some(RedactionLevel::Redacted),
+            "minimal" => Some(RedactionLevel::Minimal),
+            _ => None,
+        }
+    }
+}
+
+/// One operation as recorded before redaction.
+#[derive(Clone, Debug)]
+pub struct OperationRecord {
+    /// RFC3339 UTC time of the operation
+    pub at: String,
+    /// Short operation name, e.g. "sign" or "verify"
+    pub operation: &'static str,
+    pub key_id: Option<Id>,
+    /// Message or other user-supplied content the operation consumed
+    pub input: Vec<u8>,
+    pub succeeded: bool,
+    /// Error message when the operation failed
+    pub error: Option<String>,
+}
+
+/// What of an `OperationRecord` remains after redaction; fields that a level
+/// drops are left out of the serialized form entirely.
+#[derive(Clone, Debug, Serialize)]
+pub struct RedactedRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+    pub operation: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_id: Option<Id>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_len: Option<usize>,
+    /// Input as hex, only at `Full`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_hex: Option<String>,
+    pub succeeded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Reduce `record` to what `level` allows. Anything that writes operation
+/// records to disk applies this first, so content never reaches a file unless
+/// the user picked `Full`. Error messages are kept only at `Full`, since they
+/// can quote labels or input back.
+pub fn redact(record: &OperationRecord, level: RedactionLevel) -> RedactedRecord {
+    let detailed = level != RedactionLevel::Minimal;
+    let full = level == RedactionLevel::Full;
+
+    RedactedRecord {
+        at: detailed.then(|| record.at.clone()),
+        operation: record.operation,
+        key_id: record.key_id.filter(|_| detailed),
+        input_len: detailed.then_some(record.input.len()),
+        input_hex: full.then(|| hex::encode(&record.input)),
+        succeeded: record.succeeded,
+        error: record.error.clone().filter(|_| full),
+    }
+}
+
+/// Log `record` as one JSON event, reduced to what `level` allows.
+pub fn log_operation(record: &OperationRecord, level: RedactionLevel) {
+    match serde_json::to_string(&redact(record, level)) {
+        Ok(json) => tracing::info!(target: OPERATION_LOG_TARGET, record = %json, "operation"),
+        Err(e) => tracing::warn!(error = %e, "cannot serialize operation record"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> OperationRecord {
+        OperationRecord {
+            at: "2026-01-02T03:04:05Z".into(),
+            operation: "sign",
+            key_id: Some(0x0010),
+            input: b"secret".to_vec(),
+            succeeded: false,
+            error: Some("label 'secret' not found".into()),
+        }
+    }
+
+    #[test]
+    fn redacted_keeps_lengths_and_ids_but_no_content() {
+        let json = serde_json::to_string(&redact(&record(), RedactionLevel::Redacted)).unwrap();
+
+        assert!(json.contains("\"input_len\":6"));
+        assert!(json.contains("\"key_id\":16"));
+        assert!(!json.contains("secret"));
+        assert!(!json.contains(&hex::encode("secret")));
+    }
+
+    #[test]
+    fn minimal_keeps_only_operation_and_outcome() {
+        let json = serde_json::to_string(&redact(&record(), RedactionLevel::Minimal)).unwrap();
+
+        assert_eq!(json, r#"{"operation":"sign","succeeded":false}"#);
+    }
+
+    #[test]
+    fn full_keeps_content_as_hex() {
+        let redacted = redact(&record(), RedactionLevel::Full);
+
+        assert_eq!(
+            redacted.input_hex.as_deref(),
+            Some(hex::encode("secret").as_str())
+        );
+        assert!(redacted.error.is_some());
+    }
+}
@@ -0,0 +1,55 @@
+use super::client::HsmClient;
+use super::error::HsmResult;
+use super::operations::get_random;
+use chrono::{DateTime, Utc};
+
+/// Source of the current time. Features that embed a time take one of these so
+/// their output can be reproduced exactly with `FixedClock`.
+pub trait Clock {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system wall clock; used everywhere outside tests.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock stuck at one instant, for deterministic payloads and file names.
+#[cfg(test)]
+pub struct FixedClock(pub DateTime<Utc>);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+/// Source of random bytes.
+pub trait RandomSource {
+    fn random_bytes(&self, len: usize) -> HsmResult<Vec<u8>>;
+}
+
+/// The device RNG (`get-pseudo-random`); used everywhere outside tests.
+pub struct DeviceRandom<'a>(pub &'a HsmClient);
+
+impl RandomSource for DeviceRandom<'_> {
+    fn random_bytes(&self, len: usize) -> HsmResult<Vec<u8>> {
+        get_random(self.0, len)
+    }
+}
+
+/// Predictable bytes counting up from `seed`, for deterministic tests.
+#[cfg(test)]
+pub struct FixedRandom(pub u8);
+
+#[cfg(test)]
+impl RandomSource for FixedRandom {
+    fn random_bytes(&self, len: usize) -> HsmResult<Vec<u8>> {
+        Ok((0..len).map(|i| self.0.wrapping_add(i as u8)).collect())
+    }
+}
@@ -0,0 +1,367 @@
+use super::error::{HsmError, HsmResult};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use p256::pkcs8::DecodePublicKey;
+use sha2::{Digest, Sha256};
+use yubihsm::asymmetric::{self, PublicKey};
+
+/// DER tags used when building SubjectPublicKeyInfo structures
+const TAG_INTEGER: u8 = 0x02;
+const TAG_BIT_STRING: u8 = 0x03;
+const TAG_NULL: u8 = 0x05;
+const TAG_SEQUENCE: u8 = 0x30;
+
+/// id-ecPublicKey (1.2.840.10045.2.1)
+const OID_EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// rsaEncryption (1.2.840.113549.1.1.1)
+const OID_RSA_ENCRYPTION: &[u8] = &[
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01,
+];
+/// id-Ed25519 (1.3.101.112)
+const OID_ED25519: &[u8] = &[0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// Public exponent used by YubiHSM2 generated RSA keys
+const RSA_PUBLIC_EXPONENT: &[u8] = &[0x01, 0x00, 0x01];
+
+/// Named curve OID for an EC algorithm, or None for non-EC algorithms.
+fn curve_oid(algorithm: asymmetric::Algorithm) -> Option<&'static [u8]> {
+    use asymmetric::Algorithm::*;
+
+    let oid: &'static [u8] = match algorithm {
+        EcP224 => &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x21],
+        EcP256 => &[0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07],
+        EcP384 => &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x22],
+        EcP521 => &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x23],
+        EcK256 => &[0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a],
+        EcBp256 => &[
+            0x06, 0x09, 0x2b, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x07,
+        ],
+        EcBp384 => &[
+            0x06, 0x09, 0x2b, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0b,
+        ],
+        EcBp512 => &[
+            0x06, 0x09, 0x2b, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x0d,
+        ],
+        _ => return None,
+    };
+
+    Some(oid)
+}
+
+/// Size in bytes of one coordinate for an EC algorithm, or None for non-EC algorithms.
+fn ec_field_len(algorithm: asymmetric::Algorithm) -> Option<usize> {
+    use asymmetric::Algorithm::*;
+
+    match algorithm {
+        EcP224 => Some(28),
+        EcP256 | EcK256 | EcBp256 => Some(32),
+        EcP384 | EcBp384 => Some(48),
+        EcBp512 => Some(64),
+        EcP521 => Some(66),
+        _ => None,
+    }
+}
+
+/// Modulus size in bytes for an RSA algorithm, or None for non-RSA algorithms.
+fn rsa_modulus_len(algorithm: asymmetric::Algorithm) -> Option<usize> {
+    use asymmetric::Algorithm::*;
+
+    match algorithm {
+        Rsa2048 => Some(256),
+        Rsa3072 => Some(384),
+        Rsa4096 => Some(512),
+        _ => None,
+    }
+}
+
+/// Refuse key bytes whose length does not match the declared algorithm.
+fn check_key_len(public_key: &PublicKey, expected_len: usize) -> HsmResult<()> {
+    if public_key.bytes.len() != expected_len {
+        return Err(HsmError::InvalidKey(format!(
+            "{:?} public key is {} bytes, expected {}",
+            public_key.algorithm,
+            public_key.bytes.len(),
+            expected_len
+        )));
+    }
+    Ok(())
+}
+
+/// Parse `der` back with the curve's own crate and check it yields `point`, so
+/// strict consumers never receive an SPKI whose parameters disagree with the key.
+/// Curves without a RustCrypto implementation here are trusted as built.
+fn check_ec_spki(algorithm: asymmetric::Algorithm, der: &[u8], point: &[u8]) -> HsmResult<()> {
+    use asymmetric::Algorithm::*;
+
+    let parsed = match algorithm {
+        EcP256 => p256::PublicKey::from_public_key_der(der)
+            .map(|key| key.to_encoded_point(false).as_bytes().to_vec()),
+        EcP384 => p384::PublicKey::from_public_key_der(der)
+            .map(|key| key.to_encoded_point(false).as_bytes().to_vec()),
+        EcK256 => k256::PublicKey::from_public_key_der(der)
+            .map(|key| key.to_encoded_point(false).as_bytes().to_vec()),
+        _ => return Ok(()),
+    }
+    .map_err(|e| {
+        HsmError::InvalidKey(format!(
+            "Encoded {:?} SubjectPublicKeyInfo does not parse: {}",
+            algorithm, e
+        ))
+    })?;
+
+    if parsed != point {
+        return Err(HsmError::InvalidKey(format!(
+            "Encoded {:?} SubjectPublicKeyInfo does not round-trip to the device key",
+            algorithm
+        )));
+    }
+    Ok(())
+}
+
+/// Encode a DER length prefix.
+fn der_length(len: usize) -> Vec<u8> {
+    if len < 0x80 {
+        return vec![len as u8];
+    }
+
+    let bytes: Vec<u8> = len
+        .to_be_bytes()
+        .into_iter()
+        .skip_while(|b| *b == 0)
+        .collect();
+    let mut out = vec![0x80 | bytes.len() as u8];
+    out.extend_from_slice(&bytes);
+    out
+}
+
+/// Encode a DER tag-length-value triple.
+fn der_tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    out.extend_from_slice(&der_length(content.len()));
+    out.extend_from_slice(content);
+    out
+}
+
+/// Encode big-endian bytes as a non-negative DER INTEGER.
+fn der_unsigned_integer(bytes: &[u8]) -> Vec<u8> {
+    let trimmed: Vec<u8> = bytes.iter().copied().skip_while(|b| *b == 0).collect();
+    let mut content = Vec::with_capacity(trimmed.len() + 1);
+    // A leading zero keeps the value positive when the high bit is set
+    if trimmed.first().is_none_or(|b| b & 0x80 != 0) {
+        content.push(0x00);
+    }
+    content.extend_from_slice(&trimmed);
+    der_tlv(TAG_INTEGER, &content)
+}
+
+/// Encode a BIT STRING with no unused bits.
+fn der_bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = Vec::with_capacity(bytes.len() + 1);
+    content.push(0x00);
+    content.extend_from_slice(bytes);
+    der_tlv(TAG_BIT_STRING, &content)
+}
+
+/// Encode a public key returned by the HSM as a DER SubjectPublicKeyInfo
+/// (RFC 5280), the form other tools hash for fingerprints and wrap in PEM.
+pub fn public_key_spki_der(public_key: &PublicKey) -> HsmResult<Vec<u8>> {
+    let (algorithm_identifier, subject_public_key) =
+        if let Some(curve) = curve_oid(public_key.algorithm) {
+            // The curve OID comes from the device's algorithm; refuse a point that
+            // does not fit that curve rather than emitting inconsistent parameters
+            let expected_len = ec_field_len(public_key.algorithm).map(|len| len * 2);
+            if expected_len != Some(public_key.bytes.len()) {
+                return Err(HsmError::InvalidKey(format!(
+                    "{:?} public key is {} bytes, expected {:?}",
+                    public_key.algorithm,
+                    public_key.bytes.len(),
+                    expected_len
+                )));
+            }
+
+            // HSM returns EC points as raw x || y; SPKI wants an uncompressed SEC1 point
+            let mut point = Vec::with_capacity(public_key.bytes.len() + 1);
+            point.push(0x04);
+            point.extend_from_slice(&public_key.bytes);
+
+            ([OID_EC_PUBLIC_KEY, curve].concat(), point)
+        } else if public_key.algorithm == asymmetric::Algorithm::Ed25519 {
+            check_key_len(public_key, 32)?;
+            (OID_ED25519.to_vec(), public_key.bytes.clone())
+        } else if let Some(modulus_len) = rsa_modulus_len(public_key.algorithm) {
+            check_key_len(public_key, modulus_len)?;
+            // HSM returns only the modulus; the exponent is always 65537
+            let rsa_key = der_tlv(
+                TAG_SEQUENCE,
+                &[
+                    der_unsigned_integer(&public_key.bytes),
+                    der_unsigned_integer(RSA_PUBLIC_EXPONENT),
+                ]
+                .concat(),
+            );
+
+            ([OID_RSA_ENCRYPTION, &[TAG_NULL, 0x00]].concat(), rsa_key)
+        } else {
+            return Err(HsmError::InvalidKey(format!(
+                "Unsupported public key algorithm for SPKI encoding: {:?}",
+                public_key.algorithm
+            )));
+        };
+
+    let der = der_tlv(
+        TAG_SEQUENCE,
+        &[
+            der_tlv(TAG_SEQUENCE, &algorithm_identifier),
+            der_bit_string(&subject_public_key),
+        ]
+        .concat(),
+    );
+
+    if curve_oid(public_key.algorithm).is_some() {
+        check_ec_spki(public_key.algorithm, &der, &subject_public_key)?;
+    }
+
+    Ok(der)
+}
+
+/// Encode a public key returned by the HSM as a PEM `PUBLIC KEY` block.
+pub fn public_key_pem(public_key: &PublicKey) -> HsmResult<String> {
+    let der = public_key_spki_der(public_key)?;
+    let encoded = STANDARD.encode(der);
+
+    let mut pem = String::from("-----BEGIN PUBLIC KEY-----\n");
+    // PEM bodies are wrapped at 64 characters
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).expect("base64 output is ASCII"));
+        pem.push('\n');
+    }
+    pem.push_str("-----END PUBLIC KEY-----\n");
+    Ok(pem)
+}
+
+/// Hex-encoded SHA-256 over the SPKI DER encoding of a public key.
+pub fn public_key_fingerprint(public_key: &PublicKey) -> HsmResult<String> {
+    let der = public_key_spki_der(public_key)?;
+    Ok(hex::encode(Sha256::digest(&der)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use x509_parser::pem::parse_x509_pem;
+    use x509_parser::prelude::FromDer;
+    use x509_parser::x509::SubjectPublicKeyInfo;
+
+    /// Device-style `x || y` public key of a fixed secret on the curve of `$curve`.
+    macro_rules! ec_public_key {
+        ($curve:ident, $algorithm:expr, $scalar_len:expr) => {{
+            let public = $curve::SecretKey::from_slice(&[0x42; $scalar_len])
+                .unwrap()
+                .public_key();
+            let device_key = PublicKey {
+                algorithm: $algorithm,
+                bytes: public.to_encoded_point(false).as_bytes()[1..].to_vec(),
+            };
+            (public, device_key)
+        }};
+    }
+
+    /// Algorithm and named-curve OIDs of `der` as read by x509-parser.
+    fn oids(der: &[u8]) -> (String, String) {
+        let (rest, spki) = SubjectPublicKeyInfo::from_der(der).unwrap();
+        assert!(rest.is_empty());
+        let curve = spki
+            .algorithm
+            .parameters
+            .as_ref()
+            .unwrap()
+            .as_oid()
+            .unwrap();
+        (
+            spki.algorithm.algorithm.to_id_string(),
+            curve.to_id_string(),
+        )
+    }
+
+    #[test]
+    fn ec_spki_names_the_curve_of_the_device_key() {
+        use asymmetric::Algorithm::*;
+
+        let cases = [
+            (EcP256, 64, "1.2.840.10045.3.1.7"),
+            (EcP384, 96, "1.3.132.0.34"),
+            (EcP521, 132, "1.3.132.0.35"),
+            (EcK256, 64, "1.3.132.0.10"),
+            (EcBp256, 64, "1.3.36.3.3.2.8.1.1.7"),
+        ];
+        let (_, p256_key) = ec_public_key!(p256, EcP256, 32);
+        let (_, p384_key) = ec_public_key!(p384, EcP384, 48);
+        let (_, k256_key) = ec_public_key!(k256, EcK256, 32);
+
+        for (algorithm, len, curve) in cases {
+            // Curves checked by check_ec_spki need a real point
+            let public_key = match algorithm {
+                EcP256 => p256_key.clone(),
+                EcP384 => p384_key.clone(),
+                EcK256 => k256_key.clone(),
+                _ => PublicKey {
+                    algorithm,
+                    bytes: vec![0x11; len],
+                },
+            };
+            let der = public_key_spki_der(&public_key).unwrap();
+
+            assert_eq!(
+                oids(&der),
+                ("1.2.840.10045.2.1".to_string(), curve.to_string()),
+                "{:?}",
+                algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn ec_spki_parses_back_to_the_same_key() {
+        use asymmetric::Algorithm::*;
+
+        let (p256_public, p256_key) = ec_public_key!(p256, EcP256, 32);
+        let (p384_public, p384_key) = ec_public_key!(p384, EcP384, 48);
+        let (k256_public, k256_key) = ec_public_key!(k256, EcK256, 32);
+
+        let der = |key: &PublicKey| public_key_spki_der(key).unwrap();
+        assert_eq!(
+            p256::PublicKey::from_public_key_der(&der(&p256_key)).unwrap(),
+            p256_public
+        );
+        assert_eq!(
+            p384::PublicKey::from_public_key_der(&der(&p384_key)).unwrap(),
+            p384_public
+        );
+        assert_eq!(
+            k256::PublicKey::from_public_key_der(&der(&k256_key)).unwrap(),
+            k256_public
+        );
+    }
+
+    #[test]
+    fn point_of_the_wrong_size_for_the_curve_is_refused() {
+        let (_, mut key) = ec_public_key!(p256, asymmetric::Algorithm::EcP256, 32);
+        key.algorithm = asymmetric::Algorithm::EcP384;
+
+        assert!(matches!(
+            public_key_spki_der(&key),
+            Err(HsmError::InvalidKey(_))
+        ));
+    }
+
+    #[test]
+    fn pem_holds_the_spki_der() {
+        let (_, key) = ec_public_key!(p256, asymmetric::Algorithm::EcP256, 32);
+
+        let pem = public_key_pem(&key).unwrap();
+        let (_, parsed) = parse_x509_pem(pem.as_bytes()).unwrap();
+
+        assert_eq!(parsed.label, "PUBLIC KEY");
+        assert_eq!(parsed.contents, public_key_spki_der(&key).unwrap());
+    }
+}
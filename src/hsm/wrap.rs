@@ -0,0 +1,206 @@
+use super::client::HsmClient;
+use super::error::{HsmError, HsmResult, device_error_kind};
+use super::operations::get_object_info;
+use yubihsm::device::ErrorKind as DeviceErrorKind;
+use yubihsm::object::{Id, Label, Type};
+use yubihsm::{Capability, Domain, wrap};
+
+/// Nonce and authentication tag around every wrapped object (AES-CCM)
+const WRAP_NONCE_LEN: usize = 13;
+const WRAP_MAC_LEN: usize = 16;
+
+/// Object created on the device by `import_wrapped`.
+#[derive(Clone, Copy, Debug)]
+pub struct ImportResult {
+    pub object_id: Id,
+    pub object_type: Type,
+}
+
+/// Capabilities given to wrap keys created by the app: the key may export
+/// and import objects under wrap, nothing else.
+pub fn default_wrap_capabilities() -> Capability {
+    Capability::EXPORT_WRAPPED | Capability::IMPORT_WRAPPED
+}
+
+/// Capabilities that objects exported/imported under the wrap key may keep.
+/// Covers signing keys and lets them be re-exported under wrap later.
+pub fn default_wrap_delegated_capabilities() -> Capability {
+    Capability::SIGN_ECDSA
+        | Capability::SIGN_EDDSA
+        | Capability::SIGN_PKCS
+        | Capability::SIGN_PSS
+        | Capability::EXPORTABLE_UNDER_WRAP
+}
+
+/// Parse a label, enforcing the device's size limit.
+pub(crate) fn parse_label(label: &str) -> HsmResult<Label> {
+    Label::from_bytes(label.as_bytes())
+        .map_err(|e| HsmError::InvalidInput(format!("Invalid label '{}': {}", label, e)))
+}
+
+/// Generate a new wrap key on the device. Pass `key_id` 0 to let the device pick one.
+pub fn generate_wrap_key(
+    client: &HsmClient,
+    key_id: Id,
+    label: &str,
+    domains: Domain,
+    capabilities: Capability,
+    delegated_capabilities: Capability,
+    algorithm: wrap::Algorithm,
+) -> HsmResult<Id> {
+    client.ensure_destructive_allowed()?;
+    let label = parse_label(label)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::GenerationFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.generate_wrap_key(
+        key_id,
+        label,
+        domains,
+        capabilities,
+        delegated_capabilities,
+        algorithm,
+    )
+    .map_err(|e| HsmError::from_client_object(e, key_id, Type::WrapKey, HsmError::GenerationFailed))
+}
+
+/// Import existing wrap key material. Pass `key_id` 0 to let the device pick one.
+#[allow(clippy::too_many_arguments)]
+pub fn put_wrap_key(
+    client: &HsmClient,
+    key_id: Id,
+    label: &str,
+    domains: Domain,
+    capabilities: Capability,
+    delegated_capabilities: Capability,
+    algorithm: wrap::Algorithm,
+    key_bytes: &[u8],
+) -> HsmResult<Id> {
+    client.ensure_destructive_allowed()?;
+
+    let expected_len = wrap_key_len(algorithm);
+    if key_bytes.len() != expected_len {
+        return Err(HsmError::InvalidInput(format!(
+            "{:?} needs a {}-byte key, got {} bytes",
+            algorithm,
+            expected_len,
+            key_bytes.len()
+        )));
+    }
+
+    let label = parse_label(label)?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ImportFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.put_wrap_key(
+        key_id,
+        label,
+        domains,
+        capabilities,
+        delegated_capabilities,
+        algorithm,
+        key_bytes.to_vec(),
+    )
+    .map_err(|e| HsmError::from_client_object(e, key_id, Type::WrapKey, HsmError::ImportFailed))
+}
+
+/// Key size in bytes for a wrap algorithm.
+fn wrap_key_len(algorithm: wrap::Algorithm) -> usize {
+    match algorithm {
+        wrap::Algorithm::Aes128Ccm => 16,
+        wrap::Algorithm::Aes192Ccm => 24,
+        wrap::Algorithm::Aes256Ccm => 32,
+    }
+}
+
+/// Export object `object_id` encrypted under wrap key `wrap_key_id`. The blob
+/// (nonce followed by ciphertext) can only be imported by a device holding the
+/// same wrap key. Objects without `exportable-under-wrap` are refused up front.
+pub fn export_wrapped(
+    client: &HsmClient,
+    wrap_key_id: Id,
+    object_type: Type,
+    object_id: Id,
+) -> HsmResult<Vec<u8>> {
+    let info = get_object_info(client, object_id, object_type)?;
+    if !info
+        .capabilities
+        .contains(Capability::EXPORTABLE_UNDER_WRAP)
+    {
+        return Err(HsmError::ExportFailed(format!(
+            "0x{:04x} ({:?}) lacks the exportable-under-wrap capability, so the device will not export it",
+            object_id, object_type
+        )));
+    }
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ExportFailed(format!("Failed to lock client: {}", e)))?;
+
+    hsm.export_wrapped(wrap_key_id, object_type, object_id)
+        .map(|message| message.into_vec())
+        .map_err(|e| {
+            match HsmError::from_client_object(
+                e,
+                wrap_key_id,
+                Type::WrapKey,
+                HsmError::ExportFailed,
+            ) {
+                // The object is exportable, so the wrap key is what is missing rights
+                HsmError::InsufficientPermissions(msg) => HsmError::ExportFailed(format!(
+                    "wrap key 0x{:04x} may not export 0x{:04x}: it needs export-wrapped, and \
+                     exportable-under-wrap among its delegated capabilities ({})",
+                    wrap_key_id, object_id, msg
+                )),
+                other => other,
+            }
+        })
+}
+
+/// Import a blob produced by `export_wrapped` (or yubihsm-shell) under wrap key
+/// `wrap_key_id`. The object keeps the ID and type it had when exported.
+pub fn import_wrapped(client: &HsmClient, wrap_key_id: Id, data: &[u8]) -> HsmResult<ImportResult> {
+    client.ensure_destructive_allowed()?;
+
+    if data.len() <= WRAP_NONCE_LEN + WRAP_MAC_LEN {
+        return Err(HsmError::CorruptWrappedData(format!(
+            "{} bytes is too short to hold a nonce, an object and its authentication tag",
+            data.len()
+        )));
+    }
+    let message = wrap::Message::from_vec(data.to_vec())
+        .map_err(|e| HsmError::CorruptWrappedData(format!("{:?}", e)))?;
+
+    let hsm_client = client.client();
+    let hsm = hsm_client
+        .lock()
+        .map_err(|e| HsmError::ImportFailed(format!("Failed to lock client: {}", e)))?;
+
+    let handle = hsm
+        .import_wrapped(wrap_key_id, message)
+        .map_err(|e| match device_error_kind(&e) {
+            // CCM authentication failed: the key differs or the blob was altered
+            Some(DeviceErrorKind::InvalidData) => HsmError::WrapKeyMismatch(format!("{:?}", e)),
+            Some(DeviceErrorKind::WrongLength | DeviceErrorKind::InvalidCommand) => {
+                HsmError::CorruptWrappedData(format!("{:?}", e))
+            }
+            // The ID in the blob is taken, not the wrap key's
+            Some(DeviceErrorKind::ObjectExists) => HsmError::ImportFailed(format!(
+                "an object with the same ID and type is already on the device; delete it first ({:?})",
+                e
+            )),
+            _ => HsmError::from_client_object(e, wrap_key_id, Type::WrapKey, HsmError::ImportFailed),
+        })?;
+
+    Ok(ImportResult {
+        object_id: handle.object_id,
+        object_type: handle.object_type,
+    })
+}
@@ -1,173 +1,880 @@
 mod config;
 mod hsm;
+mod palette;
 mod screens;
 mod ui;
 
+use chrono::{DateTime, SecondsFormat, Utc};
 use config::*;
 use gpui::{
-    App, Application, Bounds, Context, Entity, EventEmitter, Focusable, IntoElement, KeyBinding,
-    MouseButton, ParentElement, Render, SharedString, Styled, Window, WindowBounds, WindowOptions,
-    actions, div, prelude::*, px, rgb, size,
+    App, Application, Bounds, Context, Entity, EventEmitter, FocusHandle, Focusable, IntoElement,
+    KeyBinding, MouseButton, ParentElement, PathPromptOptions, Render, SharedString, Styled, Task,
+    Window, WindowBounds, WindowOptions, actions, div, prelude::*, px, rgb, size,
 };
 use gpui_component::table::TableState;
-use hsm::{HsmClient, HsmConfig, SessionManager};
-use screens::keys_config::KeysTableDelegate;
-use ui::TextArea;
+use hsm::audit::DEFAULT_AUDIT_PAGE_SIZE;
+use hsm::{
+    AuditLogPager, ConnectionState, HsmClient, HsmConfig, SessionManager, SignatureEncoding,
+    error::{HsmError, HsmResult},
+};
+use screens::audit::AuditLogDelegate;
+use screens::key_gen::{KEY_GEN_ALGORITHMS, KEY_GEN_CAPABILITIES};
+use screens::keys_config::{InventoryDiffDelegate, KeysTableDelegate, ListingProgress};
+use screens::sign_verify::{
+    InputMode, OutputSegment, format_freshness, format_sign_outcome, format_verify_outcome,
+    session_error_output,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use ui::{CapabilityPicker, CommandPalette, DomainPicker, Modal, ModalEvent, TextArea};
+use yubihsm::{Capability, Domain, asymmetric, device::SerialNumber};
 
-actions!(hsm_demo, [SignText, VerifyText]);
+actions!(
+    hsm_demo,
+    [
+        SignText,
+        VerifyText,
+        CompareSignature,
+        SignJws,
+        ExportForOpenssl,
+        RecoverPublicKey,
+        SignAndAttest,
+        RefreshSessionInfo,
+        ToggleCommandPalette
+    ]
+);
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Screen {
     Auth,
     SignVerify,
     KeysConfig,
+    KeyGen,
+    Random,
+    Hmac,
+    Opaque,
+    Device,
+    AuditLog,
+    Settings,
 }
 
 pub struct HsmApp {
     auth_password_input: Entity<TextArea>,
+    /// Optional purpose of the next session, shown in the sidebar once connected
+    auth_label_input: Entity<TextArea>,
+    /// Connect through a yubihsm-connector over HTTP instead of USB
+    auth_use_http: bool,
+    /// yubihsm-connector address as `host[:port]`
+    auth_connector_input: Entity<TextArea>,
+    /// USB serial number typed by hand; overrides the device picker when set
+    auth_serial_input: Entity<TextArea>,
     auth_status: SharedString,
+    /// Connection attempt in progress, retried while the device is unreachable
+    auth_connecting: Option<Task<()>>,
+    /// Prominent notice shown on the Auth screen (e.g. after the device was unplugged)
+    auth_banner: Option<SharedString>,
+    /// Whether the Auth screen's advanced section is expanded
+    auth_show_advanced: bool,
+    /// Connect timeout in seconds; empty uses the default
+    auth_timeout_input: Entity<TextArea>,
     session: SessionManager,
     text_input: Entity<TextArea>,
-    output_text: SharedString,
+    /// Signing key reference: numeric ID (hex or decimal) or key label
+    signing_key_input: Entity<TextArea>,
+    /// Asymmetric signing keys offered by the key picker, from the last listing
+    signing_keys: Vec<hsm::ObjectSummary>,
+    /// Key picked in the key picker; used when the key input is empty
+    selected_signing_key: Option<u16>,
+    /// Styled result of the last Sign & Verify operation
+    output: Vec<OutputSegment>,
     signature: Option<Vec<u8>>,
+    /// `signature` normalized to fixed-width raw `r || s`
+    signature_raw: Option<Vec<u8>>,
+    /// Algorithm of the key behind `signature`, which decides how it converts
+    signature_algorithm: Option<asymmetric::Algorithm>,
+    /// Which form of the current signature Verify checks
+    verify_encoding: SignatureEncoding,
+    /// Which form "Save signature…" writes
+    signature_file_encoding: SignatureEncoding,
+    /// Whether the input field holds text or hex bytes
+    input_mode: InputMode,
+    /// Prefix the input with the current UTC time before signing
+    sign_with_timestamp: bool,
+    /// Hash applied before ECDSA signing and verification
+    hash_alg: hsm::HashAlg,
+    /// Sign and verify the input as a precomputed digest instead of hashing it
+    sign_prehashed: bool,
+    /// Freshness window in seconds for verifying timestamped signatures
+    max_age_input: Entity<TextArea>,
+    /// Timestamp embedded in the payload behind `signature`, if any
+    signature_timestamp: Option<DateTime<Utc>>,
+    /// Fingerprint of the key that produced `signature`, to detect a replaced key
+    signature_key_fingerprint: Option<String>,
+    /// Whether `signature` was copied to the clipboard, for the "Copied!" hint
+    signature_copied: bool,
     current_screen: Screen,
     keys_output: SharedString,
     keys_table: Option<Entity<TableState<KeysTableDelegate>>>,
-    /// Cached keys data for deletion operations
+    /// Every object from the last listing, before the capability filter
+    keys_all: Vec<hsm::ObjectSummary>,
+    /// Rows shown in the keys table; selection indexes into this
     keys_data: Vec<hsm::ObjectSummary>,
+    /// Only show objects that have this capability
+    keys_capability_filter: Option<Capability>,
+    /// Only list objects of this type; applied by the device, so changing it re-lists
+    keys_type_filter: Option<yubihsm::object::Type>,
+    /// Filters the keys table by label, ID, type or algorithm as the user types
+    keys_search_input: Entity<TextArea>,
+    /// Search text last applied to the keys table
+    keys_search_query: String,
+    /// Result of the last "Compare snapshot", shown instead of the keys table
+    inventory_diff: Option<Entity<TableState<InventoryDiffDelegate>>>,
+    /// Index into `KEY_GEN_ALGORITHMS` of the algorithm the key generation form uses
+    keygen_algorithm: usize,
+    /// Capabilities the generated key gets; reset to the algorithm's defaults when it changes
+    keygen_capabilities: Entity<CapabilityPicker>,
+    /// Domains the generated key is placed in
+    keygen_domains: Entity<DomainPicker>,
+    keygen_label_input: Entity<TextArea>,
+    /// Object ID for the generated key; empty or "auto" lets the device pick
+    keygen_id_input: Entity<TextArea>,
+    /// Result of the last key generation, including the public key
+    keygen_output: SharedString,
+    /// Serial, firmware and algorithms of the connected device, read after connecting
+    device_info: Option<hsm::DeviceInfo>,
+    /// Free/total records and pages, refreshed after objects are created or deleted
+    storage_info: Option<hsm::StorageInfo>,
+    /// Must read exactly "RESET" before the Device screen's reset button enables
+    reset_confirm_input: Entity<TextArea>,
+    /// Why the last reset attempt failed
+    reset_error: Option<SharedString>,
+    password_old_input: Entity<TextArea>,
+    password_new_input: Entity<TextArea>,
+    password_confirm_input: Entity<TextArea>,
+    /// Why the last password change failed
+    password_error: Option<SharedString>,
+    /// Number of bytes the Random screen asks the device RNG for
+    random_len_input: Entity<TextArea>,
+    /// Bytes from the last RNG read, kept for "Copy hex"
+    random_bytes: Option<Vec<u8>>,
+    random_output: SharedString,
+    /// HMAC keys from the last listing, for the HMAC key picker
+    hmac_keys: Vec<hsm::ObjectSummary>,
+    /// Key picked in the HMAC key picker; used when the key ID field is empty
+    selected_hmac_key: Option<u16>,
+    hmac_key_input: Entity<TextArea>,
+    hmac_data_input: Entity<TextArea>,
+    /// Hex tag to verify; empty verifies `hmac_tag`
+    hmac_tag_input: Entity<TextArea>,
+    /// Tag from the last "Compute HMAC"
+    hmac_tag: Option<Vec<u8>>,
+    hmac_output: SharedString,
+    opaque_id_input: Entity<TextArea>,
+    opaque_label_input: Entity<TextArea>,
+    /// Domains uploaded objects are placed in
+    opaque_domains: Entity<DomainPicker>,
+    /// Upload as an X.509 certificate (validated, PEM converted to DER) rather than raw data
+    opaque_as_certificate: bool,
+    /// ID and contents of the last fetched opaque object, kept for "Save fetched"
+    opaque_data: Option<(u16, Vec<u8>)>,
+    opaque_output: SharedString,
+    /// Audit log entries read so far
+    audit_table: Option<Entity<TableState<AuditLogDelegate>>>,
+    /// Position in the audit log; its last item is what "Acknowledge" marks as read
+    audit_pager: AuditLogPager,
+    audit_output: SharedString,
     /// Currently selected key row index for deletion
     selected_key_row: Option<usize>,
+    /// Row of `keys_data` awaiting delete confirmation; set when the dialog opens
+    pending_delete: Option<usize>,
+    /// Progress (done, total) of the last multi-object Keys config operation
+    keys_progress: Option<(usize, usize)>,
+    /// Live progress of the listing running in the background, if any
+    keys_listing: Option<Arc<ListingProgress>>,
+    /// Dialog currently shown over the content area, if any
+    modal: Option<Entity<Modal>>,
+    /// Quick action palette (cmd-k), if open
+    command_palette: Option<Entity<CommandPalette>>,
+    /// Serial numbers of the YubiHSM2 devices found on USB at the last scan
+    usb_devices: Vec<SerialNumber>,
+    /// Device the next Connect targets
+    selected_device: Option<SerialNumber>,
+    /// Ephemeral demo key created this session; deleted on disconnect/close
+    scratch_key: Option<u16>,
+    /// Safe mode: all mutating operations are hidden and refused
+    destructive_disabled: bool,
+    /// Close the session after this long without mouse/keyboard activity
+    idle_timeout: Option<Duration>,
+    /// Preferences persisted across restarts
+    settings: Settings,
+    settings_auth_key_input: Entity<TextArea>,
+    settings_signing_key_input: Entity<TextArea>,
+    settings_idle_input: Entity<TextArea>,
+    settings_connector_input: Entity<TextArea>,
+    settings_status: SharedString,
+    /// Polls for inactivity while a session is open; dropping it stops the timer
+    idle_timer: Option<Task<()>>,
+    /// Time between session heartbeats; None disables them
+    heartbeat_interval: Option<Duration>,
+    /// Pings the session while it is open; dropping it stops the heartbeat
+    heartbeat_timer: Option<Task<()>>,
+    /// Focus target of the keys table, for keyboard navigation
+    keys_focus: FocusHandle,
+    /// Focus target of the Sign & Verify screen, so its shortcuts work before
+    /// any field has been clicked
+    sign_verify_focus: FocusHandle,
+    /// Last self-test or benchmark report, kept for "Export report"
+    last_report: Option<hsm::DiagnosticsReport>,
+    /// Time source for signing timestamps and generated file names
+    clock: Box<dyn hsm::Clock>,
+    /// A device operation is running in the background; its buttons show "Working…"
+    hsm_busy: bool,
+    /// Domain (1-16) keys must belong to before they are used or deleted; None allows any
+    active_domain: Option<u8>,
+    /// Level of the log written to stderr
+    log_level: LevelFilter,
+    /// What each logged sign or verify includes
+    log_redaction: hsm::RedactionLevel,
 }
 
 impl HsmApp {
-    fn new(cx: &mut Context<'_, Self>) -> Self {
+    fn new(cx: &mut Context<'_, Self>, options: LaunchOptions, settings: Settings) -> Self {
         let auth_password_input =
-            cx.new(|cx| TextArea::new(cx, "Enter YubiHSM auth password...".to_string()));
+            cx.new(|cx| TextArea::new_masked(cx, "Enter YubiHSM auth password...".to_string()));
+        let auth_label_input = cx.new(|cx| {
+            TextArea::new(
+                cx,
+                "Session label (optional), e.g. \"prod signer\"".to_string(),
+            )
+        });
+        let connector_placeholder = format!(
+            "{}:{} (connector address)",
+            hsm::DEFAULT_CONNECTOR_ADDR,
+            hsm::DEFAULT_CONNECTOR_PORT
+        );
+        let auth_connector_input = cx.new(|cx| {
+            let mut input = TextArea::new(cx, connector_placeholder.clone());
+            input.set_content(settings.connector_address.clone(), cx);
+            input
+        });
+        let auth_serial_input =
+            cx.new(|cx| TextArea::new(cx, "Serial number (optional)".to_string()));
+        let auth_timeout_input = cx.new(|cx| {
+            TextArea::new(
+                cx,
+                format!(
+                    "{} (connect timeout in seconds)",
+                    hsm::DEFAULT_CONNECT_TIMEOUT.as_secs()
+                ),
+            )
+        });
         let text_input = cx.new(|cx| TextArea::new(cx, "Type your text here...".to_string()));
+        let signing_key_input = cx.new(|cx| {
+            TextArea::new(
+                cx,
+                format!("0x{:04x} (key ID or label)", settings.signing_key_id),
+            )
+        });
 
-        Self {
+        let max_age_input =
+            cx.new(|cx| TextArea::new(cx, format!("{} (seconds)", DEFAULT_MAX_SIGNATURE_AGE_SECS)));
+        let keygen_label_input = cx.new(|cx| TextArea::new(cx, "Key label".to_string()));
+        let keygen_id_input = cx.new(|cx| TextArea::new(cx, "auto".to_string()));
+        let keygen_capabilities =
+            cx.new(|_| CapabilityPicker::new(KEY_GEN_CAPABILITIES, KEY_GEN_ALGORITHMS[0].2));
+        cx.observe(&keygen_capabilities, |_, _, cx| cx.notify())
+            .detach();
+        let keygen_domains = cx.new(|_| DomainPicker::new(Domain::DOM1));
+        cx.observe(&keygen_domains, |_, _, cx| cx.notify()).detach();
+        let opaque_domains = cx.new(|_| DomainPicker::new(Domain::DOM1));
+        cx.observe(&opaque_domains, |_, _, cx| cx.notify()).detach();
+        let random_len_input =
+            cx.new(|cx| TextArea::new(cx, format!("{} (bytes)", DEFAULT_RANDOM_LEN)));
+        let hmac_key_input = cx.new(|cx| TextArea::new(cx, "Key ID, e.g. 0x0010".to_string()));
+        let hmac_data_input = cx.new(|cx| TextArea::new(cx, "Data to authenticate".to_string()));
+        let hmac_tag_input = cx.new(|cx| TextArea::new(cx, "Tag (hex)".to_string()));
+        let opaque_id_input = cx.new(|cx| TextArea::new(cx, "auto".to_string()));
+        let opaque_label_input = cx.new(|cx| TextArea::new(cx, "Object label".to_string()));
+        let keys_search_input =
+            cx.new(|cx| TextArea::new(cx, "Filter by label, ID, type or algorithm".to_string()));
+        cx.observe(&keys_search_input, |app, _, cx| app.apply_keys_search(cx))
+            .detach();
+        let settings_auth_key_input = cx.new(|cx| {
+            let mut input = TextArea::new(cx, format!("0x{:04x}", DEFAULT_AUTH_KEY_ID));
+            input.set_content(format!("0x{:04x}", settings.auth_key_id), cx);
+            input
+        });
+        let settings_signing_key_input = cx.new(|cx| {
+            let mut input = TextArea::new(cx, format!("0x{:04x}", DEFAULT_SIGNING_KEY_ID));
+            input.set_content(format!("0x{:04x}", settings.signing_key_id), cx);
+            input
+        });
+        let settings_idle_input = cx.new(|cx| {
+            let mut input =
+                TextArea::new(cx, format!("{} (minutes)", DEFAULT_IDLE_TIMEOUT_MINUTES));
+            input.set_content(settings.idle_timeout_minutes.to_string(), cx);
+            input
+        });
+        let settings_connector_input = cx.new(|cx| {
+            let mut input = TextArea::new(cx, connector_placeholder);
+            input.set_content(settings.connector_address.clone(), cx);
+            input
+        });
+        let reset_confirm_input = cx.new(|cx| TextArea::new(cx, "Type RESET".to_string()));
+        let password_old_input =
+            cx.new(|cx| TextArea::new_masked(cx, "Current password".to_string()));
+        let password_new_input = cx.new(|cx| TextArea::new_masked(cx, "New password".to_string()));
+        let password_confirm_input =
+            cx.new(|cx| TextArea::new_masked(cx, "New password again".to_string()));
+        cx.observe(&reset_confirm_input, |_, _, cx| cx.notify())
+            .detach();
+
+        let mut app = Self {
             auth_password_input,
+            auth_label_input,
+            auth_use_http: settings.use_http,
+            auth_connector_input,
+            auth_serial_input,
             auth_status: SharedString::from("Please authenticate to the YubiHSM session."),
+            auth_connecting: None,
+            auth_banner: None,
+            auth_show_advanced: false,
+            auth_timeout_input,
             session: SessionManager::new(),
             text_input,
-            output_text: SharedString::from("Ready. Type text and click Sign."),
+            signing_key_input,
+            signing_keys: Vec::new(),
+            selected_signing_key: None,
+            output: vec![OutputSegment::detail("Ready. Type text and click Sign.")],
             signature: None,
+            signature_raw: None,
+            signature_algorithm: None,
+            verify_encoding: SignatureEncoding::Der,
+            signature_file_encoding: SignatureEncoding::Der,
+            input_mode: InputMode::default(),
+            sign_with_timestamp: false,
+            hash_alg: settings.hash_alg,
+            sign_prehashed: options.prehashed,
+            max_age_input,
+            signature_timestamp: None,
+            signature_key_fingerprint: None,
+            signature_copied: false,
             current_screen: Screen::Auth,
             keys_output: SharedString::from(
                 "Click \"List keys\" to query objects from the YubiHSM2.",
             ),
             keys_table: None,
+            keys_all: Vec::new(),
             keys_data: Vec::new(),
+            keys_capability_filter: None,
+            keys_type_filter: None,
+            keys_search_input,
+            keys_search_query: String::new(),
+            inventory_diff: None,
+            keygen_algorithm: 0,
+            keygen_capabilities,
+            keygen_domains,
+            keygen_label_input,
+            keygen_id_input,
+            keygen_output: SharedString::from("Pick an algorithm and click \"Generate key\"."),
+            device_info: None,
+            storage_info: None,
+            reset_confirm_input,
+            reset_error: None,
+            password_old_input,
+            password_new_input,
+            password_confirm_input,
+            password_error: None,
+            random_len_input,
+            random_bytes: None,
+            random_output: SharedString::from("Enter a length and click \"Generate\"."),
+            hmac_keys: Vec::new(),
+            selected_hmac_key: None,
+            hmac_key_input,
+            hmac_data_input,
+            hmac_tag_input,
+            hmac_tag: None,
+            hmac_output: SharedString::from(
+                "Pick an HMAC key, type data and click \"Compute HMAC\".",
+            ),
+            opaque_id_input,
+            opaque_label_input,
+            opaque_domains,
+            opaque_as_certificate: true,
+            opaque_data: None,
+            opaque_output: SharedString::from(
+                "Pick a file and click \"Upload file\", or enter an ID and click \"Fetch\".",
+            ),
+            audit_table: None,
+            audit_pager: AuditLogPager::new(DEFAULT_AUDIT_PAGE_SIZE),
+            audit_output: SharedString::from("Click \"Refresh log\" to read the device audit log."),
             selected_key_row: None,
+            pending_delete: None,
+            keys_progress: None,
+            keys_listing: None,
+            modal: None,
+            command_palette: None,
+            usb_devices: Vec::new(),
+            selected_device: None,
+            scratch_key: None,
+            destructive_disabled: options.safe_mode,
+            idle_timeout: idle_timeout_from_minutes(
+                options
+                    .idle_minutes
+                    .unwrap_or(settings.idle_timeout_minutes),
+            ),
+            settings,
+            settings_auth_key_input,
+            settings_signing_key_input,
+            settings_idle_input,
+            settings_connector_input,
+            settings_status: SharedString::default(),
+            idle_timer: None,
+            heartbeat_interval: options.heartbeat_interval,
+            heartbeat_timer: None,
+            keys_focus: cx.focus_handle(),
+            sign_verify_focus: cx.focus_handle(),
+            active_domain: None,
+            log_level: options.log_level,
+            log_redaction: options.log_redaction,
+            hsm_busy: false,
+            last_report: None,
+            clock: Box::new(hsm::SystemClock),
+        };
+
+        // Best-effort cleanup of the scratch key when the window or app goes away
+        cx.on_release(|app, _| {
+            app.delete_scratch_key();
+        })
+        .detach();
+        cx.on_app_quit(|app, _| {
+            app.delete_scratch_key();
+            std::future::ready(())
+        })
+        .detach();
+
+        // Find out up front which devices can be picked on the Auth screen
+        app.scan_usb_devices();
+        app
+    }
+
+    /// Start polling for inactivity on the new session, replacing any earlier
+    /// timer so only one can fire.
+    pub(crate) fn start_idle_timer(&mut self, cx: &mut Context<'_, Self>) {
+        if self.idle_timeout.is_none() {
+            return;
         }
+
+        self.idle_timer = Some(cx.spawn(async move |this, cx| {
+            loop {
+                cx.background_executor().timer(IDLE_CHECK_INTERVAL).await;
+                if this.update(cx, |app, cx| app.check_idle(cx)).is_err() {
+                    break;
+                }
+            }
+        }));
     }
 
-    fn sign_text(&mut self, _: &SignText, _window: &mut Window, cx: &mut Context<'_, Self>) {
-        let text = self.text_input.read(cx).content();
-        if text.is_empty() {
-            self.output_text = "Error: Input text is empty".into();
-            cx.notify();
+    /// Disconnect if the session has been idle for longer than the timeout.
+    fn check_idle(&mut self, cx: &mut Context<'_, Self>) {
+        let Some(timeout) = self.idle_timeout else {
+            return;
+        };
+        if !self.session.is_authenticated() || self.session.idle_for() < timeout {
+            return;
+        }
+
+        self.disconnect_session(cx);
+        self.auth_status = SharedString::from("Session expired due to inactivity.");
+        cx.notify();
+    }
+
+    /// Ping the new session every heartbeat interval, replacing any earlier
+    /// heartbeat. Beats are skipped while a device operation is running.
+    pub(crate) fn start_heartbeat(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(interval) = self.heartbeat_interval else {
             return;
+        };
+
+        self.heartbeat_timer = Some(cx.spawn_in(window, async move |this, cx| {
+            loop {
+                cx.background_executor().timer(interval).await;
+                let Ok(client) = this.update(cx, |app, _| {
+                    if app.hsm_busy {
+                        None
+                    } else {
+                        app.session.heartbeat_client()
+                    }
+                }) else {
+                    break;
+                };
+                let Some(client) = client else {
+                    continue;
+                };
+
+                let task_client = client.clone();
+                let result = cx
+                    .background_executor()
+                    .spawn(async move { task_client.try_ping() })
+                    .await;
+                let finished = this.update_in(cx, |app, window, cx| {
+                    app.finish_heartbeat(&client, result, window, cx);
+                });
+                if finished.is_err() {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// Record the outcome of a heartbeat. Once the session is lost the
+    /// heartbeat stops and the user is asked to authenticate again.
+    fn finish_heartbeat(
+        &mut self,
+        client: &Arc<HsmClient>,
+        result: Option<HsmResult<()>>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        // Skipped because an operation held the client, or the session changed meanwhile
+        let Some(result) = result else {
+            return;
+        };
+        if !self.session.is_active(client) {
+            return;
+        }
+
+        let state = match &result {
+            Ok(()) => ConnectionState::Connected,
+            Err(e) if e.is_device_disconnected() || e.is_session_expired() => ConnectionState::Lost,
+            Err(_) => ConnectionState::Degraded,
+        };
+        self.session.set_connection_state(state);
+        cx.notify();
+
+        if let (ConnectionState::Lost, Err(e)) = (state, result) {
+            self.heartbeat_timer = None;
+            self.prompt_reauthenticate(e, window, cx);
+        }
+    }
+
+    /// Tell the user the session is gone and offer to return to the Auth screen.
+    fn prompt_reauthenticate(
+        &mut self,
+        error: HsmError,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Session lost",
+                format!(
+                    "The YubiHSM2 stopped answering this session ({}). Operations will fail until you authenticate again.",
+                    error
+                ),
+            )
+            .confirm_label("Authenticate again")
+        });
+
+        self.open_modal(modal, window, cx, |view, _, _window, cx| {
+            view.disconnect_session(cx);
+            view.auth_banner = Some(SharedString::from(
+                "The session was lost. Reconnect the YubiHSM2 if needed and authenticate again.",
+            ));
+        });
+    }
+
+    /// Record user activity so the idle timer starts over.
+    fn touch(&mut self) {
+        self.session.touch();
+    }
+
+    /// Run `op` with the active session on a background thread so USB I/O does
+    /// not freeze the window, then hand its result to `done` on the UI thread.
+    /// Only one task runs at a time; while one is in flight this does nothing.
+    /// If the session is closed or replaced before `op` finishes, the result is
+    /// dropped. Fails only if there is no session.
+    fn spawn_hsm_task<T: Send + 'static>(
+        &mut self,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+        op: impl FnOnce(&HsmClient) -> T + Send + 'static,
+        done: impl FnOnce(&mut Self, T, &mut Window, &mut Context<'_, Self>) + 'static,
+    ) -> HsmResult<()> {
+        if self.hsm_busy {
+            return Ok(());
         }
+        let client = self.session.shared_client()?;
+        self.hsm_busy = true;
+        cx.notify();
+
+        cx.spawn_in(window, async move |this, cx| {
+            // `op` locks and releases the client mutex itself; no lock is held across this await
+            let task_client = client.clone();
+            let result = cx
+                .background_executor()
+                .spawn(async move { op(&task_client) })
+                .await;
+
+            let _ = this.update_in(cx, |app, window, cx| {
+                app.hsm_busy = false;
+                if app.session.is_active(&client) {
+                    done(app, result, window, cx);
+                }
+                cx.notify();
+            });
+        })
+        .detach();
+        Ok(())
+    }
+
+    fn sign_text(&mut self, _: &SignText, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let message = match self.input_bytes(cx) {
+            Ok(message) => message,
+            Err(e) => {
+                self.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                cx.notify();
+                return;
+            }
+        };
+
+        let mode = self.input_mode;
+        let timestamp = self.sign_with_timestamp.then(|| self.clock.now());
+        let key_ref = self.signing_key_ref(cx);
+        let default_key_note = key_ref.default_key_note();
+        let hash_alg = self.message_hash();
+        let logged_input = message.clone();
 
         // Use the active HSM session to sign
-        match self.session.active_client() {
-            Ok(client) => match hsm::sign(client, DEFAULT_SIGNING_KEY_ID, text.as_bytes()) {
-                Ok(signature) => {
-                    let sig_hex = hex::encode(&signature);
-                    self.signature = Some(signature);
-                    self.output_text = format!(
-                            "✓ Successfully signed text\n\nInput: '{}'\n\nSignature (hex):\n{}\n\nLength: {} bytes",
-                            text,
-                            sig_hex,
-                            self.signature.as_ref().unwrap().len()
-                        ).into();
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                key_ref.resolve(client).and_then(|key_id| {
+                    client.with_session_retry(|c| {
+                        hsm::sign_message(c, key_id, &message, timestamp, hash_alg)
+                    })
+                })
+            },
+            move |view, result, _, cx| match result {
+                Ok(outcome) => {
+                    view.log_operation("sign", Some(outcome.key_id), logged_input, None);
+                    view.output = format_sign_outcome(&outcome, mode);
+                    view.output.extend(default_key_note);
+                    view.set_signature(
+                        outcome.signature,
+                        outcome.algorithm,
+                        outcome.timestamp,
+                        outcome.key_fingerprint,
+                    );
                 }
                 Err(e) => {
-                    self.output_text = format!("Signing failed: {}\n\nMake sure key ID 0x{:x} exists in your YubiHSM2 (secp256r1/ECDSA type)", e, DEFAULT_SIGNING_KEY_ID).into();
+                    view.log_operation("sign", None, logged_input, Some(&e));
+                    view.output = vec![
+                        OutputSegment::error(format!("Signing failed: {}", e)),
+                        OutputSegment::detail(
+                            "Make sure the key ID or label refers to an ECDSA (P-256), Ed25519 or RSA signing key in your YubiHSM2",
+                        ),
+                    ];
+                    view.handle_device_disconnected(&e, cx);
                 }
             },
-            Err(e) => {
-                self.output_text = format!(
-                    "Failed to use YubiHSM2 session: {}\n\nGo to the Auth screen and authenticate first.",
-                    e
-                )
-                .into();
-            }
+        );
+        if let Err(e) = spawned {
+            self.output = session_error_output(&e);
         }
 
         cx.notify();
     }
 
-    fn verify_text(&mut self, _: &VerifyText, _window: &mut Window, cx: &mut Context<'_, Self>) {
-        let text = self.text_input.read(cx).content();
+    /// Log a sign or verify of `input`, reduced to what the redaction level allows.
+    fn log_operation(
+        &self,
+        operation: &'static str,
+        key_id: Option<u16>,
+        input: Vec<u8>,
+        error: Option<&HsmError>,
+    ) {
+        let record = hsm::OperationRecord {
+            at: self.clock.now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            operation,
+            key_id,
+            input,
+            succeeded: error.is_none(),
+            error: error.map(|e| e.to_string()),
+        };
+        hsm::log_operation(&record, self.log_redaction);
+    }
 
-        if text.is_empty() {
-            self.output_text = "Error: Input text is empty".into();
-            cx.notify();
-            return;
-        }
+    fn verify_text(&mut self, _: &VerifyText, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let message = match self.input_bytes(cx) {
+            Ok(message) => message,
+            Err(e) => {
+                self.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                cx.notify();
+                return;
+            }
+        };
+        let mode = self.input_mode;
 
-        if self.signature.is_none() {
-            self.output_text = "Error: No signature to verify. Sign text first.".into();
+        let encoding = self.verify_encoding;
+        let signature = match encoding {
+            SignatureEncoding::Der => self.signature.clone(),
+            SignatureEncoding::Raw => self.signature_raw.clone(),
+        };
+        let Some(signature) = signature else {
+            self.output = vec![OutputSegment::error(
+                "Error: No signature to verify. Sign text first.",
+            )];
             cx.notify();
             return;
-        }
+        };
+
+        let max_age = match self.max_signature_age(cx) {
+            Ok(max_age) => max_age,
+            Err(e) => {
+                self.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                cx.notify();
+                return;
+            }
+        };
+
+        // Rebuild the exact payload that was signed
+        let payload = self.signed_payload(&message);
+        let signed_fingerprint = self.signature_key_fingerprint.clone();
+        let key_ref = self.signing_key_ref(cx);
+        let default_key_note = key_ref.default_key_note();
+        let hash_alg = self.message_hash();
+        // Only timestamped signatures have an age to check
+        let freshness_check = self
+            .signature_timestamp
+            .is_some()
+            .then(|| (max_age, self.clock.now()));
 
         // Use the active HSM session to verify
-        match self.session.active_client() {
-            Ok(client) => {
-                match hsm::verify(
-                    client,
-                    DEFAULT_SIGNING_KEY_ID,
-                    text.as_bytes(),
-                    self.signature.as_ref().unwrap(),
-                ) {
-                    Ok(is_valid) => {
-                        if is_valid {
-                            self.output_text = format!(
-                                "✓ Signature verification SUCCESSFUL\n\nInput: '{}'\n\nThe signature is valid!",
-                                text
-                            ).into();
-                        } else {
-                            self.output_text = format!(
-                                "✗ Signature verification FAILED\n\nInput: '{}'\n\nThe signature does not match the text.",
-                                text
-                            ).into();
-                        }
-                    }
-                    Err(e) => {
-                        self.output_text = format!("Verification failed: {}", e).into();
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                key_ref.resolve(client).and_then(|key_id| {
+                    client.with_session_retry(|c| match freshness_check {
+                        Some((max_age, now)) => hsm::verify_timestamped(
+                            c,
+                            key_id,
+                            &payload,
+                            &signature,
+                            encoding,
+                            signed_fingerprint.as_deref(),
+                            hash_alg,
+                            max_age,
+                            now,
+                        )
+                        .map(|checked| (checked.verify.clone(), Some(checked))),
+                        None => hsm::verify_message(
+                            c,
+                            key_id,
+                            &payload,
+                            &signature,
+                            encoding,
+                            signed_fingerprint.as_deref(),
+                            hash_alg,
+                        )
+                        .map(|outcome| (outcome, None)),
+                    })
+                })
+            },
+            move |view, result, _, cx| match result {
+                Ok((outcome, freshness)) => {
+                    view.log_operation("verify", Some(outcome.key_id), message.clone(), None);
+                    view.output = format_verify_outcome(&message, mode, &outcome);
+                    view.output.extend(default_key_note);
+                    if let Some(freshness) = freshness {
+                        view.output.push(format_freshness(&freshness));
                     }
                 }
-            }
-            Err(e) => {
-                self.output_text = format!(
-                    "Failed to use YubiHSM2 session: {}\n\nGo to the Auth screen and authenticate first.",
-                    e
-                )
-                .into();
-            }
+                Err(e) => {
+                    view.log_operation("verify", None, message, Some(&e));
+                    view.output = vec![OutputSegment::error(format!("Verification failed: {}", e))];
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.output = session_error_output(&e);
         }
 
         cx.notify();
     }
 
     fn disconnect_session(&mut self, cx: &mut Context<'_, Self>) {
-        // Drop the active HSM session
+        // Remove the scratch key while the session can still reach the device
+        self.delete_scratch_key();
+
+        // Drop the active HSM session and stop its idle timer and heartbeat
         self.session.disconnect();
+        self.idle_timer = None;
+        self.heartbeat_timer = None;
 
         // Reset app state
         self.current_screen = Screen::Auth;
         self.auth_status =
             SharedString::from("Disconnected. Please authenticate to the YubiHSM session.");
-        self.output_text = SharedString::from("Ready. Type text and click Sign.");
+        self.output = vec![OutputSegment::detail("Ready. Type text and click Sign.")];
         self.keys_output =
             SharedString::from("Click \"List keys\" to query objects from the YubiHSM2.");
-        self.signature = None;
+        self.clear_signature();
+        self.signing_keys = Vec::new();
+        self.selected_signing_key = None;
         self.keys_table = None;
+        self.keys_all = Vec::new();
         self.keys_data = Vec::new();
+        self.keys_capability_filter = None;
+        self.keys_type_filter = None;
+        self.keys_search_input.update(cx, |input, cx| {
+            input.set_content(String::new(), cx);
+        });
+        self.keys_search_query = String::new();
+        self.inventory_diff = None;
         self.selected_key_row = None;
+        self.pending_delete = None;
+        self.keys_progress = None;
+        self.keys_listing = None;
+        self.keygen_output = SharedString::from("Pick an algorithm and click \"Generate key\".");
+        self.device_info = None;
+        self.storage_info = None;
+        self.reset_confirm_input.update(cx, |input, cx| {
+            input.set_content(String::new(), cx);
+        });
+        self.reset_error = None;
+        for input in [
+            &self.password_old_input,
+            &self.password_new_input,
+            &self.password_confirm_input,
+        ] {
+            input.update(cx, |input, cx| input.set_content(String::new(), cx));
+        }
+        self.password_error = None;
+        self.random_bytes = None;
+        self.random_output = SharedString::from("Enter a length and click \"Generate\".");
+        self.hmac_keys = Vec::new();
+        self.selected_hmac_key = None;
+        self.hmac_tag = None;
+        self.hmac_output =
+            SharedString::from("Pick an HMAC key, type data and click \"Compute HMAC\".");
+        self.opaque_data = None;
+        self.opaque_output = SharedString::from(
+            "Pick a file and click \"Upload file\", or enter an ID and click \"Fetch\".",
+        );
+        self.audit_table = None;
+        self.audit_pager = AuditLogPager::new(DEFAULT_AUDIT_PAGE_SIZE);
+        self.audit_output =
+            SharedString::from("Click \"Refresh log\" to read the device audit log.");
+        self.last_report = None;
+        self.modal = None;
+        self.command_palette = None;
 
         // Clear password field
         self.auth_password_input
@@ -175,6 +882,236 @@ impl HsmApp {
 
         cx.notify();
     }
+
+    /// Show `modal` over the content area. The modal is dismissed on either
+    /// outcome; `on_confirm` runs with the input value when the user confirms.
+    fn open_modal(
+        &mut self,
+        modal: Entity<Modal>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+        on_confirm: impl Fn(&mut Self, Option<String>, &mut Window, &mut Context<'_, Self>) + 'static,
+    ) {
+        cx.subscribe_in(&modal, window, move |view, _modal, event, window, cx| {
+            view.modal = None;
+            if let ModalEvent::Confirmed(value) = event {
+                on_confirm(view, value.clone(), window, cx);
+            }
+            cx.notify();
+        })
+        .detach();
+
+        window.focus(&modal.read(cx).initial_focus(cx));
+        self.modal = Some(modal);
+        cx.notify();
+    }
+
+    /// Show the platform save dialog in the working directory with
+    /// `suggested_name` filled in, then run `on_path` with the chosen path, or
+    /// with the error if the dialog could not be shown. Nothing runs if the
+    /// user cancels.
+    fn prompt_save_path(
+        &mut self,
+        suggested_name: &str,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+        on_path: impl FnOnce(&mut Self, anyhow::Result<PathBuf>, &mut Window, &mut Context<'_, Self>)
+        + 'static,
+    ) {
+        let directory = std::env::current_dir().unwrap_or_default();
+        let chosen = cx.prompt_for_new_path(&directory, Some(suggested_name));
+        cx.spawn_in(window, async move |this, cx| {
+            let path = match chosen.await {
+                Ok(Ok(Some(path))) => Ok(path),
+                Ok(Err(e)) => Err(e),
+                // Cancelled, or the dialog went away with the window
+                Ok(Ok(None)) | Err(_) => return,
+            };
+            let _ = this.update_in(cx, |view, window, cx| {
+                on_path(view, path, window, cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Show the platform open dialog for a single file titled `prompt`, then
+    /// run `on_path` like `prompt_save_path` does.
+    fn prompt_open_path(
+        &mut self,
+        prompt: &'static str,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+        on_path: impl FnOnce(&mut Self, anyhow::Result<PathBuf>, &mut Window, &mut Context<'_, Self>)
+        + 'static,
+    ) {
+        let chosen = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some(prompt.into()),
+        });
+        cx.spawn_in(window, async move |this, cx| {
+            let path = match chosen.await {
+                Ok(Ok(Some(paths))) => match paths.into_iter().next() {
+                    Some(path) => Ok(path),
+                    None => return,
+                },
+                Ok(Err(e)) => Err(e),
+                Ok(Ok(None)) | Err(_) => return,
+            };
+            let _ = this.update_in(cx, |view, window, cx| {
+                on_path(view, path, window, cx);
+                cx.notify();
+            });
+        })
+        .detach();
+    }
+
+    /// Fail unless the object is in the active domain (if one is selected).
+    fn check_active_domain(
+        &self,
+        client: &HsmClient,
+        object_id: u16,
+        object_type: yubihsm::object::Type,
+    ) -> HsmResult<()> {
+        check_domain(client, self.active_domain, object_id, object_type)
+    }
+
+    /// Sidebar summary of the active session: its label, if one was given, auth
+    /// key and heartbeat status.
+    fn render_session_badge(&self) -> impl IntoElement {
+        let auth_key = self
+            .session
+            .peek_client()
+            .map(|client| format!("Auth key 0x{:04x}", client.auth_key_id()))
+            .unwrap_or_default();
+        let connection = self.session.connection_state();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .bg(rgb(0x2a2a2a))
+            .rounded_md()
+            .px_3()
+            .py_2()
+            .when_some(self.session.label().map(str::to_string), |el, label| {
+                el.child(div().text_sm().text_color(rgb(0xffffff)).child(label))
+            })
+            .child(div().text_xs().text_color(rgb(0x888888)).child(auth_key))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .w(px(8.))
+                            .h(px(8.))
+                            .rounded_full()
+                            .bg(rgb(match connection {
+                                ConnectionState::Connected => 0x28a745,
+                                ConnectionState::Degraded => 0xffc107,
+                                ConnectionState::Lost => 0xdc3545,
+                            })),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .child(connection.label()),
+                    ),
+            )
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x888888))
+                    .child(match &self.device_info {
+                        Some(info) => format!("Serial {}, firmware {}", info.serial, info.firmware),
+                        None => "Device info unavailable".to_string(),
+                    }),
+            )
+    }
+
+    /// Sidebar picker for the active domain: "Any" or one of the 16 domains.
+    fn render_domain_selector(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let chip = |label: String, value: Option<u8>, cx: &mut Context<'_, Self>| {
+            let is_active = self.active_domain == value;
+            div()
+                .w(px(30.))
+                .py_1()
+                .rounded_md()
+                .text_center()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(rgb(0xffffff))
+                .bg(if is_active {
+                    rgb(0x007acc)
+                } else {
+                    rgb(0x2a2a2a)
+                })
+                .hover(|style| style.bg(rgb(0x404040)))
+                .child(label)
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |view, _, _, cx| {
+                        view.active_domain = value;
+                        cx.notify();
+                    }),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child("Active domain"),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .child(chip("Any".to_string(), None, cx).w(px(64.)))
+                    .children((1..=16).map(|n| chip(n.to_string(), Some(n), cx))),
+            )
+    }
+
+    /// If `error` means the device is gone, drop the session and return to the
+    /// Auth screen with a banner. Returns true when the session was torn down.
+    fn handle_device_disconnected(&mut self, error: &HsmError, cx: &mut Context<'_, Self>) -> bool {
+        if !error.is_device_disconnected() {
+            return false;
+        }
+
+        self.disconnect_session(cx);
+        self.auth_banner = Some(SharedString::from(
+            "Device disconnected. Reconnect the YubiHSM2 and authenticate again.",
+        ));
+        cx.notify();
+        true
+    }
+}
+
+/// Fail unless the object is in `domain` (1-16); None allows any domain.
+fn check_domain(
+    client: &HsmClient,
+    domain: Option<u8>,
+    object_id: u16,
+    object_type: yubihsm::object::Type,
+) -> HsmResult<()> {
+    match domain {
+        Some(number) => {
+            let domain = hsm::domain_from_number(number)?;
+            client.with_session_retry(|c| hsm::ensure_in_domain(c, object_id, object_type, domain))
+        }
+        None => Ok(()),
+    }
 }
 
 impl Render for HsmApp {
@@ -182,19 +1119,27 @@ impl Render for HsmApp {
         // If not authenticated, show only the auth screen (no sidebar)
         if !self.session.is_authenticated() {
             return div()
+                .relative()
                 .flex()
                 .flex_col()
                 .bg(rgb(0x2e2e2e))
                 .size_full()
-                .child(self.render_auth_screen(cx));
+                .child(self.render_auth_screen(cx))
+                .when_some(self.modal.clone(), |el, modal| el.child(modal));
         }
 
         // If authenticated, show full UI with sidebar
         div()
+            .relative()
             .flex()
             .flex_row()
             .bg(rgb(0x2e2e2e))
             .size_full()
+            // Any activity resets the idle timer; capture so nothing can swallow it
+            .capture_any_mouse_down(cx.listener(|view, _, _, _| view.touch()))
+            .capture_key_down(cx.listener(|view, _, _, _| view.touch()))
+            .on_mouse_move(cx.listener(|view, _, _, _| view.touch()))
+            .on_action(cx.listener(Self::toggle_command_palette))
             .child(
                 // Sidebar navigation
                 div()
@@ -229,8 +1174,9 @@ impl Render for HsmApp {
                             .child("Sign & Verify")
                             .on_mouse_down(
                                 MouseButton::Left,
-                                cx.listener(|view, _, _, cx| {
+                                cx.listener(|view, _, window, cx| {
                                     view.current_screen = Screen::SignVerify;
+                                    window.focus(&view.sign_verify_focus);
                                     cx.notify();
                                 }),
                             )
@@ -260,8 +1206,195 @@ impl Render for HsmApp {
                                 }),
                             )
                     })
+                    .child({
+                        let is_active = self.current_screen == Screen::KeyGen;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("Key generation")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::KeyGen;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child({
+                        let is_active = self.current_screen == Screen::Random;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("Random bytes")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::Random;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child({
+                        let is_active = self.current_screen == Screen::Hmac;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("HMAC")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::Hmac;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child({
+                        let is_active = self.current_screen == Screen::Opaque;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("Opaque objects")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::Opaque;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child({
+                        let is_active = self.current_screen == Screen::Device;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("Device")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::Device;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child({
+                        let is_active = self.current_screen == Screen::AuditLog;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("Audit log")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::AuditLog;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child({
+                        let is_active = self.current_screen == Screen::Settings;
+                        let bg = if is_active {
+                            rgb(0x3c3c3c)
+                        } else {
+                            rgb(0x2a2a2a)
+                        };
+
+                        div()
+                            .bg(bg)
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .rounded_md()
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .child("Settings")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.current_screen = Screen::Settings;
+                                    cx.notify();
+                                }),
+                            )
+                    })
+                    .child(self.render_session_badge())
+                    .child(self.render_domain_selector(cx))
                     // Spacer to push the disconnect button to the bottom
                     .child(div().flex_grow())
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .text_center()
+                            .child(self.storage_summary()),
+                    )
+                    .when(self.destructive_disabled, |el| {
+                        el.child(screens::auth::safe_mode_badge())
+                    })
                     // Centered disconnect button at the bottom
                     .child(
                         div().flex().justify_center().child(
@@ -290,19 +1423,94 @@ impl Render for HsmApp {
                     Screen::Auth => self.render_auth_screen(cx),
                     Screen::SignVerify => self.render_sign_verify_screen(cx),
                     Screen::KeysConfig => self.render_keys_config_screen(cx),
+                    Screen::KeyGen => self.render_key_gen_screen(cx),
+                    Screen::Random => self.render_random_screen(cx),
+                    Screen::Hmac => self.render_hmac_screen(cx),
+                    Screen::Opaque => self.render_opaque_screen(cx),
+                    Screen::Device => self.render_device_screen(cx),
+                    Screen::AuditLog => self.render_audit_log_screen(cx),
+                    Screen::Settings => self.render_settings_screen(cx),
                 },
             )
+            .when_some(self.modal.clone(), |el, modal| el.child(modal))
+            .when_some(self.command_palette.clone(), |el, palette| {
+                el.child(palette)
+            })
     }
 }
 
 impl EventEmitter<()> for HsmApp {}
 
+/// How often the idle timer checks for inactivity
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Idle timeout for a number of minutes; 0 disables it.
+fn idle_timeout_from_minutes(minutes: u64) -> Option<Duration> {
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+/// Settings read from the command line and environment at startup.
+#[derive(Clone, Copy)]
+struct LaunchOptions {
+    /// Safe mode is on if `--safe` was passed or the safe mode env var is set
+    safe_mode: bool,
+    /// `--prehashed`: sign and verify the input as a precomputed digest
+    prehashed: bool,
+    /// Idle timeout in minutes from the environment; overrides the settings file
+    idle_minutes: Option<u64>,
+    /// Time between session heartbeats; None disables them
+    heartbeat_interval: Option<Duration>,
+    /// Initial log level; changed at runtime on the Device screen
+    log_level: LevelFilter,
+    /// What each logged sign or verify includes
+    log_redaction: hsm::RedactionLevel,
+}
+
+impl LaunchOptions {
+    fn from_env() -> Self {
+        let safe_mode = std::env::args().any(|arg| arg == SAFE_MODE_FLAG)
+            || std::env::var(SAFE_MODE_ENV).is_ok_and(|value| value == "1" || value == "true");
+        let prehashed = std::env::args().any(|arg| arg == PREHASHED_FLAG);
+
+        let idle_minutes = std::env::var(IDLE_TIMEOUT_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok());
+
+        let heartbeat_secs = std::env::var(HEARTBEAT_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_HEARTBEAT_SECS);
+
+        let log_level = std::env::var(LOG_LEVEL_ENV)
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_LOG_LEVEL);
+
+        let log_redaction = std::env::var(LOG_REDACTION_ENV)
+            .ok()
+            .and_then(|value| hsm::RedactionLevel::parse(&value))
+            .unwrap_or_default();
+
+        Self {
+            safe_mode,
+            prehashed,
+            log_level,
+            log_redaction,
+            idle_minutes,
+            heartbeat_interval: (heartbeat_secs > 0).then(|| Duration::from_secs(heartbeat_secs)),
+        }
+    }
+}
+
 fn main() {
     Application::new().run(|cx: &mut App| {
         // Initialize gpui-component (theme, global state, etc.)
         gpui_component::init(cx);
 
         let bounds = Bounds::centered(None, size(px(800.), px(600.)), cx);
+        let options = LaunchOptions::from_env();
+        hsm::init_logging(options.log_level);
+        let settings = Settings::load();
 
         // Bind keys for textarea actions
         cx.bind_keys([
@@ -316,20 +1524,85 @@ fn main() {
             KeyBinding::new("cmd-x", ui::textarea::Cut, None),
         ]);
 
+        // Bind keys for the command palette
+        cx.bind_keys([
+            KeyBinding::new("cmd-k", ToggleCommandPalette, None),
+            KeyBinding::new(
+                "up",
+                ui::command_palette::SelectPrev,
+                Some(ui::command_palette::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "down",
+                ui::command_palette::SelectNext,
+                Some(ui::command_palette::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "enter",
+                ui::command_palette::Confirm,
+                Some(ui::command_palette::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "escape",
+                ui::command_palette::Dismiss,
+                Some(ui::command_palette::KEY_CONTEXT),
+            ),
+        ]);
+
+        // Bind keys for the Keys config table; scoped so text inputs keep their keys
+        cx.bind_keys([
+            KeyBinding::new(
+                "up",
+                screens::keys_config::SelectPrevKey,
+                Some(screens::keys_config::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "down",
+                screens::keys_config::SelectNextKey,
+                Some(screens::keys_config::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "enter",
+                screens::keys_config::OpenKeyDetails,
+                Some(screens::keys_config::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "delete",
+                screens::keys_config::DeleteSelectedKey,
+                Some(screens::keys_config::KEY_CONTEXT),
+            ),
+            KeyBinding::new(
+                "backspace",
+                screens::keys_config::DeleteSelectedKey,
+                Some(screens::keys_config::KEY_CONTEXT),
+            ),
+        ]);
+
+        // Sign & Verify shortcuts; cmd-shift-v leaves the text inputs' cmd-v paste alone
+        cx.bind_keys([
+            KeyBinding::new("cmd-s", SignText, Some(screens::sign_verify::KEY_CONTEXT)),
+            KeyBinding::new(
+                "cmd-shift-v",
+                VerifyText,
+                Some(screens::sign_verify::KEY_CONTEXT),
+            ),
+        ]);
+
         let window = cx
             .open_window(
                 WindowOptions {
                     window_bounds: Some(WindowBounds::Windowed(bounds)),
                     ..Default::default()
                 },
-                |_, cx| cx.new(HsmApp::new),
+                move |_, cx| cx.new(|cx| HsmApp::new(cx, options, settings)),
             )
             .unwrap();
 
-        // Focus the auth password input on startup
+        // Focus the auth password input on startup, then connect if the user opted in
         window
             .update(cx, |view, window, cx| {
                 window.focus(&view.auth_password_input.focus_handle(cx));
+                view.auto_connect(window, cx);
             })
             .unwrap();
     });
@@ -0,0 +1,210 @@
+use gpui::{AppContext, Context, Focusable, SharedString, Window};
+
+use crate::{
+    CompareSignature, ExportForOpenssl, HsmApp, RecoverPublicKey, Screen, SignAndAttest, SignJws,
+    SignText, ToggleCommandPalette, VerifyText,
+    ui::{CommandPalette, CommandPaletteEvent},
+};
+
+/// Quick actions offered by the command palette.
+#[derive(Clone, Copy)]
+enum PaletteCommand {
+    Sign,
+    Verify,
+    Compare,
+    SignJws,
+    ExportForOpenssl,
+    RecoverPublicKey,
+    SignAndAttest,
+    ListKeys,
+    RefreshSessionInfo,
+    CreateScratchKey,
+    RunSelfTest,
+    RunBenchmark,
+    OpenSignVerify,
+    OpenKeysConfig,
+    OpenKeyGen,
+    OpenRandom,
+    OpenHmac,
+    OpenOpaque,
+    OpenDevice,
+    OpenAuditLog,
+    OpenSettings,
+    Disconnect,
+}
+
+impl PaletteCommand {
+    const ALL: &[PaletteCommand] = &[
+        PaletteCommand::Sign,
+        PaletteCommand::Verify,
+        PaletteCommand::Compare,
+        PaletteCommand::SignJws,
+        PaletteCommand::ExportForOpenssl,
+        PaletteCommand::RecoverPublicKey,
+        PaletteCommand::SignAndAttest,
+        PaletteCommand::ListKeys,
+        PaletteCommand::RefreshSessionInfo,
+        PaletteCommand::CreateScratchKey,
+        PaletteCommand::RunSelfTest,
+        PaletteCommand::RunBenchmark,
+        PaletteCommand::OpenSignVerify,
+        PaletteCommand::OpenKeysConfig,
+        PaletteCommand::OpenKeyGen,
+        PaletteCommand::OpenRandom,
+        PaletteCommand::OpenHmac,
+        PaletteCommand::OpenOpaque,
+        PaletteCommand::OpenDevice,
+        PaletteCommand::OpenAuditLog,
+        PaletteCommand::OpenSettings,
+        PaletteCommand::Disconnect,
+    ];
+
+    /// Commands that create or delete objects; hidden in safe mode.
+    fn is_destructive(self) -> bool {
+        matches!(self, PaletteCommand::CreateScratchKey)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PaletteCommand::Sign => "Sign text",
+            PaletteCommand::Verify => "Verify signature",
+            PaletteCommand::Compare => "Compare device vs host ECDSA",
+            PaletteCommand::SignJws => "Sign as JWS",
+            PaletteCommand::ExportForOpenssl => "Export signature for openssl",
+            PaletteCommand::RecoverPublicKey => "Recover public key from signature",
+            PaletteCommand::SignAndAttest => "Sign and attest the signing key",
+            PaletteCommand::ListKeys => "List keys",
+            PaletteCommand::RefreshSessionInfo => "Refresh session info",
+            PaletteCommand::CreateScratchKey => "Create scratch key",
+            PaletteCommand::RunSelfTest => "Run device self-test",
+            PaletteCommand::RunBenchmark => "Run signing benchmark",
+            PaletteCommand::OpenSignVerify => "Go to Sign & Verify",
+            PaletteCommand::OpenKeysConfig => "Go to Keys config",
+            PaletteCommand::OpenKeyGen => "Go to Key generation",
+            PaletteCommand::OpenRandom => "Go to Random bytes",
+            PaletteCommand::OpenHmac => "Go to HMAC",
+            PaletteCommand::OpenOpaque => "Go to Opaque objects",
+            PaletteCommand::OpenDevice => "Go to Device",
+            PaletteCommand::OpenAuditLog => "Go to Audit log",
+            PaletteCommand::OpenSettings => "Go to Settings",
+            PaletteCommand::Disconnect => "Disconnect",
+        }
+    }
+}
+
+impl HsmApp {
+    /// Open the command palette, or close it if it is already shown.
+    pub(crate) fn toggle_command_palette(
+        &mut self,
+        _: &ToggleCommandPalette,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        if self.command_palette.take().is_some() {
+            cx.notify();
+            return;
+        }
+
+        // Palette commands need a session; nothing to offer on the Auth screen
+        if !self.session.is_authenticated() {
+            return;
+        }
+
+        let commands: Vec<PaletteCommand> = PaletteCommand::ALL
+            .iter()
+            .copied()
+            .filter(|command| !(self.destructive_disabled && command.is_destructive()))
+            .collect();
+        let items = commands
+            .iter()
+            .map(|command| SharedString::from(command.label()))
+            .collect();
+        let palette = cx.new(|cx| CommandPalette::new(cx, items));
+
+        cx.subscribe_in(
+            &palette,
+            window,
+            move |view, _palette, event, window, cx| {
+                view.command_palette = None;
+                if let CommandPaletteEvent::Selected(ix) = event {
+                    view.run_palette_command(commands[*ix], window, cx);
+                }
+                cx.notify();
+            },
+        )
+        .detach();
+
+        window.focus(&palette.focus_handle(cx));
+        self.command_palette = Some(palette);
+        cx.notify();
+    }
+
+    fn run_palette_command(
+        &mut self,
+        command: PaletteCommand,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        match command {
+            PaletteCommand::Sign => {
+                self.current_screen = Screen::SignVerify;
+                self.sign_text(&SignText, window, cx);
+            }
+            PaletteCommand::Verify => {
+                self.current_screen = Screen::SignVerify;
+                self.verify_text(&VerifyText, window, cx);
+            }
+            PaletteCommand::Compare => {
+                self.current_screen = Screen::SignVerify;
+                self.compare_signature(&CompareSignature, window, cx);
+            }
+            PaletteCommand::SignJws => {
+                self.current_screen = Screen::SignVerify;
+                self.sign_jws(&SignJws, window, cx);
+            }
+            PaletteCommand::ExportForOpenssl => {
+                self.current_screen = Screen::SignVerify;
+                self.export_for_openssl(&ExportForOpenssl, window, cx);
+            }
+            PaletteCommand::RecoverPublicKey => {
+                self.current_screen = Screen::SignVerify;
+                self.recover_public_key(&RecoverPublicKey, window, cx);
+            }
+            PaletteCommand::SignAndAttest => {
+                self.current_screen = Screen::SignVerify;
+                self.sign_and_attest(&SignAndAttest, window, cx);
+            }
+            PaletteCommand::ListKeys => {
+                self.current_screen = Screen::KeysConfig;
+                self.load_keys_from_hsm(window, cx);
+            }
+            PaletteCommand::RefreshSessionInfo => {
+                self.refresh_session_info(&crate::RefreshSessionInfo, window, cx);
+            }
+            PaletteCommand::CreateScratchKey => {
+                self.current_screen = Screen::KeysConfig;
+                self.create_scratch_key(window, cx);
+            }
+            PaletteCommand::RunSelfTest => {
+                self.current_screen = Screen::KeysConfig;
+                self.run_self_test(cx);
+            }
+            PaletteCommand::RunBenchmark => {
+                self.current_screen = Screen::KeysConfig;
+                self.run_sign_benchmark(cx);
+            }
+            PaletteCommand::OpenSignVerify => self.current_screen = Screen::SignVerify,
+            PaletteCommand::OpenKeysConfig => self.current_screen = Screen::KeysConfig,
+            PaletteCommand::OpenKeyGen => self.current_screen = Screen::KeyGen,
+            PaletteCommand::OpenRandom => self.current_screen = Screen::Random,
+            PaletteCommand::OpenHmac => self.current_screen = Screen::Hmac,
+            PaletteCommand::OpenOpaque => self.current_screen = Screen::Opaque,
+            PaletteCommand::OpenDevice => self.current_screen = Screen::Device,
+            PaletteCommand::OpenAuditLog => self.current_screen = Screen::AuditLog,
+            PaletteCommand::OpenSettings => self.current_screen = Screen::Settings,
+            PaletteCommand::Disconnect => self.disconnect_session(cx),
+        }
+
+        cx.notify();
+    }
+}
@@ -0,0 +1,341 @@
+use gpui::{
+    AnyElement, App, Context, IntoElement, MouseButton, ParentElement, Styled, Window, div,
+    prelude::*, rgb,
+};
+use gpui_component::table::{Column, Table, TableDelegate, TableState};
+use yubihsm::audit::LogEntry;
+
+use crate::{
+    HsmApp,
+    hsm::{self, AuditLogPager, audit::DEFAULT_AUDIT_PAGE_SIZE, error::HsmError},
+    ui::Modal,
+};
+
+/// Shown when the log holds as many entries as the device can store
+const LOG_FULL_MESSAGE: &str = "The audit log is full. If the device has force-audit enabled it \
+refuses further commands until entries are acknowledged; click \"Acknowledge\" to free them.";
+
+/// Table delegate for the entries of the device audit log.
+pub struct AuditLogDelegate {
+    rows: Vec<LogEntry>,
+    columns: Vec<Column>,
+}
+
+impl AuditLogDelegate {
+    pub fn new(rows: Vec<LogEntry>) -> Self {
+        Self {
+            rows,
+            columns: vec![
+                Column::new("item", "Item").width(70.),
+                Column::new("cmd", "Command").width(200.),
+                Column::new("session", "Session key").width(100.),
+                Column::new("target", "Target key").width(100.),
+                Column::new("second", "Second key").width(100.),
+                Column::new("result", "Result").width(200.),
+            ],
+        }
+    }
+}
+
+impl AuditLogDelegate {
+    /// Add the entries of a later page below the ones shown.
+    fn append(&mut self, rows: Vec<LogEntry>) {
+        self.rows.extend(rows);
+    }
+
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+}
+
+impl TableDelegate for AuditLogDelegate {
+    fn columns_count(&self, _: &App) -> usize {
+        self.columns.len()
+    }
+
+    fn rows_count(&self, _: &App) -> usize {
+        self.rows.len()
+    }
+
+    fn column(&self, col_ix: usize, _: &App) -> &Column {
+        &self.columns[col_ix]
+    }
+
+    fn render_td(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        _: &mut Context<TableState<Self>>,
+    ) -> impl IntoElement {
+        let row = &self.rows[row_ix];
+        let failed = !row.result.is_success();
+
+        let text = match self.columns[col_ix].key.as_ref() {
+            "item" => format!("{}", row.item),
+            "cmd" => format!("{:?}", row.cmd),
+            "session" => format!("0x{:04x}", row.session_key),
+            "target" => format!("0x{:04x}", row.target_key),
+            "second" => format!("0x{:04x}", row.second_key),
+            "result" => format!("{:?}", row.result),
+            _ => String::new(),
+        };
+
+        div()
+            .text_color(if failed { rgb(0xff6b6b) } else { rgb(0xffffff) })
+            .child(text)
+    }
+}
+
+impl HsmApp {
+    /// Read the audit log in the background and show its first page in the table.
+    pub(crate) fn refresh_audit_log(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let capacity = self.device_info.as_ref().map(|info| info.log_capacity);
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            |client| client.with_session_retry(hsm::get_audit_log),
+            move |view, result, window, cx| match result {
+                Ok(log) => {
+                    let total = log.len();
+                    view.audit_pager = AuditLogPager::new(DEFAULT_AUDIT_PAGE_SIZE);
+                    let entries = view.audit_pager.next_page(log);
+                    view.audit_output = if capacity.is_some_and(|cap| total >= cap as usize) {
+                        LOG_FULL_MESSAGE.into()
+                    } else {
+                        format!(
+                            "Showing {} of {} log entries on the device.",
+                            entries.len(),
+                            total
+                        )
+                        .into()
+                    };
+                    view.audit_table = Some(
+                        cx.new(|cx| TableState::new(AuditLogDelegate::new(entries), window, cx)),
+                    );
+                }
+                Err(HsmError::AuditLogFull(_)) => {
+                    view.audit_output = LOG_FULL_MESSAGE.into();
+                }
+                Err(e) => {
+                    view.audit_table = None;
+                    view.audit_pager = AuditLogPager::new(DEFAULT_AUDIT_PAGE_SIZE);
+                    view.audit_output = format!("Error: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.audit_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    /// Read the audit log again and append the entries after the last one shown.
+    fn load_more_audit_log(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(state) = self.audit_table.clone() else {
+            return;
+        };
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            |client| client.with_session_retry(hsm::get_audit_log),
+            move |view, result, _, cx| match result {
+                Ok(log) => {
+                    let entries = view.audit_pager.next_page(log);
+                    let shown = state.update(cx, |table, cx| {
+                        table.delegate_mut().append(entries);
+                        table.refresh(cx);
+                        table.delegate().len()
+                    });
+                    view.audit_output = if view.audit_pager.has_more() {
+                        format!("Showing {} log entries; more are on the device.", shown).into()
+                    } else {
+                        format!("Showing all {} log entries.", shown).into()
+                    };
+                }
+                Err(HsmError::AuditLogFull(_)) => {
+                    view.audit_output = LOG_FULL_MESSAGE.into();
+                }
+                Err(e) => {
+                    view.audit_output = format!("Error: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.audit_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    /// Ask before marking every entry shown as read, since the device may then
+    /// overwrite them.
+    fn prompt_acknowledge_audit_log(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(last_item) = self.audit_pager.last_item() else {
+            return;
+        };
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Acknowledge log entries",
+                format!(
+                    "Mark entries up to item {} as read? The device may overwrite them afterwards.",
+                    last_item
+                ),
+            )
+            .confirm_label("Acknowledge")
+        });
+
+        self.open_modal(modal, window, cx, move |view, _, window, cx| {
+            let result = view
+                .session
+                .with_reconnect(|c| hsm::set_log_index(c, last_item));
+            match result {
+                Ok(()) => view.refresh_audit_log(window, cx),
+                Err(e) => {
+                    view.audit_output = format!("Error: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                    cx.notify();
+                }
+            }
+        });
+    }
+
+    pub fn render_audit_log_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let can_acknowledge = !self.hsm_busy && self.audit_pager.last_item().is_some();
+        let can_load_more = !self.hsm_busy && self.audit_pager.has_more();
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Audit log"),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(
+                "Commands the YubiHSM2 has recorded, oldest first. Failed commands are shown in red.",
+            ))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .bg(if self.hsm_busy {
+                                rgb(0x555555)
+                            } else {
+                                rgb(0x007acc)
+                            })
+                            .when(!self.hsm_busy, |el| {
+                                el.hover(|style| style.bg(rgb(0x005a9e)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.refresh_audit_log(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child(if self.hsm_busy {
+                                "Working…"
+                            } else {
+                                "Refresh log"
+                            }),
+                    )
+                    .child(
+                        div()
+                            .bg(if can_acknowledge {
+                                rgb(0x6c757d)
+                            } else {
+                                rgb(0x555555)
+                            })
+                            .when(can_acknowledge, |el| {
+                                el.hover(|style| style.bg(rgb(0x5a6268)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.prompt_acknowledge_audit_log(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child("Acknowledge"),
+                    )
+                    .child(
+                        div()
+                            .bg(if can_load_more {
+                                rgb(0x6c757d)
+                            } else {
+                                rgb(0x555555)
+                            })
+                            .when(can_load_more, |el| {
+                                el.hover(|style| style.bg(rgb(0x5a6268)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.load_more_audit_log(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child("Load more"),
+                    ),
+            )
+            .child(
+                div()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .p_2()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(self.audit_output.clone()),
+            )
+            .when_some(self.audit_table.clone(), |el, state| {
+                el.child(
+                    div()
+                        .flex_1()
+                        .min_h_0()
+                        .w_full()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .child(
+                            Table::new(&state)
+                                .stripe(true)
+                                .bordered(true)
+                                .scrollbar_visible(true, true),
+                        ),
+                )
+            })
+            .into_any()
+    }
+}
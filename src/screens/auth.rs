@@ -1,13 +1,244 @@
 use gpui::{
     AnyElement, Context, Element, InteractiveElement, MouseButton, ParentElement, SharedString,
-    Styled, div, rgb,
+    Styled, Window, div, prelude::FluentBuilder, rgb,
 };
 
-use crate::{HsmApp, Screen, config::DEFAULT_AUTH_KEY_ID, hsm::HsmConfig};
+use crate::{
+    HsmApp, Screen,
+    config::{self, CONNECT_ATTEMPTS, CONNECT_RETRY_BASE_DELAY_MS},
+    hsm::{
+        self, ConnectRetry, ConnectorKind, HsmClient, HsmConfig,
+        error::{HsmError, HsmResult},
+    },
+};
+use std::time::Duration;
+use yubihsm::device::SerialNumber;
+use zeroize::Zeroizing;
+
+/// Indicator shown while safe mode is active.
+pub(crate) fn safe_mode_badge() -> AnyElement {
+    div()
+        .bg(rgb(0x3d3300))
+        .border_1()
+        .border_color(rgb(0xffc107))
+        .rounded_md()
+        .px_2()
+        .py_1()
+        .text_xs()
+        .text_color(rgb(0xffc107))
+        .child("Safe mode: delete, generate and import are disabled")
+        .into_any()
+}
 
 impl HsmApp {
-    fn authenticate_session(&mut self, cx: &mut Context<'_, Self>) {
-        let password = self.auth_password_input.read(cx).content();
+    /// Refresh the list of USB devices. A single device is selected automatically;
+    /// with several, the previous choice is kept if that device is still present.
+    pub(crate) fn scan_usb_devices(&mut self) {
+        if !matches!(ConnectorKind::default(), ConnectorKind::Usb { .. }) {
+            return;
+        }
+
+        match hsm::list_usb_devices() {
+            Ok(devices) => {
+                self.selected_device = match devices.as_slice() {
+                    [only] => Some(*only),
+                    _ => self
+                        .selected_device
+                        .filter(|serial| devices.contains(serial)),
+                };
+                self.usb_devices = devices;
+            }
+            Err(e) => {
+                self.usb_devices = Vec::new();
+                self.selected_device = None;
+                self.auth_status = format!("{}", e).into();
+            }
+        }
+    }
+
+    /// Radio buttons choosing between USB and a yubihsm-connector over HTTP.
+    fn render_connector_choice(&self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let option = |label: &'static str, use_http: bool, cx: &mut Context<'_, Self>| {
+            div()
+                .flex()
+                .gap_2()
+                .cursor_pointer()
+                .text_sm()
+                .text_color(rgb(0xcccccc))
+                .child(if self.auth_use_http == use_http {
+                    "(o)"
+                } else {
+                    "( )"
+                })
+                .child(label)
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |view, _, _, cx| {
+                        view.auth_use_http = use_http;
+                        view.update_settings(|settings| settings.use_http = use_http);
+                        if !use_http {
+                            view.scan_usb_devices();
+                        }
+                        cx.notify();
+                    }),
+                )
+        };
+
+        div()
+            .flex()
+            .gap_4()
+            .child(option("USB", false, cx))
+            .child(option("HTTP (yubihsm-connector)", true, cx))
+            .into_any()
+    }
+
+    fn render_device_picker(&self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let summary = match self.usb_devices.len() {
+            0 => "No YubiHSM2 found on USB. Plug in a device and click Rescan.".to_string(),
+            1 => format!("Using YubiHSM2 with serial {}.", self.usb_devices[0]),
+            n => format!("{} YubiHSM2 devices found. Pick the one to connect to:", n),
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(div().text_sm().text_color(rgb(0xcccccc)).child(summary))
+                    .child(
+                        div()
+                            .bg(rgb(0x6c757d))
+                            .hover(|style| style.bg(rgb(0x5a6268)))
+                            .rounded_md()
+                            .px_2()
+                            .py_1()
+                            .text_xs()
+                            .text_color(rgb(0xffffff))
+                            .cursor_pointer()
+                            .child("Rescan")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.scan_usb_devices();
+                                    cx.notify();
+                                }),
+                            ),
+                    ),
+            )
+            .when(self.usb_devices.len() > 1, |el| {
+                el.children(self.usb_devices.iter().map(|&serial| {
+                    let is_selected = self.selected_device == Some(serial);
+                    div()
+                        .flex()
+                        .gap_2()
+                        .cursor_pointer()
+                        .text_sm()
+                        .text_color(rgb(0xcccccc))
+                        .child(if is_selected { "(o)" } else { "( )" })
+                        .child(format!("Serial {}", serial))
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |view, _, _, cx| {
+                                view.selected_device = Some(serial);
+                                cx.notify();
+                            }),
+                        )
+                }))
+            })
+            .child(
+                div()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .p_2()
+                    .min_h(gpui::px(24.))
+                    .child(self.auth_serial_input.clone()),
+            )
+            .into_any()
+    }
+
+    /// Collapsible section with connection details that rarely need attention.
+    fn render_auth_advanced(&self, cx: &mut Context<'_, Self>) -> AnyElement {
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .child(
+                div()
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(if self.auth_show_advanced {
+                        "[-] Advanced"
+                    } else {
+                        "[+] Advanced"
+                    })
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.auth_show_advanced = !view.auth_show_advanced;
+                            cx.notify();
+                        }),
+                    ),
+            )
+            .when(self.auth_show_advanced, |el| {
+                el.child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .pl_4()
+                        .text_xs()
+                        .text_color(rgb(0x888888))
+                        .child(format!(
+                            "Password key derivation: {}, salt \"{}\", {} iterations, {} bytes \
+                             (encryption key || MAC key). Fixed by the yubihsm library and \
+                             compatible with yubihsm-shell; keys set up with other parameters \
+                             will not authenticate.",
+                            hsm::PASSWORD_KDF,
+                            hsm::PASSWORD_KDF_SALT,
+                            hsm::PASSWORD_KDF_ITERATIONS,
+                            hsm::PASSWORD_KDF_OUTPUT_LEN
+                        ))
+                        .child(match self.idle_timeout {
+                            Some(timeout) => format!(
+                                "Idle timeout: the session is closed after {} minute(s) without activity.",
+                                timeout.as_secs() / 60
+                            ),
+                            None => "Idle timeout: disabled.".to_string(),
+                        })
+                        .child(
+                            "Connect timeout: how long to wait for the device or connector before \
+                             giving up. Empty uses the default.",
+                        )
+                        .child(
+                            div()
+                                .bg(rgb(0x1e1e1e))
+                                .border_1()
+                                .border_color(rgb(0x444444))
+                                .rounded_md()
+                                .p_2()
+                                .min_h(gpui::px(24.))
+                                .w(gpui::px(240.))
+                                .text_sm()
+                                .child(self.auth_timeout_input.clone()),
+                        ),
+                )
+            })
+            .into_any()
+    }
+
+    fn authenticate_session(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        if self.auth_connecting.is_some() {
+            return;
+        }
+        // Wiped on every return path, including the early ones below
+        let password = Zeroizing::new(self.auth_password_input.read(cx).content());
 
         if password.trim().is_empty() {
             self.auth_status = SharedString::from("Password cannot be empty.");
@@ -15,21 +246,231 @@ impl HsmApp {
             return;
         }
 
+        let typed_timeout = self
+            .auth_timeout_input
+            .read(cx)
+            .content()
+            .trim()
+            .to_string();
+        let connect_timeout = if typed_timeout.is_empty() {
+            hsm::DEFAULT_CONNECT_TIMEOUT
+        } else {
+            match typed_timeout.parse::<u64>() {
+                Ok(secs) if secs > 0 => Duration::from_secs(secs),
+                _ => {
+                    self.auth_status = format!(
+                        "'{}' is not a connect timeout; enter a whole number of seconds.",
+                        typed_timeout
+                    )
+                    .into();
+                    cx.notify();
+                    return;
+                }
+            }
+        };
+
+        let typed_serial = self.auth_serial_input.read(cx).content().trim().to_string();
+        let connector = if self.auth_use_http {
+            match ConnectorKind::http_from_address(&self.auth_connector_input.read(cx).content()) {
+                Ok(connector) => connector,
+                Err(e) => {
+                    self.auth_status = format!("{}", e).into();
+                    cx.notify();
+                    return;
+                }
+            }
+        } else if !cfg!(feature = "usb") {
+            // Connecting reports that USB support is not compiled in
+            ConnectorKind::Usb { serial: None }
+        } else if !typed_serial.is_empty() {
+            // A typed serial wins, so a device the scan missed can still be reached
+            match typed_serial.parse::<SerialNumber>() {
+                Ok(serial) => ConnectorKind::Usb {
+                    serial: Some(serial),
+                },
+                Err(_) => {
+                    self.auth_status =
+                        format!("'{}' is not a YubiHSM2 serial number.", typed_serial).into();
+                    cx.notify();
+                    return;
+                }
+            }
+        } else {
+            // Explain a missing or ambiguous device instead of letting the connect fail
+            if self.usb_devices.is_empty() {
+                self.auth_status = SharedString::from(
+                    "No YubiHSM2 detected on USB. Plug in a device and click Rescan.",
+                );
+                cx.notify();
+                return;
+            }
+            let Some(serial) = self.selected_device else {
+                self.auth_status = SharedString::from(
+                    "Several YubiHSM2 devices are connected. Pick one above or enter its serial number, then click Connect.",
+                );
+                cx.notify();
+                return;
+            };
+            ConnectorKind::Usb {
+                serial: Some(serial),
+            }
+        };
+
+        // Kept for the keyring once the password is known to work
+        let remember = (self.settings.auto_connect && self.auth_use_http).then(|| password.clone());
         let config = HsmConfig {
-            auth_key_id: DEFAULT_AUTH_KEY_ID,
+            auth_key_id: self.settings.auth_key_id,
             auth_password: password,
+            connector,
+            connect_timeout,
+            destructive_disabled: self.destructive_disabled,
         };
 
-        match self.session.connect(config) {
-            Ok(()) => {
+        self.auth_status = SharedString::from("Connecting…");
+        self.connect(config, remember, window, cx);
+    }
+
+    /// Connect over HTTP with the saved connector address and the password from
+    /// the keyring, if auto-connect is on. Failures are shown on the Auth screen,
+    /// whose fields already hold the saved values.
+    pub(crate) fn auto_connect(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        if !self.settings.auto_connect || !self.settings.use_http {
+            return;
+        }
+        let connector = match ConnectorKind::http_from_address(&self.settings.connector_address) {
+            Ok(connector) => connector,
+            Err(e) => {
+                self.auth_status = format!("Auto-connect skipped: {}", e).into();
+                cx.notify();
+                return;
+            }
+        };
+        let Some(password) = config::stored_password(self.settings.auth_key_id) else {
+            self.auth_status = SharedString::from(
+                "Auto-connect is on but no password is stored in the keyring. Connect once to store it.",
+            );
+            cx.notify();
+            return;
+        };
+
+        let config = HsmConfig {
+            auth_key_id: self.settings.auth_key_id,
+            auth_password: password,
+            connector,
+            connect_timeout: hsm::DEFAULT_CONNECT_TIMEOUT,
+            destructive_disabled: self.destructive_disabled,
+        };
+
+        self.auth_status = SharedString::from("Auto-connecting…");
+        self.connect(config, None, window, cx);
+    }
+
+    /// Open a session with `config`, retrying while the device or connector is
+    /// unreachable. On success `remember` is stored in the keyring for auto-connect.
+    fn connect(
+        &mut self,
+        config: HsmConfig,
+        remember: Option<Zeroizing<String>>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let label = self.auth_label_input.read(cx).content();
+        let retry = ConnectRetry {
+            attempts: CONNECT_ATTEMPTS,
+            base_delay: Duration::from_millis(CONNECT_RETRY_BASE_DELAY_MS),
+        };
+
+        cx.notify();
+
+        // Connecting blocks, so each attempt runs off the UI thread
+        self.auth_connecting = Some(cx.spawn_in(window, async move |this, cx| {
+            let mut attempt = 1;
+            let result = loop {
+                let config = config.clone();
+                let result = cx
+                    .background_executor()
+                    .spawn(async move { HsmClient::connect(config) })
+                    .await;
+                match result {
+                    Err(e) if retry.should_retry(attempt, &e) => {
+                        let delay = retry.delay(attempt);
+                        attempt += 1;
+                        let status = format!("{}. Retrying ({}/{})…", e, attempt, retry.attempts);
+                        let _ = this.update(cx, |app, cx| {
+                            app.auth_status = status.into();
+                            cx.notify();
+                        });
+                        cx.background_executor().timer(delay).await;
+                    }
+                    result => break result,
+                }
+            };
+
+            let _ = this.update_in(cx, |app, window, cx| {
+                app.auth_connecting = None;
+                app.finish_authentication(result, label, remember, window, cx);
+            });
+        }));
+    }
+
+    /// Make a successful connection the active session, or explain the failure.
+    fn finish_authentication(
+        &mut self,
+        result: HsmResult<HsmClient>,
+        label: String,
+        remember: Option<Zeroizing<String>>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        match result {
+            Ok(client) => {
+                self.session.adopt_labeled(client, Some(label));
                 self.auth_status =
                     SharedString::from("Successfully authenticated to YubiHSM session.");
+                self.start_idle_timer(cx);
+                self.start_heartbeat(window, cx);
+                self.reset_domain_pickers(cx);
+                self.auth_banner = None;
+                if self.auth_use_http {
+                    // Remember the connector that worked for the next start
+                    let address = self
+                        .auth_connector_input
+                        .read(cx)
+                        .content()
+                        .trim()
+                        .to_string();
+                    self.update_settings(|settings| settings.connector_address = address);
+                }
+                let auth_key_id = self.settings.auth_key_id;
+                if let Some(Err(e)) =
+                    remember.map(|password| config::store_password(auth_key_id, &password))
+                {
+                    tracing::warn!(error = %e, "cannot store the password for auto-connect");
+                }
                 // After successful auth, switch to main Sign & Verify screen
                 self.current_screen = Screen::SignVerify;
+                window.focus(&self.sign_verify_focus);
                 // Clear the password field for security
                 self.auth_password_input.update(cx, |input, cx| {
                     input.set_content(String::new(), cx);
                 });
+                self.refresh_device_info(cx);
+                self.refresh_signing_keys(window, cx);
+            }
+            Err(e @ HsmError::ConnectorTimedOut(_)) => {
+                self.auth_status = format!(
+                    "{}. Check that the connector is running at that address, or raise the \
+                     connect timeout under Advanced.",
+                    e
+                )
+                .into();
+            }
+            Err(e @ (HsmError::ConnectorUnavailable(_) | HsmError::ConnectorUnreachable(_))) => {
+                self.auth_status = format!(
+                    "{}. Check that it is plugged in or the connector is running.",
+                    e
+                )
+                .into();
             }
             Err(e) => {
                 self.auth_status = format!("Authentication failed: {}", e).into();
@@ -55,9 +496,39 @@ impl HsmApp {
                     .text_color(rgb(0xffffff))
                     .child("Authenticate YubiHSM session"),
             )
+            .when(self.destructive_disabled, |el| el.child(safe_mode_badge()))
+            .when_some(self.auth_banner.clone(), |el, banner| {
+                el.child(
+                    div()
+                        .bg(rgb(0x5c1e24))
+                        .border_1()
+                        .border_color(rgb(0xdc3545))
+                        .rounded_md()
+                        .p_2()
+                        .text_sm()
+                        .text_color(rgb(0xffffff))
+                        .child(banner),
+                )
+            })
             .child(div().text_xs().text_color(rgb(0x888888)).child(
                 "Enter the authentication password for the YubiHSM auth key, then click Connect.",
             ))
+            .child(self.render_connector_choice(cx))
+            .when(!self.auth_use_http, |el| {
+                el.child(self.render_device_picker(cx))
+            })
+            .when(self.auth_use_http, |el| {
+                el.child(
+                    div()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .p_2()
+                        .min_h(gpui::px(24.))
+                        .child(self.auth_connector_input.clone()),
+                )
+            })
             .child(
                 div()
                     .flex()
@@ -67,7 +538,7 @@ impl HsmApp {
                         div()
                             .text_sm()
                             .text_color(rgb(0xcccccc))
-                            .child(format!("Auth key ID: 0x{:04x}", DEFAULT_AUTH_KEY_ID)),
+                            .child(format!("Auth key ID: 0x{:04x}", self.settings.auth_key_id)),
                     )
                     .child(
                         div()
@@ -77,29 +548,50 @@ impl HsmApp {
                             .rounded_md()
                             .p_2()
                             .min_h(gpui::px(24.))
-                            // Note: TextArea doesn't mask input; this is a simple demo.
                             .child(self.auth_password_input.clone()),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x1e1e1e))
+                            .border_1()
+                            .border_color(rgb(0x444444))
+                            .rounded_md()
+                            .p_2()
+                            .min_h(gpui::px(24.))
+                            .child(self.auth_label_input.clone()),
                     ),
             )
-            .child(
+            .child(self.render_auth_advanced(cx))
+            .child({
+                let connecting = self.auth_connecting.is_some();
                 div().flex().gap_2().child(
                     div()
-                        .bg(rgb(0x28a745))
-                        .hover(|style| style.bg(rgb(0x1e7e34)))
+                        .bg(if connecting {
+                            rgb(0x555555)
+                        } else {
+                            rgb(0x28a745)
+                        })
+                        .when(!connecting, |el| {
+                            el.hover(|style| style.bg(rgb(0x1e7e34)))
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.authenticate_session(window, cx);
+                                    }),
+                                )
+                        })
                         .rounded_md()
                         .px_4()
                         .py_2()
                         .text_color(rgb(0xffffff))
-                        .cursor_pointer()
-                        .child("Connect")
-                        .on_mouse_down(
-                            MouseButton::Left,
-                            cx.listener(|view, _, _, cx| {
-                                view.authenticate_session(cx);
-                            }),
-                        ),
-                ),
-            )
+                        .child(if connecting {
+                            "Connecting…"
+                        } else {
+                            "Connect"
+                        }),
+                )
+            })
             .child(
                 div()
                     .bg(rgb(0x1e1e1e))
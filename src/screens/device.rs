@@ -0,0 +1,457 @@
+use gpui::{
+    AnyElement, Context, MouseButton, ParentElement, SharedString, Styled, div, prelude::*, px, rgb,
+};
+use tracing::level_filters::LevelFilter;
+use yubihsm::Capability;
+use zeroize::Zeroizing;
+
+use crate::{HsmApp, hsm};
+
+/// Word the user must type before "Reset device" enables
+const RESET_CONFIRM_WORD: &str = "RESET";
+
+impl HsmApp {
+    /// Query the device info again and cache it; a failure leaves `None`, which
+    /// renders as "unavailable" instead of an error.
+    pub(crate) fn refresh_device_info(&mut self, cx: &mut Context<'_, Self>) {
+        match self.session.with_reconnect(hsm::device_info) {
+            Ok(info) => self.device_info = Some(info),
+            Err(e) => {
+                self.device_info = None;
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+        self.refresh_storage_info(cx);
+
+        cx.notify();
+    }
+
+    /// Query free storage again. Like the device info, a failure (e.g. a stale
+    /// session) only leaves `None`, shown as a dash.
+    pub(crate) fn refresh_storage_info(&mut self, cx: &mut Context<'_, Self>) {
+        match self.session.with_reconnect(hsm::storage_info) {
+            Ok(info) => self.storage_info = Some(info),
+            Err(e) => {
+                self.storage_info = None;
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// "records: X free of Y", or a dash while unknown.
+    pub(crate) fn storage_summary(&self) -> String {
+        match &self.storage_info {
+            Some(info) => format!(
+                "records: {} free of {}",
+                info.free_records, info.total_records
+            ),
+            None => "records: –".to_string(),
+        }
+    }
+
+    fn set_log_level(&mut self, level: LevelFilter, cx: &mut Context<'_, Self>) {
+        hsm::set_log_level(level);
+        self.log_level = level;
+        cx.notify();
+    }
+
+    /// Chips choosing how much is logged to stderr.
+    fn render_log_level_picker(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(div().text_lg().text_color(rgb(0xffffff)).child("Logging"))
+            .child(div().text_xs().text_color(rgb(0x888888)).child(
+                "Device operations are logged to stderr with their key IDs, lengths and \
+                 duration. Passwords and key material are never logged.",
+            ))
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .gap_1()
+                    .children(hsm::LOG_LEVELS.iter().map(|&level| {
+                        div()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .text_xs()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .bg(if self.log_level == level {
+                                rgb(0x007acc)
+                            } else {
+                                rgb(0x2a2a2a)
+                            })
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .child(level.to_string())
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |view, _, _, cx| {
+                                    view.set_log_level(level, cx);
+                                }),
+                            )
+                    })),
+            )
+    }
+
+    /// Factory-reset the device, then drop the now invalid session and return
+    /// to the Auth screen.
+    fn reset_device(&mut self, cx: &mut Context<'_, Self>) {
+        if self.reset_confirm_input.read(cx).content().trim() != RESET_CONFIRM_WORD {
+            return;
+        }
+
+        // No session retry: a second attempt would reset a freshly reset device
+        let result = self.session.active_client().and_then(hsm::reset_device);
+
+        match result {
+            Ok(()) => {
+                // The reset erased the scratch key along with everything else
+                self.scratch_key = None;
+                self.disconnect_session(cx);
+                self.auth_banner = Some(SharedString::from(
+                    "The YubiHSM2 was reset to factory defaults. Log in with auth key 1 and the \
+                     password \"password\", then change it.",
+                ));
+            }
+            Err(e) => {
+                if !self.handle_device_disconnected(&e, cx) {
+                    self.reset_confirm_input.update(cx, |input, cx| {
+                        input.set_content(String::new(), cx);
+                    });
+                    self.reset_error = Some(format!("Error: {}", e).into());
+                }
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Change the session's own auth key password from the form, then
+    /// disconnect so the next session uses the new password.
+    fn change_password(&mut self, cx: &mut Context<'_, Self>) {
+        let old_password = Zeroizing::new(self.password_old_input.read(cx).content());
+        let new_password = Zeroizing::new(self.password_new_input.read(cx).content());
+        let confirm_password = Zeroizing::new(self.password_confirm_input.read(cx).content());
+
+        if old_password.is_empty() || new_password.trim().is_empty() {
+            self.password_error = Some("Enter the current password and a new one.".into());
+            cx.notify();
+            return;
+        }
+        if new_password != confirm_password {
+            self.password_error = Some("The new passwords do not match.".into());
+            cx.notify();
+            return;
+        }
+
+        // Not retried: once changed, renegotiating with the old password fails
+        let result = self.session.active_client().and_then(|client| {
+            let key_id = client.auth_key_id();
+            hsm::change_auth_password(client, key_id, &old_password, &new_password).map(|()| key_id)
+        });
+
+        match result {
+            Ok(key_id) => {
+                self.disconnect_session(cx);
+                self.auth_banner = Some(
+                    format!(
+                        "The password of auth key 0x{:04x} was changed. Authenticate again with the new password.",
+                        key_id
+                    )
+                    .into(),
+                );
+            }
+            Err(e) => {
+                if !self.handle_device_disconnected(&e, cx) {
+                    self.password_error = Some(format!("Error: {}", e).into());
+                }
+            }
+        }
+
+        cx.notify();
+    }
+
+    pub fn render_device_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let can_change_password = !self.destructive_disabled
+            && !self.hsm_busy
+            && self.session_allows(Capability::CHANGE_AUTHENTICATION_KEY);
+        let reset_allowed =
+            !self.destructive_disabled && self.session_allows(Capability::RESET_DEVICE);
+        let can_reset = reset_allowed
+            && !self.hsm_busy
+            && self.reset_confirm_input.read(cx).content().trim() == RESET_CONFIRM_WORD;
+
+        let row = |name: &'static str, value: String| {
+            div()
+                .flex()
+                .gap_2()
+                .text_sm()
+                .child(
+                    div()
+                        .w(gpui::px(140.))
+                        .text_color(rgb(0x888888))
+                        .child(name),
+                )
+                .child(div().text_color(rgb(0xffffff)).child(value))
+        };
+
+        let password_field = |caption: &'static str, input: AnyElement| {
+            div()
+                .flex()
+                .items_center()
+                .gap_2()
+                .child(
+                    div()
+                        .w(px(140.))
+                        .text_sm()
+                        .text_color(rgb(0x888888))
+                        .child(caption),
+                )
+                .child(
+                    div()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .p_2()
+                        .min_h(px(24.))
+                        .w(px(240.))
+                        .child(input),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Device"),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(
+                "What the connected YubiHSM2 reports about itself, read once after connecting.",
+            ))
+            .child(match &self.device_info {
+                Some(info) => {
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_2()
+                        .child(row("Serial number", info.serial.clone()))
+                        .child(row("Firmware", info.firmware.clone()))
+                        .child(row(
+                            "Audit log",
+                            format!("{} of {} entries used", info.log_used, info.log_capacity),
+                        ))
+                        .child(row("Storage", self.storage_summary()))
+                        .child(row(
+                            "Pages",
+                            match &self.storage_info {
+                                Some(storage) => format!(
+                                    "{} free of {} ({} bytes each)",
+                                    storage.free_pages, storage.total_pages, storage.page_size
+                                ),
+                                None => "–".to_string(),
+                            },
+                        ))
+                        .child(row(
+                            "Algorithms",
+                            format!("{} supported", info.algorithms.len()),
+                        ))
+                        .child(div().flex().flex_wrap().gap_1().children(
+                            info.algorithms.iter().map(|algorithm| {
+                                div()
+                                    .px_2()
+                                    .py_1()
+                                    .rounded_md()
+                                    .bg(rgb(0x2a2a2a))
+                                    .text_xs()
+                                    .text_color(rgb(0xcccccc))
+                                    .child(format!("{:?}", algorithm))
+                            }),
+                        ))
+                }
+                None => div()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child("Device info unavailable. Click Refresh to query the device again."),
+            })
+            .child(
+                div().flex().gap_2().child(
+                    div()
+                        .bg(rgb(0x6c757d))
+                        .hover(|style| style.bg(rgb(0x5a6268)))
+                        .rounded_md()
+                        .px_4()
+                        .py_2()
+                        .cursor_pointer()
+                        .text_color(rgb(0xffffff))
+                        .child("Refresh")
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|view, _, _, cx| {
+                                view.refresh_device_info(cx);
+                            }),
+                        ),
+                ),
+            )
+            .child(div().mt_4().child(self.render_log_level_picker(cx)))
+            .child(
+                div()
+                    .mt_4()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_lg()
+                            .text_color(rgb(0xffffff))
+                            .child("Change password"),
+                    )
+                    .child(div().text_xs().text_color(rgb(0x888888)).child(
+                        "Sets a new password on this session's auth key. You are logged out \
+                         afterwards and must authenticate with the new password.",
+                    ))
+                    .child(password_field(
+                        "Current password",
+                        self.password_old_input.clone().into_any_element(),
+                    ))
+                    .child(password_field(
+                        "New password",
+                        self.password_new_input.clone().into_any_element(),
+                    ))
+                    .child(password_field(
+                        "Repeat new password",
+                        self.password_confirm_input.clone().into_any_element(),
+                    ))
+                    .child(
+                        div().flex().child(
+                            div()
+                                .bg(if can_change_password {
+                                    rgb(0x007acc)
+                                } else {
+                                    rgb(0x555555)
+                                })
+                                .when(can_change_password, |el| {
+                                    el.hover(|style| style.bg(rgb(0x005a9e)))
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(|view, _, _, cx| {
+                                                view.change_password(cx);
+                                            }),
+                                        )
+                                })
+                                .rounded_md()
+                                .px_4()
+                                .py_2()
+                                .text_color(rgb(0xffffff))
+                                .child("Change password"),
+                        ),
+                    )
+                    .when(
+                        !self.destructive_disabled
+                            && !self.session_allows(Capability::CHANGE_AUTHENTICATION_KEY),
+                        |el| {
+                            el.child(div().text_xs().text_color(rgb(0x888888)).child(
+                                "This session's auth key lacks the change-authentication-key capability.",
+                            ))
+                        },
+                    )
+                    .when_some(self.password_error.clone(), |el, error| {
+                        el.child(div().text_sm().text_color(rgb(0xff6b6b)).child(error))
+                    }),
+            )
+            .child(
+                div()
+                    .mt_4()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .p_3()
+                    .border_1()
+                    .border_color(rgb(0xdc3545))
+                    .rounded_md()
+                    .child(
+                        div()
+                            .text_lg()
+                            .text_color(rgb(0xff6b6b))
+                            .child("Danger zone"),
+                    )
+                    .child(div().text_sm().text_color(rgb(0xcccccc)).child(
+                        "Factory reset erases every key and object on the device and clears the \
+                         audit log. Only the default auth key 1 (password \"password\") remains. \
+                         This cannot be undone.",
+                    ))
+                    .when(!reset_allowed, |el| {
+                        el.child(div().text_xs().text_color(rgb(0x888888)).child(
+                            if self.destructive_disabled {
+                                "Unavailable in safe mode."
+                            } else {
+                                "This session's auth key lacks the reset-device capability."
+                            },
+                        ))
+                    })
+                    .when(reset_allowed, |el| {
+                        el.child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0x888888))
+                                .child("Type RESET to enable the button:"),
+                        )
+                        .child(
+                            div()
+                                .bg(rgb(0x1e1e1e))
+                                .border_1()
+                                .border_color(rgb(0x444444))
+                                .rounded_md()
+                                .p_2()
+                                .min_h(px(24.))
+                                .w(px(200.))
+                                .child(self.reset_confirm_input.clone()),
+                        )
+                    })
+                    .child(
+                        div().flex().child(
+                            div()
+                                .bg(if can_reset {
+                                    rgb(0xdc3545)
+                                } else {
+                                    rgb(0x555555)
+                                })
+                                .when(can_reset, |el| {
+                                    el.hover(|style| style.bg(rgb(0xc82333)))
+                                        .cursor_pointer()
+                                        .on_mouse_down(
+                                            MouseButton::Left,
+                                            cx.listener(|view, _, _, cx| {
+                                                view.reset_device(cx);
+                                            }),
+                                        )
+                                })
+                                .rounded_md()
+                                .px_4()
+                                .py_2()
+                                .text_color(rgb(0xffffff))
+                                .child("Reset device"),
+                        ),
+                    )
+                    .when_some(self.reset_error.clone(), |el, error| {
+                        el.child(div().text_sm().text_color(rgb(0xff6b6b)).child(error))
+                    }),
+            )
+            .into_any()
+    }
+}
@@ -0,0 +1,319 @@
+use gpui::{
+    AnyElement, Context, MouseButton, ParentElement, Styled, Window, div, prelude::*, px, rgb,
+};
+use yubihsm::object::Type;
+
+use crate::{HsmApp, hsm, screens::sign_verify::parse_key_id};
+
+impl HsmApp {
+    /// Keep the HMAC keys of a fresh listing for the key picker.
+    pub(crate) fn set_hmac_keys(&mut self, rows: &[hsm::ObjectSummary]) {
+        self.hmac_keys = rows
+            .iter()
+            .filter(|row| row.object_type == Type::HmacKey)
+            .cloned()
+            .collect();
+        let keys = &self.hmac_keys;
+        if self
+            .selected_hmac_key
+            .is_some_and(|key_id| !keys.iter().any(|key| key.object_id == key_id))
+        {
+            self.selected_hmac_key = None;
+        }
+    }
+
+    /// Key from the ID field, falling back to the picker.
+    fn hmac_key_id(&self, cx: &Context<'_, Self>) -> Result<u16, String> {
+        let input = self.hmac_key_input.read(cx).content();
+        let input = input.trim();
+        if input.is_empty() {
+            return self
+                .selected_hmac_key
+                .ok_or_else(|| "Pick an HMAC key or enter its ID.".to_string());
+        }
+        parse_key_id(input)
+            .ok_or_else(|| format!("'{}' is not a key ID; use hex with 0x or decimal.", input))
+    }
+
+    /// Compute the tag of the data field on the device.
+    fn compute_hmac(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let key_id = match self.hmac_key_id(cx) {
+            Ok(key_id) => key_id,
+            Err(message) => {
+                self.hmac_output = message.into();
+                cx.notify();
+                return;
+            }
+        };
+        let data = self.hmac_data_input.read(cx).content();
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| client.with_session_retry(|c| hsm::hmac_sign(c, key_id, data.as_bytes())),
+            move |view, result, _, cx| match result {
+                Ok(tag) => {
+                    view.hmac_output = format!(
+                        "HMAC with key 0x{:04x} ({} bytes):\n{}",
+                        key_id,
+                        tag.len(),
+                        hex::encode(&tag)
+                    )
+                    .into();
+                    view.hmac_tag = Some(tag);
+                }
+                Err(e) => {
+                    view.hmac_tag = None;
+                    view.hmac_output = format!("Error: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.hmac_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    /// Check the tag field (or the last computed tag) against the data field.
+    fn verify_hmac(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let key_id = match self.hmac_key_id(cx) {
+            Ok(key_id) => key_id,
+            Err(message) => {
+                self.hmac_output = message.into();
+                cx.notify();
+                return;
+            }
+        };
+        let data = self.hmac_data_input.read(cx).content();
+
+        let tag_input = self.hmac_tag_input.read(cx).content();
+        let tag_input = tag_input.trim();
+        let tag = if tag_input.is_empty() {
+            self.hmac_tag.clone()
+        } else {
+            match hex::decode(tag_input) {
+                Ok(tag) => Some(tag),
+                Err(e) => {
+                    self.hmac_output = format!("The tag is not valid hex: {}", e).into();
+                    cx.notify();
+                    return;
+                }
+            }
+        };
+        let Some(tag) = tag else {
+            self.hmac_output = "Enter a tag as hex, or compute one first.".into();
+            cx.notify();
+            return;
+        };
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                client.with_session_retry(|c| hsm::hmac_verify(c, key_id, data.as_bytes(), &tag))
+            },
+            move |view, result, _, cx| match result {
+                Ok(true) => {
+                    view.hmac_output =
+                        format!("✓ The tag is valid for this data and key 0x{:04x}.", key_id)
+                            .into();
+                }
+                Ok(false) => {
+                    view.hmac_output = format!(
+                        "✗ The tag does not match this data under key 0x{:04x}.",
+                        key_id
+                    )
+                    .into();
+                }
+                Err(e) => {
+                    view.hmac_output = format!("Error: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.hmac_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    pub fn render_hmac_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let field = |caption: &'static str, input: AnyElement| {
+            div()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(div().text_sm().text_color(rgb(0xcccccc)).child(caption))
+                .child(
+                    div()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .p_2()
+                        .min_h(px(24.))
+                        .child(input),
+                )
+        };
+        let button = |label: &'static str, bg: u32, hover_bg: u32| {
+            div()
+                .bg(if self.hsm_busy {
+                    rgb(0x555555)
+                } else {
+                    rgb(bg)
+                })
+                .when(!self.hsm_busy, |el| {
+                    el.hover(move |style| style.bg(rgb(hover_bg)))
+                        .cursor_pointer()
+                })
+                .rounded_md()
+                .px_4()
+                .py_2()
+                .text_color(rgb(0xffffff))
+                .child(if self.hsm_busy { "Working…" } else { label })
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("HMAC"),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(
+                "Compute and check HMAC tags with a symmetric key that never leaves the YubiHSM2.",
+            ))
+            .child(
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .mr_1()
+                            .child("HMAC key:"),
+                    )
+                    .when(self.hmac_keys.is_empty(), |el| {
+                        el.child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0x888888))
+                                .child("none listed"),
+                        )
+                    })
+                    .children(self.hmac_keys.iter().map(|key| {
+                        let key_id = key.object_id;
+                        div()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .text_xs()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .bg(if self.selected_hmac_key == Some(key_id) {
+                                rgb(0x007acc)
+                            } else {
+                                rgb(0x2a2a2a)
+                            })
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .child(format!(
+                                "0x{:04x} {} ({:?})",
+                                key_id, key.label, key.algorithm
+                            ))
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |view, _, _, cx| {
+                                    view.selected_hmac_key = Some(key_id);
+                                    // The typed ID wins over the picker, so clear it
+                                    view.hmac_key_input.update(cx, |input, cx| {
+                                        input.set_content(String::new(), cx);
+                                    });
+                                    cx.notify();
+                                }),
+                            )
+                    }))
+                    .child(
+                        div()
+                            .ml_2()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .text_xs()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .bg(rgb(0x6c757d))
+                            .hover(|style| style.bg(rgb(0x5a6268)))
+                            .child("Refresh")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.refresh_signing_keys(window, cx);
+                                }),
+                            ),
+                    ),
+            )
+            .child(field(
+                "Key ID (optional, overrides the picker):",
+                self.hmac_key_input.clone().into_any_element(),
+            ))
+            .child(field(
+                "Data:",
+                self.hmac_data_input.clone().into_any_element(),
+            ))
+            .child(field(
+                "Tag to verify (hex; empty uses the last computed tag):",
+                self.hmac_tag_input.clone().into_any_element(),
+            ))
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .child(
+                        button("Compute HMAC", 0x007acc, 0x005a9e).when(!self.hsm_busy, |el| {
+                            el.on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.compute_hmac(window, cx);
+                                }),
+                            )
+                        }),
+                    )
+                    .child(
+                        button("Verify tag", 0x28a745, 0x1e7e34).when(!self.hsm_busy, |el| {
+                            el.on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.verify_hmac(window, cx);
+                                }),
+                            )
+                        }),
+                    ),
+            )
+            .child(
+                div()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .p_2()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(self.hmac_output.clone()),
+            )
+            .into_any()
+    }
+}
@@ -0,0 +1,303 @@
+use gpui::{
+    AnyElement, Context, MouseButton, ParentElement, Styled, Window, div, prelude::*, px, rgb,
+};
+use yubihsm::{Capability, asymmetric, object::Type};
+
+use crate::{
+    HsmApp,
+    hsm::{self, error::HsmError},
+    screens::{auth::safe_mode_badge, sign_verify::parse_key_id},
+};
+
+/// Algorithms offered by the key generation form, with the capabilities ticked
+/// by default when the algorithm is picked.
+pub(crate) const KEY_GEN_ALGORITHMS: &[(&str, asymmetric::Algorithm, Capability)] = &[
+    (
+        "EC P-256",
+        asymmetric::Algorithm::EcP256,
+        Capability::SIGN_ECDSA,
+    ),
+    (
+        "EC secp256k1",
+        asymmetric::Algorithm::EcK256,
+        Capability::SIGN_ECDSA,
+    ),
+    (
+        "Ed25519",
+        asymmetric::Algorithm::Ed25519,
+        Capability::SIGN_EDDSA,
+    ),
+    (
+        "RSA 2048",
+        asymmetric::Algorithm::Rsa2048,
+        Capability::SIGN_PKCS,
+    ),
+];
+
+/// Capabilities offered by the key generation form; all apply to asymmetric keys.
+pub(crate) const KEY_GEN_CAPABILITIES: &[Capability] = &[
+    Capability::SIGN_ECDSA,
+    Capability::SIGN_EDDSA,
+    Capability::SIGN_PKCS,
+    Capability::SIGN_PSS,
+    Capability::DECRYPT_PKCS,
+    Capability::DECRYPT_OAEP,
+    Capability::DERIVE_ECDH,
+    Capability::SIGN_ATTESTATION_CERTIFICATE,
+    Capability::EXPORTABLE_UNDER_WRAP,
+];
+
+impl HsmApp {
+    /// Generate a key from the form with the picked capabilities and domains; the
+    /// keys table is refreshed afterwards.
+    fn generate_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let label = self
+            .keygen_label_input
+            .read(cx)
+            .content()
+            .trim()
+            .to_string();
+        let id_input = self.keygen_id_input.read(cx).content().trim().to_string();
+
+        // 0 asks the device to pick a free ID
+        let key_id = if id_input.is_empty() || id_input.eq_ignore_ascii_case("auto") {
+            0
+        } else {
+            match parse_key_id(&id_input) {
+                Some(key_id) => key_id,
+                None => {
+                    self.keygen_output = format!(
+                        "'{}' is not a key ID; use hex with 0x, decimal, or \"auto\".",
+                        id_input
+                    )
+                    .into();
+                    cx.notify();
+                    return;
+                }
+            }
+        };
+
+        let domain = self.keygen_domains.read(cx).selected();
+        if domain.is_empty() {
+            self.keygen_output = "Pick at least one domain for the key.".into();
+            cx.notify();
+            return;
+        }
+
+        let capability = self.keygen_capabilities.read(cx).selected();
+        if capability.is_empty() {
+            self.keygen_output =
+                "Tick at least one capability; a key without any is unusable.".into();
+            cx.notify();
+            return;
+        }
+
+        let (name, algorithm, _) = KEY_GEN_ALGORITHMS[self.keygen_algorithm];
+        self.keygen_output = format!("Generating {} key…", name).into();
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                let key_id = client.with_session_retry(|c| {
+                    hsm::generate_asymmetric_key(c, key_id, &label, domain, capability, algorithm)
+                })?;
+                // Separate call so a retry can never generate a second key
+                let public_key = client.with_session_retry(|c| hsm::get_public_key(c, key_id));
+                Ok::<_, HsmError>((key_id, public_key))
+            },
+            move |view, result, window, cx| match result {
+                Ok((key_id, public_key)) => {
+                    view.session.forget_public_key(key_id, Type::AsymmetricKey);
+                    view.keygen_output = format!(
+                        "Generated {} key 0x{:04x}.\n\nPublic key (hex):\n{}",
+                        name,
+                        key_id,
+                        match public_key {
+                            Ok(public_key) => hex::encode(&public_key.bytes),
+                            Err(e) => format!("(could not read it back: {})", e),
+                        }
+                    )
+                    .into();
+                    view.reload_keys(
+                        Some(format!("Generated {} key 0x{:04x}.", name, key_id)),
+                        window,
+                        cx,
+                    );
+                }
+                Err(HsmError::ObjectExists { id, .. }) => {
+                    view.keygen_output = format!(
+                        "An asymmetric key already exists at 0x{:04x}. Enter a free ID, or \"auto\" to let the device choose.",
+                        id
+                    )
+                    .into();
+                }
+                Err(e) => {
+                    view.keygen_output = format!("Key generation failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.keygen_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    pub fn render_key_gen_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let can_generate = !self.destructive_disabled
+            && !self.hsm_busy
+            && self.session_allows(Capability::GENERATE_ASYMMETRIC_KEY)
+            && !self.keygen_capabilities.read(cx).selected().is_empty()
+            && !self.keygen_domains.read(cx).selected().is_empty();
+
+        let field = |caption: &'static str, input: AnyElement| {
+            div()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(div().text_sm().text_color(rgb(0xcccccc)).child(caption))
+                .child(
+                    div()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .p_2()
+                        .min_h(px(24.))
+                        .child(input),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Key generation"),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(
+                "Generate a signing key on the YubiHSM2. The private key never leaves the device; \
+                 the domains default to those of the auth key you logged in with.",
+            ))
+            .when(self.destructive_disabled, |el| el.child(safe_mode_badge()))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .child("Algorithm:"),
+                    )
+                    .children(KEY_GEN_ALGORITHMS.iter().enumerate().map(
+                        |(ix, (name, _, default_capabilities))| {
+                            let default_capabilities = *default_capabilities;
+                            div()
+                                .flex()
+                                .gap_2()
+                                .cursor_pointer()
+                                .text_sm()
+                                .text_color(rgb(0xcccccc))
+                                .child(if self.keygen_algorithm == ix {
+                                    "(o)"
+                                } else {
+                                    "( )"
+                                })
+                                .child(*name)
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(move |view, _, _, cx| {
+                                        view.keygen_algorithm = ix;
+                                        view.keygen_capabilities.update(cx, |picker, cx| {
+                                            picker.set_selected(default_capabilities, cx);
+                                        });
+                                        cx.notify();
+                                    }),
+                                )
+                        },
+                    )),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .child("Capabilities:"),
+                    )
+                    .child(self.keygen_capabilities.clone()),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(div().text_sm().text_color(rgb(0xcccccc)).child("Domains:"))
+                    .child(self.keygen_domains.clone()),
+            )
+            .child(field(
+                "Label:",
+                self.keygen_label_input.clone().into_any_element(),
+            ))
+            .child(field(
+                "Object ID (hex with 0x, decimal, or \"auto\"):",
+                self.keygen_id_input.clone().into_any_element(),
+            ))
+            .child(
+                div().flex().gap_2().child(
+                    div()
+                        .bg(if can_generate {
+                            rgb(0x28a745)
+                        } else {
+                            rgb(0x555555)
+                        })
+                        .when(can_generate, |el| {
+                            el.hover(|style| style.bg(rgb(0x1e7e34)))
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.generate_key(window, cx);
+                                    }),
+                                )
+                        })
+                        .rounded_md()
+                        .px_4()
+                        .py_2()
+                        .text_color(rgb(0xffffff))
+                        .child(if self.hsm_busy {
+                            "Working…"
+                        } else {
+                            "Generate key"
+                        }),
+                ),
+            )
+            .child(
+                div()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .p_2()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(self.keygen_output.clone()),
+            )
+            .into_any()
+    }
+}
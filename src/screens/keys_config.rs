@@ -1,32 +1,178 @@
 use gpui::{
-    AnyElement, App, AppContext, Context, Element, InteractiveElement, IntoElement, MouseButton,
-    ParentElement, Styled, Window, div, prelude::FluentBuilder, px, rgb,
+    Animation, AnimationExt, AnyElement, App, AppContext, ClipboardItem, Context, Div, Element,
+    Entity, InteractiveElement, IntoElement, MouseButton, ParentElement, Styled, Window, actions,
+    div, prelude::FluentBuilder, px, relative, rgb,
 };
-use gpui_component::table::{Column, Table, TableDelegate, TableEvent, TableState};
-use yubihsm::object::Type;
+use gpui_component::table::{Column, ColumnSort, Table, TableDelegate, TableEvent, TableState};
+use std::cmp::Ordering;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::time::Duration;
+use yubihsm::{
+    Capability, Domain, asymmetric,
+    object::{Filter, Type},
+    wrap,
+};
+
+use crate::{
+    HsmApp, RefreshSessionInfo,
+    config::{
+        ATTESTATION_FILE_PREFIX, BENCH_SIGN_ITERATIONS, CSV_FILE_PREFIX, DEFAULT_JWKS_FILE,
+        MAX_LABEL_BYTES, OBJECT_JSON_FILE_PREFIX, PUBLIC_KEY_FILE_PREFIX, REPORT_FILE_PREFIX,
+        SCRATCH_KEY_ID, SCRATCH_KEY_LABEL, SNAPSHOT_FILE_PREFIX, WRAPPED_FILE_PREFIX,
+    },
+    hsm::{
+        self,
+        error::{HsmError, HsmResult},
+    },
+    screens::sign_verify::parse_key_id,
+    ui::{CapabilityPicker, DomainPicker, Modal},
+};
+
+actions!(
+    keys_table,
+    [
+        SelectPrevKey,
+        SelectNextKey,
+        OpenKeyDetails,
+        DeleteSelectedKey
+    ]
+);
+
+/// Key context used to scope the keys table navigation bindings
+pub const KEY_CONTEXT: &str = "KeysTable";
+
+/// Shown when creating or importing a wrap key so the defaults are not a surprise
+const WRAP_KEY_EXPLANATION: &str = "The wrap key is an AES-256-CCM key with the capabilities \
+and in the domains picked below. Keys exported or imported under it may keep \
+these delegated capabilities: sign-ecdsa, sign-eddsa, sign-pkcs, sign-pss and \
+exportable-under-wrap. Anything else is stripped from wrapped objects.";
+
+/// Capabilities offered for wrap keys when creating or importing one
+const WRAP_KEY_CAPABILITIES: &[Capability] = &[
+    Capability::EXPORT_WRAPPED,
+    Capability::IMPORT_WRAPPED,
+    Capability::WRAP_DATA,
+    Capability::UNWRAP_DATA,
+    Capability::EXPORTABLE_UNDER_WRAP,
+];
+
+/// Capabilities offered by the Keys config filter, with their yubihsm-shell names
+const CAPABILITY_FILTERS: &[(&str, Capability)] = &[
+    ("sign-ecdsa", Capability::SIGN_ECDSA),
+    ("sign-eddsa", Capability::SIGN_EDDSA),
+    ("sign-pkcs", Capability::SIGN_PKCS),
+    ("sign-pss", Capability::SIGN_PSS),
+    ("derive-ecdh", Capability::DERIVE_ECDH),
+    ("export-wrapped", Capability::EXPORT_WRAPPED),
+    ("import-wrapped", Capability::IMPORT_WRAPPED),
+    ("exportable-under-wrap", Capability::EXPORTABLE_UNDER_WRAP),
+];
 
-use crate::{HsmApp, hsm};
+/// Object types offered by the Keys config type filter
+const TYPE_FILTERS: &[(&str, Type)] = &[
+    ("asymmetric", Type::AsymmetricKey),
+    ("HMAC", Type::HmacKey),
+    ("wrap", Type::WrapKey),
+    ("auth", Type::AuthenticationKey),
+];
 
 /// Table delegate for displaying HSM objects in the Keys config screen.
 pub struct KeysTableDelegate {
     rows: Vec<hsm::ObjectSummary>,
+    /// Indices into `rows` matching the search, in display order
+    visible: Vec<usize>,
+    /// Lowercase search text rows must contain; empty matches everything
+    query: String,
+    /// Column index and direction the view is sorted by; None keeps device order
+    sort: Option<(usize, ColumnSort)>,
     columns: Vec<Column>,
+    /// ID of the ephemeral scratch key, marked as such in the table
+    scratch_key: Option<u16>,
 }
 
 impl KeysTableDelegate {
-    pub fn new(rows: Vec<hsm::ObjectSummary>) -> Self {
+    pub fn new(rows: Vec<hsm::ObjectSummary>, scratch_key: Option<u16>) -> Self {
         Self {
+            visible: (0..rows.len()).collect(),
+            query: String::new(),
+            sort: None,
             rows,
+            scratch_key,
             columns: vec![
-                Column::new("id", "ID").width(80.),
-                Column::new("ty", "Type").width(110.),
-                Column::new("alg", "Algorithm").width(140.),
-                Column::new("label", "Label").width(200.),
-                Column::new("seq", "Seq").width(60.),
+                Column::new("id", "ID").width(80.).sortable(),
+                Column::new("ty", "Type").width(110.).sortable(),
+                Column::new("alg", "Algorithm").width(140.).sortable(),
+                Column::new("label", "Label").width(200.).sortable(),
+                Column::new("seq", "Seq").width(60.).sortable(),
+                Column::new("fp", "Fingerprint").width(150.),
                 Column::new("pk", "Public key (hex)").width(260.),
             ],
         }
     }
+
+    /// Show only rows whose label, hex ID, type or algorithm contains `query`
+    /// (case-insensitive). An empty query shows every row.
+    pub fn set_filter(&mut self, query: &str) {
+        self.query = query.trim().to_lowercase();
+        self.update_visible();
+    }
+
+    /// Recompute the shown rows from the search and sort; `rows` is never reordered.
+    fn update_visible(&mut self) {
+        let mut visible: Vec<usize> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| self.query.is_empty() || matches_search(row, &self.query))
+            .map(|(ix, _)| ix)
+            .collect();
+
+        if let Some((col_ix, sort)) = self.sort {
+            let key = self.columns[col_ix].key.clone();
+            visible.sort_by(|&a, &b| compare_rows(&self.rows[a], &self.rows[b], &key));
+            if sort == ColumnSort::Descending {
+                visible.reverse();
+            }
+        }
+
+        self.visible = visible;
+    }
+
+    /// Indices into the full row list of the rows currently shown.
+    pub fn visible_rows(&self) -> &[usize] {
+        &self.visible
+    }
+}
+
+/// Ascending order of two rows by the column `key`: IDs and sequence numbers
+/// compare numerically, labels case-insensitively, type and algorithm by name.
+fn compare_rows(a: &hsm::ObjectSummary, b: &hsm::ObjectSummary, key: &str) -> Ordering {
+    match key {
+        "id" => a.object_id.cmp(&b.object_id),
+        "ty" => format!("{:?}", a.object_type).cmp(&format!("{:?}", b.object_type)),
+        "alg" => format!("{:?}", a.algorithm).cmp(&format!("{:?}", b.algorithm)),
+        "label" => a
+            .label
+            .to_string()
+            .to_lowercase()
+            .cmp(&b.label.to_string().to_lowercase()),
+        "seq" => a.sequence.cmp(&b.sequence),
+        _ => Ordering::Equal,
+    }
+}
+
+/// Whether lowercase `query` appears in the object's label, ID, type or algorithm.
+fn matches_search(row: &hsm::ObjectSummary, query: &str) -> bool {
+    [
+        row.label.to_string(),
+        format!("0x{:04x}", row.object_id),
+        format!("{:?}", row.object_type),
+        format!("{:?}", row.algorithm),
+    ]
+    .iter()
+    .any(|field| field.to_lowercase().contains(query))
 }
 
 impl TableDelegate for KeysTableDelegate {
@@ -35,13 +181,47 @@ impl TableDelegate for KeysTableDelegate {
     }
 
     fn rows_count(&self, _: &App) -> usize {
-        self.rows.len()
+        self.visible.len()
     }
 
     fn column(&self, col_ix: usize, _: &App) -> &Column {
         &self.columns[col_ix]
     }
 
+    fn perform_sort(
+        &mut self,
+        col_ix: usize,
+        sort: ColumnSort,
+        window: &mut Window,
+        cx: &mut Context<TableState<Self>>,
+    ) {
+        let previous = self.visible.clone();
+        self.sort = match sort {
+            ColumnSort::Default => None,
+            sort => Some((col_ix, sort)),
+        };
+        self.update_visible();
+
+        // Move the highlight with the selected key so it still marks the row
+        // a delete would act on
+        cx.defer_in(window, move |table, _, cx| {
+            let Some(source) = table
+                .selected_row()
+                .and_then(|ix| previous.get(ix).copied())
+            else {
+                return;
+            };
+            let new_ix = table
+                .delegate()
+                .visible_rows()
+                .iter()
+                .position(|&ix| ix == source);
+            if let Some(new_ix) = new_ix {
+                table.set_selected_row(new_ix, cx);
+            }
+        });
+    }
+
     fn render_td(
         &mut self,
         row_ix: usize,
@@ -49,15 +229,23 @@ impl TableDelegate for KeysTableDelegate {
         _: &mut Window,
         _: &mut Context<TableState<Self>>,
     ) -> impl IntoElement {
-        let row = &self.rows[row_ix];
+        let row = &self.rows[self.visible[row_ix]];
         let col = &self.columns[col_ix];
+        let is_scratch =
+            row.object_type == Type::AsymmetricKey && self.scratch_key == Some(row.object_id);
 
         let text = match col.key.as_ref() {
             "id" => format!("0x{:04x}", row.object_id),
             "ty" => format!("{:?}", row.object_type),
             "alg" => format!("{:?}", row.algorithm),
+            "label" if is_scratch => format!("{:?} (ephemeral)", row.label),
             "label" => format!("{:?}", row.label),
             "seq" => format!("{}", row.sequence),
+            "fp" => row
+                .fingerprint_hex
+                .as_ref()
+                .map(|fp| fp[..fp.len().min(16)].to_string())
+                .unwrap_or_else(|| "-".to_string()),
             "pk" => row
                 .public_key_hex
                 .as_ref()
@@ -73,63 +261,612 @@ impl TableDelegate for KeysTableDelegate {
             _ => String::new(),
         };
 
-        div().text_color(rgb(0xffffff)).child(text)
+        div()
+            .text_color(if is_scratch {
+                rgb(0xffc107)
+            } else {
+                rgb(0xffffff)
+            })
+            .child(text)
+    }
+}
+
+/// Table delegate for the result of comparing the device against a snapshot.
+pub struct InventoryDiffDelegate {
+    rows: Vec<hsm::InventoryDiffEntry>,
+    columns: Vec<Column>,
+}
+
+impl InventoryDiffDelegate {
+    pub fn new(rows: Vec<hsm::InventoryDiffEntry>) -> Self {
+        Self {
+            rows,
+            columns: vec![
+                Column::new("change", "Change").width(90.),
+                Column::new("id", "ID").width(80.),
+                Column::new("ty", "Type").width(110.),
+                Column::new("label", "Label").width(180.),
+                Column::new("fields", "Differs in").width(160.),
+                Column::new("snap_fp", "Snapshot fingerprint").width(150.),
+                Column::new("dev_fp", "Device fingerprint").width(150.),
+            ],
+        }
+    }
+}
+
+impl TableDelegate for InventoryDiffDelegate {
+    fn columns_count(&self, _: &App) -> usize {
+        self.columns.len()
+    }
+
+    fn rows_count(&self, _: &App) -> usize {
+        self.rows.len()
+    }
+
+    fn column(&self, col_ix: usize, _: &App) -> &Column {
+        &self.columns[col_ix]
+    }
+
+    fn render_td(
+        &mut self,
+        row_ix: usize,
+        col_ix: usize,
+        _: &mut Window,
+        _: &mut Context<TableState<Self>>,
+    ) -> impl IntoElement {
+        let row = &self.rows[row_ix];
+        let fingerprint = |fp: &Option<String>| {
+            fp.as_ref()
+                .map(|fp| fp[..fp.len().min(16)].to_string())
+                .unwrap_or_else(|| "-".to_string())
+        };
+
+        let text = match self.columns[col_ix].key.as_ref() {
+            "change" => row.change.label().to_string(),
+            "id" => format!("0x{:04x}", row.object_id),
+            "ty" => row.object_type.clone(),
+            "label" => row.label.clone(),
+            "fields" if row.differences.is_empty() => "-".to_string(),
+            "fields" => row.differences.join(", "),
+            "snap_fp" => fingerprint(&row.snapshot_fingerprint),
+            "dev_fp" => fingerprint(&row.device_fingerprint),
+            _ => String::new(),
+        };
+
+        div()
+            .text_color(match row.change {
+                hsm::InventoryChange::Added => rgb(0x4caf50),
+                hsm::InventoryChange::Removed => rgb(0xff6b6b),
+                hsm::InventoryChange::Changed => rgb(0xffc107),
+            })
+            .child(text)
+    }
+}
+
+/// " on serial N (firmware X.Y.Z)" for report summaries, empty if unknown.
+fn device_suffix(device: Option<&hsm::DeviceSummary>) -> String {
+    device
+        .map(|device| {
+            format!(
+                " on serial {} (firmware {})",
+                device.serial, device.firmware
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Toolbar button; disabled buttons are greyed out and the caller skips the handler.
+/// How often the progress bar is redrawn while a listing runs
+const LISTING_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Frames of the spinner shown while a device operation runs
+const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Objects fetched so far by a background listing. The listing thread writes
+/// it as each object's details arrive; the UI reads it while redrawing.
+#[derive(Default)]
+pub(crate) struct ListingProgress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl ListingProgress {
+    fn set(&self, done: usize, total: usize) {
+        self.total.store(total, AtomicOrdering::Relaxed);
+        self.done.store(done, AtomicOrdering::Relaxed);
     }
+
+    fn get(&self) -> (usize, usize) {
+        (
+            self.done.load(AtomicOrdering::Relaxed),
+            self.total.load(AtomicOrdering::Relaxed),
+        )
+    }
+}
+
+/// A spinner that keeps turning for as long as it is rendered.
+fn spinner() -> impl IntoElement {
+    div().with_animation(
+        "spinner",
+        Animation::new(Duration::from_millis(800)).repeat(),
+        |el, delta| {
+            let frame = (delta * SPINNER_FRAMES.len() as f32) as usize;
+            el.child(SPINNER_FRAMES[frame.min(SPINNER_FRAMES.len() - 1)])
+        },
+    )
+}
+
+fn toolbar_button(label: &'static str, enabled: bool, bg: u32, hover_bg: u32) -> Div {
+    div()
+        .bg(if enabled { rgb(bg) } else { rgb(0x555555) })
+        .when(enabled, |el| el.hover(move |style| style.bg(rgb(hover_bg))))
+        .rounded_md()
+        .px_4()
+        .py_2()
+        .text_color(rgb(0xffffff))
+        .cursor(if enabled {
+            gpui::CursorStyle::PointingHand
+        } else {
+            gpui::CursorStyle::Arrow
+        })
+        .child(label)
 }
 
 impl HsmApp {
-    fn load_keys_from_hsm(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
-        self.selected_key_row = None;
+    /// Progress (done, total) to show under the status text, and whether the
+    /// listing is still running.
+    fn shown_progress(&self) -> Option<(usize, usize, bool)> {
+        match &self.keys_listing {
+            Some(progress) => {
+                let (done, total) = progress.get();
+                Some((done, total, true))
+            }
+            None => self.keys_progress.map(|(done, total)| (done, total, false)),
+        }
+    }
 
-        match self.session.active_client() {
-            Ok(client) => match hsm::list_object_summaries(client) {
-                Ok(rows) => {
-                    let count = rows.len();
-                    self.keys_data = rows.clone();
-                    let state = cx.new(|cx| {
-                        TableState::new(KeysTableDelegate::new(rows), window, cx)
-                            .row_selectable(true)
-                    });
+    /// Whether the selected key may be deleted: not an auth key, not in safe mode,
+    /// and not ruled out by the auth key's capabilities.
+    fn can_delete_selected_key(&self) -> bool {
+        !self.destructive_disabled
+            && self
+                .selected_key_row
+                .and_then(|ix| self.keys_data.get(ix))
+                .is_some_and(|k| {
+                    k.object_type != Type::AuthenticationKey
+                        && self
+                            .session
+                            .info()
+                            .is_none_or(|info| info.can_delete(k.object_type))
+                })
+    }
 
-                    // Subscribe to table events for row selection
-                    cx.subscribe_in(&state, window, |view, _table, event, _window, cx| {
-                        if let TableEvent::SelectRow(row_ix) = event {
-                            view.selected_key_row = Some(*row_ix);
-                            cx.notify();
-                        }
-                    })
-                    .detach();
+    /// Indices into `keys_data` of the rows the table shows after the search.
+    fn visible_key_rows(&self, cx: &App) -> Vec<usize> {
+        self.keys_table
+            .as_ref()
+            .map(|state| state.read(cx).delegate().visible_rows().to_vec())
+            .unwrap_or_default()
+    }
 
-                    self.keys_table = Some(state);
-                    self.keys_output = format!(
-                        "Found {} object(s) visible to the current authentication key.\nClick a row to select, then use Delete button (auth keys cannot be deleted).",
-                        count
-                    )
-                    .into();
-                }
-                Err(e) => {
-                    self.keys_table = None;
-                    self.keys_data = Vec::new();
-                    self.keys_output =
-                        format!("Failed to list objects from YubiHSM2: {}", e).into();
-                }
-            },
-            Err(e) => {
-                self.keys_table = None;
-                self.keys_data = Vec::new();
+    /// Select table row `table_ix` in the widget. `selected_key_row` keeps the
+    /// `keys_data` index behind it, so deletion is unaffected by the search.
+    fn select_key_row(&mut self, table_ix: usize, cx: &mut Context<'_, Self>) {
+        let Some(state) = self.keys_table.clone() else {
+            return;
+        };
+        self.selected_key_row = self.visible_key_rows(cx).get(table_ix).copied();
+        state.update(cx, |table, cx| table.set_selected_row(table_ix, cx));
+        cx.notify();
+    }
+
+    /// Table row currently showing the selected key, if it passes the search.
+    fn selected_table_row(&self, visible: &[usize]) -> Option<usize> {
+        let selected = self.selected_key_row?;
+        visible.iter().position(|&ix| ix == selected)
+    }
+
+    fn select_prev_key(&mut self, _: &SelectPrevKey, _: &mut Window, cx: &mut Context<'_, Self>) {
+        let visible = self.visible_key_rows(cx);
+        if visible.is_empty() {
+            return;
+        }
+        let table_ix = self
+            .selected_table_row(&visible)
+            .map_or(0, |ix| ix.saturating_sub(1));
+        self.select_key_row(table_ix, cx);
+    }
+
+    fn select_next_key(&mut self, _: &SelectNextKey, _: &mut Window, cx: &mut Context<'_, Self>) {
+        let visible = self.visible_key_rows(cx);
+        let Some(last) = visible.len().checked_sub(1) else {
+            return;
+        };
+        let table_ix = self
+            .selected_table_row(&visible)
+            .map_or(0, |ix| (ix + 1).min(last));
+        self.select_key_row(table_ix, cx);
+    }
+
+    /// Show every field of the selected key, untruncated, in the status area.
+    fn open_key_details(&mut self, _: &OpenKeyDetails, _: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(key) = self.selected_key_row.and_then(|ix| self.keys_data.get(ix)) else {
+            return;
+        };
+
+        self.keys_output = format!(
+            "Object 0x{:04x}\n  Type: {:?}\n  Algorithm: {:?}\n  Label: {:?}\n  Sequence: {}\n  Capabilities: {}\n  Delegated capabilities: {}\n  Domains: {}\n  Origin: {}\n  SHA-256 fingerprint: {}\n  Public key: {}",
+            key.object_id,
+            key.object_type,
+            key.algorithm,
+            key.label,
+            key.sequence,
+            hsm::format_capabilities(key.capabilities),
+            hsm::format_capabilities(key.delegated_capabilities),
+            hsm::format_domains(key.domains),
+            hsm::format_origin(key.origin, key.capabilities),
+            key.fingerprint_hex.as_deref().unwrap_or("-"),
+            key.public_key_hex.as_deref().unwrap_or("-"),
+        )
+        .into();
+        cx.notify();
+    }
+
+    fn delete_selected_key_action(
+        &mut self,
+        _: &DeleteSelectedKey,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        if self.can_delete_selected_key() {
+            self.confirm_delete_selected_key(window, cx);
+        }
+    }
+
+    /// Returns true unless the cached session info says the auth key lacks `capability`.
+    /// Unknown session info does not block anything; the device has the final say.
+    pub(crate) fn session_allows(&self, capability: Capability) -> bool {
+        self.session.info().is_none_or(|info| info.can(capability))
+    }
+
+    /// Domains new objects default to: those of the auth key when known, else domain 1.
+    pub(crate) fn default_object_domains(&self) -> Domain {
+        self.session
+            .info()
+            .map(|info| info.domains)
+            .filter(|domains| !domains.is_empty())
+            .unwrap_or(Domain::DOM1)
+    }
+
+    /// Re-tick the default domains in the object creation forms, e.g. after logging in.
+    pub(crate) fn reset_domain_pickers(&mut self, cx: &mut Context<'_, Self>) {
+        let domains = self.default_object_domains();
+        for picker in [&self.keygen_domains, &self.opaque_domains] {
+            picker.update(cx, |picker, cx| picker.set_selected(domains, cx));
+        }
+    }
+
+    /// Re-fetch the auth key's capabilities so UI gating reflects out-of-band changes.
+    pub(crate) fn refresh_session_info(
+        &mut self,
+        _: &RefreshSessionInfo,
+        _window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        match self.session.refresh_info() {
+            Ok(info) => {
                 self.keys_output = format!(
-                    "Failed to use YubiHSM2 session: {}\n\nGo to the Auth screen and authenticate first.",
-                    e
+                    "Refreshed session info for auth key 0x{:04x}.",
+                    info.auth_key_id
                 )
                 .into();
             }
+            Err(e) => {
+                self.keys_output = format!("Failed to refresh session info: {}", e).into();
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Name of the active capability filter, as listed in `CAPABILITY_FILTERS`.
+    fn capability_filter_label(&self) -> Option<&'static str> {
+        let filter = self.keys_capability_filter?;
+        CAPABILITY_FILTERS
+            .iter()
+            .find(|(_, capability)| *capability == filter)
+            .map(|(label, _)| *label)
+    }
+
+    /// "N object(s)", "N of M objects" while searching, or "N of M object(s)
+    /// with <capability>" for the current view.
+    fn keys_count_summary(&self, cx: &App) -> String {
+        let shown = match &self.keys_table {
+            Some(_) => self.visible_key_rows(cx).len(),
+            None => self.keys_data.len(),
+        };
+        let total = self.keys_all.len();
+        match self.capability_filter_label() {
+            Some(label) => format!("{} of {} object(s) with {}", shown, total, label),
+            None if shown < total => format!("{} of {} objects", shown, total),
+            None => format!("{} object(s)", shown),
+        }
+    }
+
+    /// Re-apply the search box to the keys table as the user types. The full
+    /// listing stays in `keys_data`; only the delegate's view changes.
+    pub(crate) fn apply_keys_search(&mut self, cx: &mut Context<'_, Self>) {
+        let query = self.keys_search_input.read(cx).content();
+        // The input also notifies on focus and cursor moves
+        if query == self.keys_search_query {
+            return;
+        }
+        self.keys_search_query = query.clone();
+
+        let Some(state) = self.keys_table.clone() else {
+            return;
+        };
+        let selected = self.selected_key_row;
+        let table_ix = state.update(cx, |table, cx| {
+            table.delegate_mut().set_filter(&query);
+            table.refresh(cx);
+            let table_ix = selected.and_then(|selected| {
+                table
+                    .delegate()
+                    .visible_rows()
+                    .iter()
+                    .position(|&ix| ix == selected)
+            });
+            match table_ix {
+                Some(ix) => table.set_selected_row(ix, cx),
+                None => table.clear_selection(cx),
+            }
+            table_ix
+        });
+        if table_ix.is_none() {
+            self.selected_key_row = None;
+        }
+
+        self.keys_output = format!("Showing {}.", self.keys_count_summary(cx)).into();
+        cx.notify();
+    }
+
+    /// Rebuild the table from the cached listing, keeping only objects that match
+    /// the capability filter. No device calls.
+    fn show_filtered_keys(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        self.selected_key_row = None;
+        self.pending_delete = None;
+        self.keys_data = self
+            .keys_all
+            .iter()
+            .filter(|k| {
+                self.keys_capability_filter
+                    .is_none_or(|filter| k.capabilities.contains(filter))
+            })
+            .cloned()
+            .collect();
+
+        let rows = self.keys_data.clone();
+        let query = self.keys_search_input.read(cx).content();
+        let state = cx.new(|cx| {
+            let mut delegate = KeysTableDelegate::new(rows, self.scratch_key);
+            delegate.set_filter(&query);
+            TableState::new(delegate, window, cx).row_selectable(true)
+        });
+        self.keys_search_query = query;
+
+        // Subscribe to table events for row selection, mapped back to `keys_data`
+        cx.subscribe_in(&state, window, |view, table, event, _window, cx| {
+            if let TableEvent::SelectRow(row_ix) = event {
+                view.selected_key_row = table
+                    .read(cx)
+                    .delegate()
+                    .visible_rows()
+                    .get(*row_ix)
+                    .copied();
+                cx.notify();
+            }
+        })
+        .detach();
+
+        self.keys_table = Some(state);
+    }
+
+    /// Change the capability filter and re-filter the cached listing.
+    fn set_capability_filter(
+        &mut self,
+        filter: Option<Capability>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        self.keys_capability_filter = filter;
+        if self.keys_table.is_some() {
+            self.show_filtered_keys(window, cx);
+            self.keys_output = format!("Showing {}.", self.keys_count_summary(cx)).into();
+        }
+        cx.notify();
+    }
+
+    /// List only objects of `object_type` (all when `None`). The device does the
+    /// filtering, so this lists again instead of hiding rows.
+    fn set_type_filter(
+        &mut self,
+        object_type: Option<Type>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        if self.keys_type_filter == object_type {
+            return;
+        }
+        self.keys_type_filter = object_type;
+        if self.keys_table.is_some() {
+            self.load_keys_from_hsm(window, cx);
+        }
+        cx.notify();
+    }
+
+    /// Show `progress` while the listing it belongs to runs, redrawing a few
+    /// times a second so the bar moves. Stops once no device task is running,
+    /// including when the session went away and the listing result was dropped.
+    fn watch_listing_progress(
+        &mut self,
+        progress: Arc<ListingProgress>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        self.keys_listing = Some(progress);
+        cx.spawn_in(window, async move |this, cx| {
+            loop {
+                cx.background_executor()
+                    .timer(LISTING_PROGRESS_INTERVAL)
+                    .await;
+                let running = this.update(cx, |app, cx| {
+                    if !app.hsm_busy {
+                        app.keys_listing = None;
+                    }
+                    cx.notify();
+                    app.keys_listing.is_some()
+                });
+                if !matches!(running, Ok(true)) {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+
+    pub(crate) fn load_keys_from_hsm(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        self.reload_keys(None, window, cx);
+    }
+
+    /// Re-list the keys in the background. `notice` replaces the usual "Found …"
+    /// summary once the listing succeeds, so the outcome of the operation that
+    /// triggered the refresh stays visible.
+    pub(crate) fn reload_keys(
+        &mut self,
+        notice: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        self.selected_key_row = None;
+        self.pending_delete = None;
+        self.inventory_diff = None;
+
+        let type_filter = self.keys_type_filter;
+        let filter: Vec<Filter> = type_filter.map(Filter::Type).into_iter().collect();
+
+        let cache = self.session.public_key_cache();
+        let progress = Arc::new(ListingProgress::default());
+        let task_progress = progress.clone();
+        let already_busy = self.hsm_busy;
+
+        // One info and one public key round trip per object, so list in the background
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                client.with_session_retry(|c| {
+                    hsm::list_object_summaries_with_progress(c, &filter, &cache, |done, total| {
+                        task_progress.set(done, total);
+                    })
+                })
+            },
+            move |view, listing, window, cx| {
+                if let Some(progress) = view.keys_listing.take() {
+                    view.keys_progress = Some(progress.get());
+                }
+                match listing {
+                    Ok(hsm::Listing {
+                        objects: rows,
+                        cached_public_keys,
+                    }) => {
+                        // A listing filtered to another type says nothing about these keys
+                        if type_filter.is_none_or(|ty| ty == Type::AsymmetricKey) {
+                            view.set_signing_keys(&rows);
+                        }
+                        if type_filter.is_none_or(|ty| ty == Type::HmacKey) {
+                            view.set_hmac_keys(&rows);
+                        }
+                        view.keys_all = rows;
+                        // Whatever triggered the reload may have used or freed storage
+                        view.refresh_storage_info(cx);
+                        view.show_filtered_keys(window, cx);
+                        // Ready for arrow-key navigation straight away
+                        window.focus(&view.keys_focus);
+                        view.keys_output = notice
+                            .unwrap_or_else(|| {
+                                format!(
+                                    "Found {} visible to the current authentication key ({} public key(s) from cache).\nClick a row to select, then use Delete button (auth keys cannot be deleted).",
+                                    view.keys_count_summary(cx),
+                                    cached_public_keys
+                                )
+                            })
+                            .into();
+                    }
+                    Err(e) => {
+                        view.keys_table = None;
+                        view.keys_all = Vec::new();
+                        view.keys_data = Vec::new();
+                        view.keys_output =
+                            format!("Failed to list objects from YubiHSM2: {}", e).into();
+                        view.handle_device_disconnected(&e, cx);
+                    }
+                }
+            },
+        );
+
+        if spawned.is_ok() && !already_busy {
+            self.watch_listing_progress(progress, window, cx);
+        }
+        if let Err(e) = spawned {
+            self.keys_table = None;
+            self.keys_all = Vec::new();
+            self.keys_data = Vec::new();
+            self.keys_output = format!(
+                "Failed to use YubiHSM2 session: {}\n\nGo to the Auth screen and authenticate first.",
+                e
+            )
+            .into();
         }
 
         cx.notify();
     }
 
-    fn delete_selected_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
-        let Some(row_ix) = self.selected_key_row else {
+    /// Ask for confirmation before deleting the selected key.
+    fn confirm_delete_selected_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(key) = self
+            .selected_key_row
+            .and_then(|row_ix| self.keys_data.get(row_ix))
+        else {
+            self.keys_output = "No key selected for deletion.".into();
+            cx.notify();
+            return;
+        };
+
+        let message = format!(
+            "Permanently delete object 0x{:04x} ({:?}, label {:?}) from the YubiHSM2?\n\n\
+             Deletion is irreversible: the key material is erased on the device and cannot be \
+             recovered unless a wrapped backup exists.",
+            key.object_id, key.object_type, key.label
+        );
+        // Pin the row now so a selection change behind the dialog cannot
+        // redirect the delete to another object
+        self.pending_delete = self.selected_key_row;
+        let modal = cx.new(|cx| {
+            Modal::new(cx, "Delete object", message)
+                .confirm_label("Confirm delete")
+                .destructive(true)
+        });
+
+        self.open_modal(modal, window, cx, |view, _, window, cx| {
+            view.delete_pending_key(window, cx);
+        });
+    }
+
+    /// Delete the object the confirmation dialog was opened for.
+    fn delete_pending_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(row_ix) = self.pending_delete.take() else {
             self.keys_output = "No key selected for deletion.".into();
             cx.notify();
             return;
@@ -152,21 +889,39 @@ impl HsmApp {
         let object_type = key.object_type;
 
         match self.session.active_client() {
-            Ok(client) => match hsm::delete_object(client, object_id, object_type) {
-                Ok(()) => {
-                    self.keys_output = format!(
-                        "Successfully deleted object 0x{:04x} ({:?}).",
-                        object_id, object_type
-                    )
-                    .into();
-                    // Refresh the list
-                    self.load_keys_from_hsm(window, cx);
-                }
-                Err(e) => {
-                    self.keys_output = format!("Failed to delete object: {}", e).into();
-                    cx.notify();
+            Ok(client) => {
+                match self
+                    .check_active_domain(client, object_id, object_type)
+                    .and_then(|()| {
+                        client.with_session_retry(|c| hsm::delete_object(c, object_id, object_type))
+                    }) {
+                    Ok(()) => {
+                        self.session.forget_public_key(object_id, object_type);
+                        if object_type == Type::AsymmetricKey && self.scratch_key == Some(object_id)
+                        {
+                            self.scratch_key = None;
+                        }
+                        self.keys_output = format!(
+                            "Successfully deleted object 0x{:04x} ({:?}).",
+                            object_id, object_type
+                        )
+                        .into();
+                        // Refresh the list
+                        self.load_keys_from_hsm(window, cx);
+                    }
+                    Err(HsmError::ObjectNotFound { id, ty, .. }) => {
+                        // Someone else deleted it first; show what is left
+                        self.keys_output =
+                            format!("Object 0x{:04x} ({:?}) is already gone.", id, ty).into();
+                        self.load_keys_from_hsm(window, cx);
+                    }
+                    Err(e) => {
+                        self.keys_output = format!("Failed to delete object: {}", e).into();
+                        self.handle_device_disconnected(&e, cx);
+                        cx.notify();
+                    }
                 }
-            },
+            }
             Err(e) => {
                 self.keys_output = format!("Failed to access HSM session: {}", e).into();
                 cx.notify();
@@ -174,81 +929,1552 @@ impl HsmApp {
         }
     }
 
-    pub fn render_keys_config_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
-        div()
-            .flex()
-            .flex_col()
-            .bg(rgb(0x2e2e2e))
-            .size_full()
-            .p_4()
-            .gap_4()
-            .child(
-                div()
-                    .flex()
-                    .justify_center()
-                    .text_2xl()
-                    .text_color(rgb(0xffffff))
-                    .child("Keys config"),
-            )
-            .child(div().text_xs().text_color(rgb(0x888888)).child(
-                "List objects/keys that are visible to the current YubiHSM authentication key.",
+    /// Picker over the wrap key capabilities with the defaults ticked.
+    fn wrap_key_capability_picker(cx: &mut Context<'_, Self>) -> Entity<CapabilityPicker> {
+        cx.new(|_| CapabilityPicker::new(WRAP_KEY_CAPABILITIES, hsm::default_wrap_capabilities()))
+    }
+
+    /// Picker with the default domains for new objects ticked.
+    fn domain_picker(&self, cx: &mut Context<'_, Self>) -> Entity<DomainPicker> {
+        let domains = self.default_object_domains();
+        cx.new(|_| DomainPicker::new(domains))
+    }
+
+    /// Ask for a label, capabilities and domains, then generate an AES-256 wrap key.
+    fn prompt_create_wrap_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let picker = Self::wrap_key_capability_picker(cx);
+        let domains = self.domain_picker(cx);
+        let modal = cx.new(|cx| {
+            Modal::new(cx, "Create wrap key", WRAP_KEY_EXPLANATION)
+                .with_input(cx, "Label (e.g. \"demo wrap key\")")
+                .max_input_bytes(MAX_LABEL_BYTES)
+                .with_capabilities(cx, picker.clone())
+                .with_domains(cx, domains.clone())
+                .confirm_label("Generate")
+        });
+
+        self.open_modal(modal, window, cx, move |view, label, window, cx| {
+            let label = label.unwrap_or_default();
+            let capabilities = picker.read(cx).selected();
+            let domains = domains.read(cx).selected();
+            let result = view.session.with_reconnect(|c| {
+                hsm::generate_wrap_key(
+                    c,
+                    0,
+                    label.trim(),
+                    domains,
+                    capabilities,
+                    hsm::default_wrap_delegated_capabilities(),
+                    wrap::Algorithm::Aes256Ccm,
+                )
+            });
+            view.finish_wrap_key_change(result, "Generated", window, cx);
+        });
+    }
+
+    /// Ask for hex key material, capabilities and domains, then import it as an
+    /// AES-256 wrap key.
+    fn prompt_import_wrap_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let picker = Self::wrap_key_capability_picker(cx);
+        let domains = self.domain_picker(cx);
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Import wrap key",
+                format!(
+                    "Paste the 32-byte AES-256 key as 64 hex characters.\n\n{}",
+                    WRAP_KEY_EXPLANATION
+                ),
+            )
+            .with_input(cx, "Key material (hex)")
+            .with_capabilities(cx, picker.clone())
+            .with_domains(cx, domains.clone())
+            .confirm_label("Import")
+        });
+
+        self.open_modal(modal, window, cx, move |view, key_hex, window, cx| {
+            let capabilities = picker.read(cx).selected();
+            let domains = domains.read(cx).selected();
+            let result = hex::decode(key_hex.unwrap_or_default().trim())
+                .map_err(|e| {
+                    HsmError::InvalidInput(format!("Key material is not valid hex: {}", e))
+                })
+                .and_then(|key_bytes| {
+                    let client = view.session.active_client()?;
+                    client.with_session_retry(|c| {
+                        hsm::put_wrap_key(
+                            c,
+                            0,
+                            "Imported wrap key",
+                            domains,
+                            capabilities,
+                            hsm::default_wrap_delegated_capabilities(),
+                            wrap::Algorithm::Aes256Ccm,
+                            &key_bytes,
+                        )
+                    })
+                });
+            view.finish_wrap_key_change(result, "Imported", window, cx);
+        });
+    }
+
+    /// Report the outcome of creating/importing a wrap key and refresh the list.
+    fn finish_wrap_key_change(
+        &mut self,
+        result: HsmResult<u16>,
+        verb: &str,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        match result {
+            Ok(key_id) => {
+                self.keys_output = format!(
+                    "{} wrap key 0x{:04x} (AES-256-CCM). It can now be used to export and import keys under wrap.",
+                    verb, key_id
+                )
+                .into();
+                self.reload_keys(Some(self.keys_output.to_string()), window, cx);
+            }
+            Err(e) => {
+                self.keys_output = format!("Wrap key operation failed: {}", e).into();
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Generate an ephemeral P-256 signing key at the reserved scratch ID. It becomes
+    /// the default signing key and is deleted again when the session ends.
+    pub(crate) fn create_scratch_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        self.generate_scratch_key(SCRATCH_KEY_ID, false, window, cx);
+    }
+
+    /// Generate the scratch key at `key_id` (0 lets the device choose), deleting
+    /// whatever asymmetric key holds that ID first if `replace` is set.
+    fn generate_scratch_key(
+        &mut self,
+        key_id: u16,
+        replace: bool,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let result = self.session.with_reconnect(|c| {
+            if replace {
+                hsm::delete_object(c, key_id, Type::AsymmetricKey)?;
+            }
+            hsm::generate_asymmetric_key(
+                c,
+                key_id,
+                SCRATCH_KEY_LABEL,
+                Domain::DOM1,
+                Capability::SIGN_ECDSA,
+                asymmetric::Algorithm::EcP256,
+            )
+        });
+
+        match result {
+            Ok(key_id) => {
+                self.session.forget_public_key(key_id, Type::AsymmetricKey);
+                self.scratch_key = Some(key_id);
+                self.keys_output = format!(
+                    "Generated ephemeral P-256 scratch key 0x{:04x}. It is used for signing when no key is entered and is deleted when you disconnect.",
+                    key_id
+                )
+                .into();
+                self.reload_keys(Some(self.keys_output.to_string()), window, cx);
+            }
+            Err(HsmError::ObjectExists { id, .. }) => {
+                self.prompt_scratch_key_conflict(id, window, cx);
+            }
+            Err(e) => {
+                self.keys_output = format!("Failed to create scratch key: {}", e).into();
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// The scratch key's ID is taken, often by a scratch key left behind when a
+    /// session ended without cleanup. Ask whether to replace it or use another ID.
+    fn prompt_scratch_key_conflict(
+        &mut self,
+        key_id: u16,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Key ID already in use",
+                format!(
+                    "An asymmetric key already exists at 0x{:04x}, possibly a scratch key from an earlier session. \
+                     Leave the field empty to delete it and generate the scratch key in its place, \
+                     or enter another ID (0 lets the device choose).",
+                    key_id
+                ),
+            )
+            .with_input(cx, "New key ID (empty = replace existing key)")
+            .confirm_label("Continue")
+            .destructive(true)
+        });
+
+        self.open_modal(modal, window, cx, move |view, new_id, window, cx| {
+            let new_id = new_id.unwrap_or_default().trim().to_string();
+            if new_id.is_empty() {
+                view.generate_scratch_key(key_id, true, window, cx);
+                return;
+            }
+
+            match parse_key_id(&new_id) {
+                Some(new_id) => view.generate_scratch_key(new_id, false, window, cx),
+                None => {
+                    view.keys_output =
+                        format!("'{}' is not a key ID; use hex with 0x or decimal.", new_id).into();
+                    cx.notify();
+                }
+            }
+        });
+    }
+
+    /// Delete the scratch key if one was created. Best effort: if the device is
+    /// already gone the key stays behind and is replaced by the next scratch key.
+    pub(crate) fn delete_scratch_key(&mut self) {
+        let Some(key_id) = self.scratch_key.take() else {
+            return;
+        };
+
+        let _ = self
+            .session
+            .with_reconnect(|c| hsm::delete_object(c, key_id, Type::AsymmetricKey));
+    }
+
+    /// Run the device self-test with the configured signing key.
+    pub(crate) fn run_self_test(&mut self, cx: &mut Context<'_, Self>) {
+        let result = self.session.active_client().and_then(|client| {
+            let key_id = self.resolve_signing_key(client, cx)?;
+            client.with_session_retry(|c| hsm::self_test(c, key_id, &hsm::DeviceRandom(c)))
+        });
+
+        match result {
+            Ok(report) => {
+                let mut summary = format!(
+                    "Self-test {}{}",
+                    if report.passed { "PASSED" } else { "FAILED" },
+                    device_suffix(report.device.as_ref())
+                );
+                for step in &report.steps {
+                    summary.push_str(&format!(
+                        "\n  {} {} ({:.1} ms): {}",
+                        if step.passed { "✓" } else { "✗" },
+                        step.name,
+                        step.duration_ms,
+                        step.detail
+                    ));
+                }
+                self.keys_output = summary.into();
+                self.last_report = Some(hsm::DiagnosticsReport::SelfTest(report));
+            }
+            Err(e) => {
+                self.keys_output = format!("Self-test could not run: {}", e).into();
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Time a batch of signatures with the configured signing key.
+    pub(crate) fn run_sign_benchmark(&mut self, cx: &mut Context<'_, Self>) {
+        let result = self.session.active_client().and_then(|client| {
+            let key_id = self.resolve_signing_key(client, cx)?;
+            client.with_session_retry(|c| hsm::bench_sign(c, key_id, BENCH_SIGN_ITERATIONS))
+        });
+
+        match result {
+            Ok(report) => {
+                self.keys_output = format!(
+                    "Benchmark {}{}\n  {} of {} signatures with key 0x{:04x} in {:.1} ms\n  mean {:.1} ms, min {:.1} ms, max {:.1} ms, {:.1} signatures/s{}",
+                    if report.passed { "completed" } else { "stopped early" },
+                    device_suffix(report.device.as_ref()),
+                    report.completed,
+                    report.iterations,
+                    report.key_id,
+                    report.total_ms,
+                    report.mean_ms,
+                    report.min_ms,
+                    report.max_ms,
+                    report.signs_per_sec,
+                    report
+                        .error
+                        .as_ref()
+                        .map(|e| format!("\n  Error: {}", e))
+                        .unwrap_or_default()
+                )
+                .into();
+                self.last_report = Some(hsm::DiagnosticsReport::Benchmark(report));
+            }
+            Err(e) => {
+                self.keys_output = format!("Benchmark could not run: {}", e).into();
+                self.handle_device_disconnected(&e, cx);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Ask for a file name, then write the last self-test/benchmark report as JSON.
+    pub(crate) fn export_last_report(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(report) = self.last_report.as_ref() else {
+            self.keys_output = "Run the self-test or benchmark first.".into();
+            cx.notify();
+            return;
+        };
+
+        let kind = match report {
+            hsm::DiagnosticsReport::SelfTest(_) => "self-test",
+            hsm::DiagnosticsReport::Benchmark(_) => "benchmark",
+        };
+        let default_path = format!(
+            "{}-{}-{}.json",
+            REPORT_FILE_PREFIX,
+            kind,
+            self.clock.now().format("%Y%m%dT%H%M%SZ")
+        );
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Export report",
+                "Writes the report as JSON: timestamps, device serial and firmware, timings and pass/fail. No key material is included.",
+            )
+            .with_input(cx, format!("File (default: {})", default_path))
+            .confirm_label("Export")
+        });
+
+        self.open_modal(modal, window, cx, move |view, path, _window, cx| {
+            let path = path
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| default_path.clone());
+            let Some(report) = view.last_report.as_ref() else {
+                return;
+            };
+
+            view.keys_output = match hsm::write_report_json(report, Path::new(&path)) {
+                Ok(()) => format!("Report written to '{}'.", path).into(),
+                Err(e) => format!("Report export failed: {}", e).into(),
+            };
+            cx.notify();
+        });
+    }
+
+    /// Chips selecting the capability filter, followed by the active filter and count.
+    fn render_type_filter(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let chip = |label: &'static str, value: Option<Type>, cx: &mut Context<'_, Self>| {
+            let is_active = self.keys_type_filter == value;
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(rgb(0xffffff))
+                .bg(if is_active {
+                    rgb(0x007acc)
+                } else {
+                    rgb(0x2a2a2a)
+                })
+                .hover(|style| style.bg(rgb(0x404040)))
+                .child(label)
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |view, _, window, cx| {
+                        view.set_type_filter(value, window, cx);
+                    }),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_wrap()
+            .items_center()
+            .gap_1()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x888888))
+                    .mr_1()
+                    .child("Type:"),
+            )
+            .child(chip("all", None, cx))
+            .children(
+                TYPE_FILTERS
+                    .iter()
+                    .map(|(label, object_type)| chip(*label, Some(*object_type), cx)),
+            )
+    }
+
+    fn render_capability_filter(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let chip = |label: &'static str, value: Option<Capability>, cx: &mut Context<'_, Self>| {
+            let is_active = self.keys_capability_filter == value;
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(rgb(0xffffff))
+                .bg(if is_active {
+                    rgb(0x007acc)
+                } else {
+                    rgb(0x2a2a2a)
+                })
+                .hover(|style| style.bg(rgb(0x404040)))
+                .child(label)
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |view, _, window, cx| {
+                        view.set_capability_filter(value, window, cx);
+                    }),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_wrap()
+            .items_center()
+            .gap_1()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x888888))
+                    .mr_1()
+                    .child("Capability:"),
+            )
+            .child(chip("all", None, cx))
+            .children(
+                CAPABILITY_FILTERS
+                    .iter()
+                    .map(|(label, capability)| chip(*label, Some(*capability), cx)),
+            )
+            .when(self.keys_table.is_some(), |el| {
+                el.child(
+                    div()
+                        .ml_2()
+                        .text_xs()
+                        .text_color(rgb(0x888888))
+                        .child(self.keys_count_summary(cx)),
+                )
+            })
+    }
+
+    /// Capabilities, domains and origin of the selected object, decoded for review.
+    fn render_key_detail_panel(&self, cx: &mut Context<'_, Self>) -> Option<Div> {
+        let key = self
+            .selected_key_row
+            .and_then(|ix| self.keys_data.get(ix))?;
+        let row = |name: &'static str, value: String| {
+            div()
+                .flex()
+                .gap_2()
+                .child(
+                    div()
+                        .w(px(170.))
+                        .flex_none()
+                        .text_color(rgb(0x888888))
+                        .child(name),
+                )
+                .child(div().flex_1().text_color(rgb(0xffffff)).child(value))
+        };
+
+        Some(
+            div()
+                .flex()
+                .flex_col()
+                .gap_1()
+                .p_2()
+                .bg(rgb(0x1e1e1e))
+                .border_1()
+                .border_color(rgb(0x444444))
+                .rounded_md()
+                .text_xs()
+                .child(row(
+                    "Object",
+                    format!(
+                        "0x{:04x} {:?} {:?}",
+                        key.object_id, key.object_type, key.label
+                    ),
+                ))
+                .child(row(
+                    "Capabilities",
+                    hsm::format_capabilities(key.capabilities),
+                ))
+                .child(row(
+                    "Delegated capabilities",
+                    hsm::format_capabilities(key.delegated_capabilities),
+                ))
+                .child(row("Domains", hsm::format_domains(key.domains)))
+                .child(
+                    row("Origin", hsm::format_origin(key.origin, key.capabilities)).when(
+                        hsm::is_imported(key.origin),
+                        // Imported keys may have existed outside the device before
+                        |el| {
+                            el.child(
+                                div()
+                                    .flex_none()
+                                    .px_2()
+                                    .rounded_md()
+                                    .bg(rgb(0xffc107))
+                                    .text_color(rgb(0x000000))
+                                    .child("Not generated on-device"),
+                            )
+                        },
+                    ),
+                )
+                .when_some(key.public_key_hex.clone(), |el, public_key_hex| {
+                    el.child(
+                        row("Public key", public_key_hex).child(
+                            div()
+                                .flex_none()
+                                .px_2()
+                                .rounded_md()
+                                .bg(rgb(0x6c757d))
+                                .hover(|style| style.bg(rgb(0x5a6268)))
+                                .cursor_pointer()
+                                .text_color(rgb(0xffffff))
+                                .child("Copy")
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, _, cx| {
+                                        view.copy_selected_public_key(cx);
+                                    }),
+                                ),
+                        ),
+                    )
+                }),
+        )
+    }
+
+    /// Ask for a file name, then list the device and save the listing as a snapshot.
+    fn prompt_save_snapshot(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let default_path = format!(
+            "{}-{}.json",
+            SNAPSHOT_FILE_PREFIX,
+            self.clock.now().format("%Y%m%dT%H%M%SZ")
+        );
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Save inventory snapshot",
+                "Lists every object visible to this session and writes IDs, types, labels, capabilities and public key fingerprints as JSON.",
+            )
+            .with_input(cx, format!("File (default: {})", default_path))
+            .confirm_label("Save")
+        });
+
+        self.open_modal(modal, window, cx, move |view, path, _window, cx| {
+            let path = path
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| default_path.clone());
+
+            let result = view.session.active_client().and_then(|client| {
+                let snapshot = client.with_session_retry(hsm::take_snapshot)?;
+                hsm::write_snapshot(&snapshot, Path::new(&path))?;
+                Ok(snapshot.objects.len())
+            });
+
+            match result {
+                Ok(count) => {
+                    view.keys_output =
+                        format!("Saved a snapshot of {} object(s) to '{}'.", count, path).into();
+                }
+                Err(e) => {
+                    view.keys_output = format!("Snapshot failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            }
+            cx.notify();
+        });
+    }
+
+    /// Pick a file, then write the listed objects there as CSV, in the order
+    /// and with the search the table currently shows.
+    fn prompt_export_csv(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let rows = match &self.keys_table {
+            Some(_) => self.visible_key_rows(cx),
+            None => (0..self.keys_data.len()).collect(),
+        };
+        let suggested_name = format!(
+            "{}-{}.csv",
+            CSV_FILE_PREFIX,
+            self.clock.now().format("%Y%m%dT%H%M%SZ")
+        );
+
+        self.prompt_save_path(
+            &suggested_name,
+            window,
+            cx,
+            move |view, path, _window, _cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.keys_output = format!("Cannot open the save dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                let objects = rows.iter().filter_map(|&ix| view.keys_data.get(ix));
+                view.keys_output = match hsm::write_objects_csv(objects, &path) {
+                    Ok(count) => format!("Wrote {} row(s) to '{}'.", count, path.display()).into(),
+                    Err(e) => format!("CSV export failed: {}", e).into(),
+                };
+            },
+        );
+    }
+
+    /// Ask for a file name, then write the selected object's current metadata
+    /// there as JSON, fetched fresh from the device.
+    fn prompt_export_object_json(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(key) = self.selected_key_row.and_then(|ix| self.keys_data.get(ix)) else {
+            self.keys_output = "Select an object first.".into();
+            cx.notify();
+            return;
+        };
+
+        let (object_id, object_type) = (key.object_id, key.object_type);
+        let default_path = format!("{}-{:04x}.json", OBJECT_JSON_FILE_PREFIX, object_id);
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Export JSON",
+                format!(
+                    "Writes the metadata of 0x{:04x} ({:?}) as JSON: capabilities, domains, origin and, for asymmetric keys, the public key.",
+                    object_id, object_type
+                ),
+            )
+            .with_input(cx, format!("File (default: {})", default_path))
+            .confirm_label("Export")
+        });
+
+        self.open_modal(modal, window, cx, move |view, path, _window, cx| {
+            let path = path
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| default_path.clone());
+
+            let result = view.session.active_client().and_then(|client| {
+                let json = client
+                    .with_session_retry(|c| hsm::object_info_json(c, object_id, object_type))?;
+                hsm::export::write_file(Path::new(&path), json)
+            });
+
+            match result {
+                Ok(()) => {
+                    view.keys_output =
+                        format!("Metadata of 0x{:04x} written to '{}'.", object_id, path).into();
+                }
+                Err(e) => {
+                    view.keys_output = format!("JSON export failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            }
+            cx.notify();
+        });
+    }
+
+    /// Ask for a file name, then write every listed object there as a JSON
+    /// array. Uses the last listing, so it costs no device round trips.
+    fn prompt_export_all_json(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let default_path = format!(
+            "{}-{}.json",
+            CSV_FILE_PREFIX,
+            self.clock.now().format("%Y%m%dT%H%M%SZ")
+        );
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Export all as JSON",
+                format!(
+                    "Writes the metadata of all {} listed object(s) as a JSON array. List objects again first for the current state.",
+                    self.keys_data.len()
+                ),
+            )
+            .with_input(cx, format!("File (default: {})", default_path))
+            .confirm_label("Export")
+        });
+
+        self.open_modal(modal, window, cx, move |view, path, _window, cx| {
+            let path = path
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| default_path.clone());
+
+            view.keys_output = match hsm::write_objects_json(&view.keys_data, Path::new(&path)) {
+                Ok(count) => format!("Wrote {} object(s) to '{}'.", count, path).into(),
+                Err(e) => format!("JSON export failed: {}", e).into(),
+            };
+            cx.notify();
+        });
+    }
+
+    /// Ask for a file name, then write the device's signing keys there as a JWKS.
+    fn prompt_export_jwks(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Export JWKS",
+                "Writes every signing key (EC, Ed25519, RSA) as a JWK with kid set to its object ID in hex. \
+                 Keys on curves JOSE has no name for, such as P-224 and Brainpool, are skipped.",
+            )
+            .with_input(cx, format!("File (default: {})", DEFAULT_JWKS_FILE))
+            .confirm_label("Export")
+        });
+
+        self.open_modal(modal, window, cx, |view, path, _window, cx| {
+            let path = path
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .unwrap_or_else(|| DEFAULT_JWKS_FILE.to_string());
+
+            let result = view.session.active_client().and_then(|client| {
+                let export = client.with_session_retry(hsm::export_jwks)?;
+                hsm::write_jwks(&export, Path::new(&path))?;
+                Ok(export)
+            });
+
+            match result {
+                Ok(export) => {
+                    let mut summary =
+                        format!("Wrote {} key(s) to '{}' as a JWKS.", export.exported, path);
+                    for skipped in &export.skipped {
+                        summary.push_str(&format!(
+                            "\n  Skipped 0x{:04x}: {:?} has no JWK representation",
+                            skipped.key_id, skipped.algorithm
+                        ));
+                    }
+                    view.keys_output = summary.into();
+                }
+                Err(e) => {
+                    view.keys_output = format!("JWKS export failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            }
+            cx.notify();
+        });
+    }
+
+    /// Ask for a file, then write the selected asymmetric key's public key to it as PEM.
+    fn prompt_export_public_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(key) = self
+            .selected_key_row
+            .and_then(|ix| self.keys_data.get(ix))
+            .filter(|key| key.object_type == Type::AsymmetricKey)
+        else {
+            self.keys_output = "Select an asymmetric key first.".into();
+            cx.notify();
+            return;
+        };
+
+        let key_id = key.object_id;
+        let suggested_name = format!("{}-{:04x}.pem", PUBLIC_KEY_FILE_PREFIX, key_id);
+
+        // Written as a PEM SubjectPublicKeyInfo (BEGIN PUBLIC KEY), readable by
+        // openssl and most libraries
+        self.prompt_save_path(
+            &suggested_name,
+            window,
+            cx,
+            move |view, path, _window, cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.keys_output = format!("Cannot open the save dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                let result = view.session.active_client().and_then(|client| {
+                    let pem =
+                        client.with_session_retry(|c| hsm::export_public_key_pem(c, key_id))?;
+                    hsm::export::write_file(&path, pem)
+                });
+
+                match result {
+                    Ok(()) => {
+                        view.keys_output = format!(
+                            "Public key of 0x{:04x} written to '{}'.",
+                            key_id,
+                            path.display()
+                        )
+                        .into();
+                    }
+                    Err(e) => {
+                        view.keys_output = format!("Public key export failed: {}", e).into();
+                        view.handle_device_disconnected(&e, cx);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Ask which key should sign the attestation certificate for the selected
+    /// key; empty means the factory attestation key.
+    fn prompt_attest_key(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(key) = self
+            .selected_key_row
+            .and_then(|ix| self.keys_data.get(ix))
+            .filter(|key| key.object_type == Type::AsymmetricKey)
+        else {
+            self.keys_output = "Select an asymmetric key to attest.".into();
+            cx.notify();
+            return;
+        };
+        let key_id = key.object_id;
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Attest key",
+                format!(
+                    "Has the device certify that 0x{:04x} was generated on it. The certificate is \
+                     signed by an attestation key; leave the ID empty to use the factory one.",
+                    key_id
+                ),
+            )
+            .with_input(cx, "Attestation key ID (default: factory attestation key)")
+            .confirm_label("Next")
+        });
+
+        self.open_modal(modal, window, cx, move |view, input, window, cx| {
+            let input = input.unwrap_or_default();
+            let input = input.trim();
+            let attestation_key_id = if input.is_empty() {
+                None
+            } else {
+                match parse_key_id(input) {
+                    Some(id) => Some(id),
+                    None => {
+                        view.keys_output =
+                            format!("'{}' is not a key ID; use hex with 0x or decimal.", input)
+                                .into();
+                        cx.notify();
+                        return;
+                    }
+                }
+            };
+            view.prompt_save_attestation(key_id, attestation_key_id, window, cx);
+        });
+    }
+
+    /// Second step of "Attest": pick the file, then have the device issue the
+    /// certificate and write it there as DER.
+    fn prompt_save_attestation(
+        &mut self,
+        key_id: u16,
+        attestation_key_id: Option<u16>,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let suggested_name = format!("{}-{:04x}.der", ATTESTATION_FILE_PREFIX, key_id);
+
+        // DER; openssl reads it with `openssl x509 -inform der`
+        self.prompt_save_path(
+            &suggested_name,
+            window,
+            cx,
+            move |view, path, _window, cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.keys_output = format!("Cannot open the save dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                let result = view.session.active_client().and_then(|client| {
+                    let certificate = client.with_session_retry(|c| {
+                        hsm::sign_attestation_certificate(c, key_id, attestation_key_id)
+                    })?;
+                    hsm::export::write_file(&path, &certificate)?;
+                    Ok(certificate)
+                });
+
+                match result {
+                    Ok(certificate) => {
+                        let names = match hsm::parse_certificate(&certificate) {
+                            Ok(cert) => {
+                                format!("Subject: {}\nIssuer: {}", cert.subject, cert.issuer)
+                            }
+                            Err(e) => format!("The certificate could not be decoded: {}", e),
+                        };
+                        view.keys_output = format!(
+                            "Attestation certificate for 0x{:04x} written to '{}'.\n{}",
+                            key_id,
+                            path.display(),
+                            names
+                        )
+                        .into();
+                    }
+                    Err(e) => {
+                        view.keys_output = format!("Attestation failed: {}", e).into();
+                        view.handle_device_disconnected(&e, cx);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Ask which listed wrap key to use (the first one when left empty), then
+    /// pass it to `on_pick`.
+    fn prompt_wrap_key(
+        &mut self,
+        title: &'static str,
+        text: String,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+        on_pick: impl Fn(&mut Self, u16, &mut Window, &mut Context<'_, Self>) + 'static,
+    ) {
+        let wrap_keys: Vec<u16> = self
+            .keys_all
+            .iter()
+            .filter(|k| k.object_type == Type::WrapKey)
+            .map(|k| k.object_id)
+            .collect();
+        let Some(&default_wrap_key) = wrap_keys.first() else {
+            self.keys_output = "No wrap key is listed. Create or import one first.".into();
+            cx.notify();
+            return;
+        };
+        let available = wrap_keys
+            .iter()
+            .map(|id| format!("0x{:04x}", id))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let modal = cx.new(|cx| {
+            Modal::new(cx, title, format!("{}\n\nWrap keys: {}", text, available))
+                .with_input(
+                    cx,
+                    format!("Wrap key ID (default: 0x{:04x})", default_wrap_key),
+                )
+                .confirm_label("Next")
+        });
+
+        self.open_modal(modal, window, cx, move |view, input, window, cx| {
+            let input = input.unwrap_or_default();
+            let input = input.trim();
+            let wrap_key_id = if input.is_empty() {
+                default_wrap_key
+            } else {
+                match parse_key_id(input) {
+                    Some(wrap_key_id) => wrap_key_id,
+                    None => {
+                        view.keys_output =
+                            format!("'{}' is not a key ID; use hex with 0x or decimal.", input)
+                                .into();
+                        cx.notify();
+                        return;
+                    }
+                }
+            };
+            on_pick(view, wrap_key_id, window, cx);
+        });
+    }
+
+    /// Ask which wrap key to use, then where to save the selected object
+    /// exported under it.
+    fn prompt_export_wrapped(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(key) = self.selected_key_row.and_then(|ix| self.keys_data.get(ix)) else {
+            self.keys_output = "Select an object to export first.".into();
+            cx.notify();
+            return;
+        };
+        let object_id = key.object_id;
+        let object_type = key.object_type;
+
+        self.prompt_wrap_key(
+            "Export wrapped",
+            format!(
+                "Exports 0x{:04x} ({:?}) encrypted under a wrap key. Only a device holding the same wrap key can import it.",
+                object_id, object_type
+            ),
+            window,
+            cx,
+            move |view, wrap_key_id, window, cx| {
+                view.prompt_save_wrapped(wrap_key_id, object_type, object_id, window, cx);
+            },
+        );
+    }
+
+    /// Second step of "Export wrapped": pick the file, then export and write it.
+    fn prompt_save_wrapped(
+        &mut self,
+        wrap_key_id: u16,
+        object_type: Type,
+        object_id: u16,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let suggested_name = format!("{}-{:04x}.yhw", WRAPPED_FILE_PREFIX, object_id);
+
+        // The blob is written as is: nonce and ciphertext
+        self.prompt_save_path(
+            &suggested_name,
+            window,
+            cx,
+            move |view, path, _window, cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.keys_output = format!("Cannot open the save dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                let result = view.session.active_client().and_then(|client| {
+                    let blob = client.with_session_retry(|c| {
+                        hsm::export_wrapped(c, wrap_key_id, object_type, object_id)
+                    })?;
+                    hsm::export::write_file(&path, &blob).map(|()| blob.len())
+                });
+
+                match result {
+                    Ok(len) => {
+                        view.keys_output = format!(
+                            "Exported 0x{:04x} under wrap key 0x{:04x}: {} bytes written to '{}'.",
+                            object_id,
+                            wrap_key_id,
+                            len,
+                            path.display()
+                        )
+                        .into();
+                    }
+                    Err(e) => {
+                        view.keys_output = format!("Wrapped export failed: {}", e).into();
+                        view.handle_device_disconnected(&e, cx);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Ask which wrap key to use, then for a wrapped object file to import
+    /// under it.
+    fn prompt_import_wrapped(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        self.prompt_wrap_key(
+            "Import wrapped",
+            "Imports an object exported under a wrap key. It keeps the ID and type it had when exported, so an object with the same ID and type must not exist yet.".to_string(),
+            window,
+            cx,
+            |view, wrap_key_id, window, cx| view.prompt_load_wrapped(wrap_key_id, window, cx),
+        );
+    }
+
+    /// Second step of "Import wrapped": read the file, import it and reload the list.
+    fn prompt_load_wrapped(
+        &mut self,
+        wrap_key_id: u16,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        // Typically a .yhw file from "Export wrapped"
+        self.prompt_open_path(
+            "Load wrapped object",
+            window,
+            cx,
+            move |view, path, window, cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.keys_output = format!("Cannot open the file dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                let result = hsm::export::read_file(&path).and_then(|data| {
+                    view.session
+                        .with_reconnect(|c| hsm::import_wrapped(c, wrap_key_id, &data))
+                });
+
+                match result {
+                    Ok(imported) => {
+                        view.session
+                            .forget_public_key(imported.object_id, imported.object_type);
+                        view.reload_keys(
+                            Some(format!(
+                                "Imported {:?} 0x{:04x} from '{}' under wrap key 0x{:04x}.",
+                                imported.object_type,
+                                imported.object_id,
+                                path.display(),
+                                wrap_key_id
+                            )),
+                            window,
+                            cx,
+                        );
+                    }
+                    Err(e) => {
+                        view.keys_output = format!("Wrapped import failed: {}", e).into();
+                        view.handle_device_disconnected(&e, cx);
+                    }
+                }
+                cx.notify();
+            },
+        );
+    }
+
+    /// Ask for a snapshot file, then show how the device differs from it.
+    fn prompt_compare_snapshot(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Compare snapshot",
+                "Lists the device again and reports objects added, removed or changed since the snapshot, matched by ID and type.",
+            )
+            .with_input(cx, "Snapshot file")
+            .confirm_label("Compare")
+        });
+
+        self.open_modal(modal, window, cx, |view, path, window, cx| {
+            let path = path.unwrap_or_default().trim().to_string();
+            if path.is_empty() {
+                view.keys_output = "Enter the path of a snapshot file.".into();
+                cx.notify();
+                return;
+            }
+
+            let result = hsm::read_snapshot(Path::new(&path)).and_then(|snapshot| {
+                let client = view.session.active_client()?;
+                let current = client.with_session_retry(hsm::take_snapshot)?;
+                Ok((hsm::diff_inventory(&snapshot, &current), snapshot, current))
+            });
+
+            match result {
+                Ok((diff, snapshot, current)) => {
+                    let source = format!(
+                        "snapshot '{}' taken {}{}",
+                        path,
+                        snapshot.taken_at,
+                        device_suffix(snapshot.device.as_ref())
+                    );
+                    view.keys_output = if diff.is_empty() {
+                        format!(
+                            "Device matches {}: {} object(s).",
+                            source,
+                            current.objects.len()
+                        )
+                    } else {
+                        let count = |change| diff.iter().filter(|d| d.change == change).count();
+                        format!(
+                            "Compared with {}: {} added, {} removed, {} changed.\nClick \"List keys\" to return to the object list.",
+                            source,
+                            count(hsm::InventoryChange::Added),
+                            count(hsm::InventoryChange::Removed),
+                            count(hsm::InventoryChange::Changed)
+                        )
+                    }
+                    .into();
+                    view.inventory_diff = (!diff.is_empty()).then(|| {
+                        cx.new(|cx| TableState::new(InventoryDiffDelegate::new(diff), window, cx))
+                    });
+                }
+                Err(e) => {
+                    view.keys_output = format!("Snapshot comparison failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            }
+            cx.notify();
+        });
+    }
+
+    /// Copy the full SHA-256 fingerprint of the selected key to the clipboard.
+    fn copy_selected_fingerprint(&mut self, cx: &mut Context<'_, Self>) {
+        let fingerprint = self
+            .selected_key_row
+            .and_then(|row_ix| self.keys_data.get(row_ix))
+            .and_then(|key| key.fingerprint_hex.clone());
+
+        match fingerprint {
+            Some(fingerprint) => {
+                cx.write_to_clipboard(ClipboardItem::new_string(fingerprint.clone()));
+                self.keys_output = format!("Copied SHA-256 fingerprint:\n{}", fingerprint).into();
+            }
+            None => {
+                self.keys_output = "Select an asymmetric key to copy its fingerprint.".into();
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Copy the full public key of the selected key, not the table's preview.
+    fn copy_selected_public_key(&mut self, cx: &mut Context<'_, Self>) {
+        let key = self
+            .selected_key_row
+            .and_then(|row_ix| self.keys_data.get(row_ix))
+            .and_then(|key| Some((key.object_id, key.public_key_hex.clone()?)));
+
+        match key {
+            Some((key_id, public_key_hex)) => {
+                cx.write_to_clipboard(ClipboardItem::new_string(public_key_hex.clone()));
+                self.keys_output = format!(
+                    "Copied! Public key of 0x{:04x} ({} bytes) is on the clipboard as hex.",
+                    key_id,
+                    public_key_hex.len() / 2
+                )
+                .into();
+            }
+            None => {
+                self.keys_output = "Select an asymmetric key to copy its public key.".into();
+            }
+        }
+
+        cx.notify();
+    }
+
+    pub fn render_keys_config_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Keys config"),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(
+                "List objects/keys that are visible to the current YubiHSM authentication key.",
             ))
             .child({
-                let can_delete = self.selected_key_row.is_some()
-                    && self
-                        .selected_key_row
-                        .and_then(|ix| self.keys_data.get(ix))
-                        .map(|k| k.object_type != Type::AuthenticationKey)
-                        .unwrap_or(false);
+                let selected_key = self.selected_key_row.and_then(|ix| self.keys_data.get(ix));
+                let can_copy_fingerprint =
+                    selected_key.is_some_and(|k| k.fingerprint_hex.is_some());
+                let can_copy_public_key = selected_key.is_some_and(|k| k.public_key_hex.is_some());
+                let can_export_public_key =
+                    selected_key.is_some_and(|k| k.object_type == Type::AsymmetricKey);
+                let can_attest = can_export_public_key
+                    && self.session_allows(Capability::SIGN_ATTESTATION_CERTIFICATE);
+                let can_export_wrapped =
+                    selected_key.is_some() && self.session_allows(Capability::EXPORT_WRAPPED);
+                let mutable = !self.destructive_disabled;
+                let can_import_wrapped = mutable && self.session_allows(Capability::IMPORT_WRAPPED);
+                let can_delete = self.can_delete_selected_key();
+                let has_report = self.last_report.is_some();
+                let has_keys = !self.keys_data.is_empty();
+                let can_export_json = selected_key.is_some();
+                let can_generate_wrap =
+                    mutable && self.session_allows(Capability::GENERATE_WRAP_KEY);
+                let can_put_wrap = mutable && self.session_allows(Capability::PUT_WRAP_KEY);
+                let can_generate_asymmetric =
+                    mutable && self.session_allows(Capability::GENERATE_ASYMMETRIC_KEY);
 
                 div()
                     .flex()
+                    .flex_wrap()
                     .gap_2()
                     .child(
-                        div()
-                            .bg(rgb(0x007acc))
-                            .hover(|style| style.bg(rgb(0x005a9e)))
-                            .rounded_md()
-                            .px_4()
-                            .py_2()
-                            .text_color(rgb(0xffffff))
-                            .cursor_pointer()
-                            .child("List keys")
-                            .on_mouse_down(
+                        toolbar_button(
+                            if self.hsm_busy {
+                                "Working…"
+                            } else {
+                                "List keys"
+                            },
+                            !self.hsm_busy,
+                            0x007acc,
+                            0x005a9e,
+                        )
+                        .when(self.hsm_busy, |el| {
+                            el.flex().items_center().gap_2().child(spinner())
+                        })
+                        .when(!self.hsm_busy, |el| {
+                            el.on_mouse_down(
                                 MouseButton::Left,
                                 cx.listener(|view, _, window, cx| {
                                     view.load_keys_from_hsm(window, cx);
                                 }),
+                            )
+                        }),
+                    )
+                    .child(
+                        toolbar_button("Refresh session info", true, 0x6c757d, 0x5a6268)
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.refresh_session_info(&RefreshSessionInfo, window, cx);
+                                }),
                             ),
                     )
                     .child(
-                        div()
-                            .bg(if can_delete {
-                                rgb(0xdc3545)
-                            } else {
-                                rgb(0x555555)
-                            })
-                            .when(can_delete, |el| el.hover(|style| style.bg(rgb(0xc82333))))
-                            .rounded_md()
-                            .px_4()
-                            .py_2()
-                            .text_color(rgb(0xffffff))
-                            .cursor(if can_delete {
-                                gpui::CursorStyle::PointingHand
-                            } else {
-                                gpui::CursorStyle::Arrow
-                            })
-                            .child("Delete selected")
-                            .when(can_delete, |el| {
+                        toolbar_button("Scratch key", can_generate_asymmetric, 0xffc107, 0xe0a800)
+                            .when(can_generate_asymmetric, |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.create_scratch_key(window, cx);
+                                    }),
+                                )
+                            }),
+                    )
+                    .child(
+                        toolbar_button("Create wrap key", can_generate_wrap, 0x28a745, 0x1e7e34)
+                            .when(can_generate_wrap, |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_create_wrap_key(window, cx);
+                                    }),
+                                )
+                            }),
+                    )
+                    .child(
+                        toolbar_button("Import wrap key", can_put_wrap, 0x6c757d, 0x5a6268).when(
+                            can_put_wrap,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_import_wrap_key(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Delete selected", can_delete, 0xdc3545, 0xc82333).when(
+                            can_delete,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.confirm_delete_selected_key(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Self-test", true, 0x6f42c1, 0x59339d).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|view, _, _, cx| {
+                                view.run_self_test(cx);
+                            }),
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Benchmark", true, 0x6f42c1, 0x59339d).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|view, _, _, cx| {
+                                view.run_sign_benchmark(cx);
+                            }),
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Export report…", has_report, 0x17a2b8, 0x117a8b).when(
+                            has_report,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.export_last_report(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Export JWKS…", true, 0x17a2b8, 0x117a8b).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|view, _, window, cx| {
+                                view.prompt_export_jwks(window, cx);
+                            }),
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Export CSV…", has_keys, 0x17a2b8, 0x117a8b).when(
+                            has_keys,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_export_csv(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Export all as JSON…", has_keys, 0x17a2b8, 0x117a8b).when(
+                            has_keys,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_export_all_json(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Export JSON…", can_export_json, 0x17a2b8, 0x117a8b).when(
+                            can_export_json,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_export_object_json(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button(
+                            "Export public key…",
+                            can_export_public_key,
+                            0x17a2b8,
+                            0x117a8b,
+                        )
+                        .when(can_export_public_key, |el| {
+                            el.on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.prompt_export_public_key(window, cx);
+                                }),
+                            )
+                        }),
+                    )
+                    .child(
+                        toolbar_button("Attest…", can_attest, 0x6f42c1, 0x59339d).when(
+                            can_attest,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_attest_key(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Export wrapped…", can_export_wrapped, 0x17a2b8, 0x117a8b)
+                            .when(can_export_wrapped, |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_export_wrapped(window, cx);
+                                    }),
+                                )
+                            }),
+                    )
+                    .child(
+                        toolbar_button("Import wrapped…", can_import_wrapped, 0x17a2b8, 0x117a8b)
+                            .when(can_import_wrapped, |el| {
                                 el.on_mouse_down(
                                     MouseButton::Left,
                                     cx.listener(|view, _, window, cx| {
-                                        view.delete_selected_key(window, cx);
+                                        view.prompt_import_wrapped(window, cx);
+                                    }),
+                                )
+                            }),
+                    )
+                    .child(
+                        toolbar_button("Save snapshot…", true, 0x17a2b8, 0x117a8b).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|view, _, window, cx| {
+                                view.prompt_save_snapshot(window, cx);
+                            }),
+                        ),
+                    )
+                    .child(
+                        toolbar_button("Compare snapshot…", true, 0x17a2b8, 0x117a8b)
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.prompt_compare_snapshot(window, cx);
+                                }),
+                            ),
+                    )
+                    .child(
+                        toolbar_button(
+                            "Copy fingerprint",
+                            can_copy_fingerprint,
+                            0x6c757d,
+                            0x5a6268,
+                        )
+                        .when(can_copy_fingerprint, |el| {
+                            el.on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.copy_selected_fingerprint(cx);
+                                }),
+                            )
+                        }),
+                    )
+                    .child(
+                        toolbar_button("Copy public key", can_copy_public_key, 0x6c757d, 0x5a6268)
+                            .when(can_copy_public_key, |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, _, cx| {
+                                        view.copy_selected_public_key(cx);
                                     }),
                                 )
                             }),
                     )
             })
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x888888))
+                    .child(match self.session.info() {
+                        Some(info) => format!(
+                            "Auth key 0x{:04x}: {} capabilities ({}), domains {:?}",
+                            info.auth_key_id,
+                            info.capabilities.bits().count_ones(),
+                            if info.is_writable() {
+                                "can modify objects"
+                            } else {
+                                "read-only"
+                            },
+                            info.domains
+                        ),
+                        None => "Auth key capabilities unknown; click \"Refresh session info\"."
+                            .to_string(),
+                    }),
+            )
+            .child(self.render_type_filter(cx))
+            .child(self.render_capability_filter(cx))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(div().text_xs().text_color(rgb(0x888888)).child("Search:"))
+                    .child(
+                        div()
+                            .bg(rgb(0x1e1e1e))
+                            .border_1()
+                            .border_color(rgb(0x444444))
+                            .rounded_md()
+                            .p_2()
+                            .w(px(320.))
+                            .min_h(px(24.))
+                            .child(self.keys_search_input.clone()),
+                    ),
+            )
             // Status / summary text
             .child(
                 div()
@@ -256,9 +2482,71 @@ impl HsmApp {
                     .text_color(rgb(0xcccccc))
                     .child(self.keys_output.clone()),
             )
+            .when_some(self.shown_progress(), |el, (done, total, running)| {
+                let fraction = if total == 0 {
+                    1.
+                } else {
+                    done as f32 / total as f32
+                };
+                let label = if running {
+                    format!("Fetching {}/{}…", done, total)
+                } else {
+                    format!("{}/{} objects", done, total)
+                };
+
+                el.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(
+                            div()
+                                .flex_1()
+                                .h(px(6.))
+                                .bg(rgb(0x1e1e1e))
+                                .rounded_md()
+                                .child(
+                                    div()
+                                        .h_full()
+                                        .w(relative(fraction))
+                                        .bg(rgb(0x007acc))
+                                        .rounded_md(),
+                                ),
+                        )
+                        .child(div().text_xs().text_color(rgb(0x888888)).child(label)),
+                )
+            })
+            .when(self.inventory_diff.is_none(), |el| {
+                el.children(self.render_key_detail_panel(cx))
+            })
             .child({
-                if let Some(ref state) = self.keys_table {
+                if let Some(ref diff) = self.inventory_diff {
+                    div()
+                        .flex_1()
+                        .min_h_0()
+                        .w_full()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .child(
+                            Table::new(diff)
+                                .stripe(true)
+                                .bordered(true)
+                                .scrollbar_visible(true, true),
+                        )
+                } else if let Some(ref state) = self.keys_table {
+                    let keys_focus = self.keys_focus.clone();
                     div()
+                        .key_context(KEY_CONTEXT)
+                        .track_focus(&self.keys_focus)
+                        .on_action(cx.listener(Self::select_prev_key))
+                        .on_action(cx.listener(Self::select_next_key))
+                        .on_action(cx.listener(Self::open_key_details))
+                        .on_action(cx.listener(Self::delete_selected_key_action))
+                        .on_mouse_down(MouseButton::Left, move |_, window, _| {
+                            window.focus(&keys_focus);
+                        })
                         .flex_1()
                         .min_h_0()
                         .w_full()
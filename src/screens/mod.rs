@@ -1,3 +1,10 @@
+pub mod audit;
 pub mod auth;
+pub mod device;
+pub mod hmac;
+pub mod key_gen;
 pub mod keys_config;
+pub mod opaque;
+pub mod random;
+pub mod settings;
 pub mod sign_verify;
@@ -0,0 +1,422 @@
+use gpui::{
+    AnyElement, Context, MouseButton, ParentElement, Styled, Window, div, prelude::*, px, rgb,
+};
+use yubihsm::{Algorithm, Capability, object::Type, opaque};
+
+use crate::{
+    HsmApp,
+    config::OPAQUE_FILE_PREFIX,
+    hsm::{self, error::HsmError},
+    screens::{auth::safe_mode_badge, sign_verify::parse_key_id},
+};
+
+/// Certificate fields as shown in the output panel.
+fn describe_certificate(cert: &hsm::CertificateSummary) -> String {
+    format!(
+        "Subject: {}\nIssuer: {}\nSerial: {}\nValid from: {}\nValid until: {}{}",
+        cert.subject,
+        cert.issuer,
+        cert.serial,
+        cert.not_before,
+        cert.not_after,
+        if cert.currently_valid {
+            ""
+        } else {
+            " (not valid now)"
+        }
+    )
+}
+
+impl HsmApp {
+    /// Object ID from the form; `auto` (or empty) asks the device to pick one
+    /// when `allow_auto` is set.
+    fn opaque_object_id(&self, allow_auto: bool, cx: &Context<'_, Self>) -> Result<u16, String> {
+        let input = self.opaque_id_input.read(cx).content();
+        let input = input.trim();
+        if allow_auto && (input.is_empty() || input.eq_ignore_ascii_case("auto")) {
+            return Ok(0);
+        }
+        parse_key_id(input).ok_or_else(|| {
+            format!(
+                "'{}' is not an object ID; use hex with 0x or decimal.",
+                input
+            )
+        })
+    }
+
+    /// Pick a file and store it as an opaque object. In certificate mode PEM
+    /// is converted to DER and the file must parse as X.509.
+    fn upload_opaque(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let object_id = match self.opaque_object_id(true, cx) {
+            Ok(object_id) => object_id,
+            Err(message) => {
+                self.opaque_output = message.into();
+                cx.notify();
+                return;
+            }
+        };
+        let domain = self.opaque_domains.read(cx).selected();
+        if domain.is_empty() {
+            self.opaque_output = "Pick at least one domain for the object.".into();
+            cx.notify();
+            return;
+        }
+        let label = self
+            .opaque_label_input
+            .read(cx)
+            .content()
+            .trim()
+            .to_string();
+        let as_certificate = self.opaque_as_certificate;
+
+        self.prompt_open_path(
+            "Upload opaque object",
+            window,
+            cx,
+            move |view, path, window, cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.opaque_output =
+                            format!("Cannot open the file dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                let prepared = hsm::export::read_file(&path).and_then(|data| {
+                    if as_certificate {
+                        let der = hsm::certificate_der(&data)?;
+                        let summary = hsm::parse_certificate(&der)?;
+                        Ok((der, Some(summary)))
+                    } else {
+                        Ok((data, None))
+                    }
+                });
+                let (data, certificate) = match prepared {
+                    Ok(prepared) => prepared,
+                    Err(e) => {
+                        view.opaque_output = format!("Error: {}", e).into();
+                        return;
+                    }
+                };
+
+                let algorithm = if as_certificate {
+                    opaque::Algorithm::X509Certificate
+                } else {
+                    opaque::Algorithm::Data
+                };
+                let len = data.len();
+
+                let spawned = view.spawn_hsm_task(
+                    window,
+                    cx,
+                    move |client| {
+                        client.with_session_retry(|c| {
+                            hsm::put_opaque(c, object_id, &label, domain, algorithm, &data)
+                        })
+                    },
+                    move |view, result, window, cx| match result {
+                        Ok(object_id) => {
+                            let mut output = format!(
+                                "Stored {} bytes from '{}' as opaque object 0x{:04x}.",
+                                len,
+                                path.display(),
+                                object_id
+                            );
+                            if let Some(certificate) = &certificate {
+                                output.push_str("\n\n");
+                                output.push_str(&describe_certificate(certificate));
+                            }
+                            view.opaque_output = output.into();
+                            view.reload_keys(
+                                Some(format!("Stored opaque object 0x{:04x}.", object_id)),
+                                window,
+                                cx,
+                            );
+                        }
+                        Err(HsmError::ObjectExists { id, .. }) => {
+                            view.opaque_output = format!(
+                                "An opaque object already exists at 0x{:04x}. Enter a free ID, or \"auto\" to let the device choose.",
+                                id
+                            )
+                            .into();
+                        }
+                        Err(e) => {
+                            view.opaque_output = format!("Upload failed: {}", e).into();
+                            view.handle_device_disconnected(&e, cx);
+                        }
+                    },
+                );
+                if let Err(e) = spawned {
+                    view.opaque_output =
+                        format!("Failed to use YubiHSM2 session: {}", e).into();
+                }
+            },
+        );
+    }
+
+    /// Read the opaque object named in the form; certificates are parsed and described.
+    fn fetch_opaque(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let object_id = match self.opaque_object_id(false, cx) {
+            Ok(object_id) => object_id,
+            Err(message) => {
+                self.opaque_output = message.into();
+                cx.notify();
+                return;
+            }
+        };
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                let info = client
+                    .with_session_retry(|c| hsm::get_object_info(c, object_id, Type::Opaque))?;
+                let data = client.with_session_retry(|c| hsm::get_opaque(c, object_id))?;
+                Ok::<_, HsmError>((info.algorithm, data))
+            },
+            move |view, result, _, cx| match result {
+                Ok((algorithm, data)) => {
+                    let mut output = format!(
+                        "Opaque object 0x{:04x} ({:?}, {} bytes).",
+                        object_id,
+                        algorithm,
+                        data.len()
+                    );
+                    if algorithm == Algorithm::Opaque(opaque::Algorithm::X509Certificate) {
+                        output.push_str("\n\n");
+                        match hsm::parse_certificate(&data) {
+                            Ok(certificate) => output.push_str(&describe_certificate(&certificate)),
+                            Err(e) => output.push_str(&format!("Could not parse it: {}", e)),
+                        }
+                    } else {
+                        output.push_str(&format!("\n\n{}", hex::encode(&data)));
+                    }
+                    view.opaque_output = output.into();
+                    view.opaque_data = Some((object_id, data));
+                }
+                Err(e) => {
+                    view.opaque_data = None;
+                    view.opaque_output = format!("Fetch failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.opaque_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    /// Ask where to write the last fetched object, then save it as is.
+    fn prompt_save_opaque(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some((object_id, data)) = self.opaque_data.clone() else {
+            return;
+        };
+        let suggested_name = format!("{}-{:04x}.bin", OPAQUE_FILE_PREFIX, object_id);
+
+        // Written exactly as stored; certificates are DER
+        self.prompt_save_path(
+            &suggested_name,
+            window,
+            cx,
+            move |view, path, _window, _cx| {
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.opaque_output = format!("Cannot open the save dialog: {}", e).into();
+                        return;
+                    }
+                };
+
+                view.opaque_output = match hsm::export::write_file(&path, &data) {
+                    Ok(()) => format!(
+                        "Wrote 0x{:04x} ({} bytes) to '{}'.",
+                        object_id,
+                        data.len(),
+                        path.display()
+                    )
+                    .into(),
+                    Err(e) => format!("Error: {}", e).into(),
+                };
+            },
+        );
+    }
+
+    pub fn render_opaque_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let can_upload = !self.destructive_disabled
+            && !self.hsm_busy
+            && self.session_allows(Capability::PUT_OPAQUE)
+            && !self.opaque_domains.read(cx).selected().is_empty();
+        let can_fetch = !self.hsm_busy && self.session_allows(Capability::GET_OPAQUE);
+        let can_save = self.opaque_data.is_some();
+
+        let field = |caption: &'static str, input: AnyElement| {
+            div()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(div().text_sm().text_color(rgb(0xcccccc)).child(caption))
+                .child(
+                    div()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .p_2()
+                        .min_h(px(24.))
+                        .child(input),
+                )
+        };
+        let button = |label: &'static str, enabled: bool, bg: u32, hover_bg: u32| {
+            div()
+                .bg(if enabled { rgb(bg) } else { rgb(0x555555) })
+                .when(enabled, |el| {
+                    el.hover(move |style| style.bg(rgb(hover_bg)))
+                        .cursor_pointer()
+                })
+                .rounded_md()
+                .px_4()
+                .py_2()
+                .text_color(rgb(0xffffff))
+                .child(if self.hsm_busy { "Working…" } else { label })
+        };
+        let chip = |label: &'static str, active: bool| {
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(rgb(0xffffff))
+                .bg(if active { rgb(0x007acc) } else { rgb(0x2a2a2a) })
+                .hover(|style| style.bg(rgb(0x404040)))
+                .child(label)
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .items_center()
+                    .gap_2()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Opaque objects")
+                    .when(self.destructive_disabled, |el| el.child(safe_mode_badge())),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(format!(
+                "Store arbitrary data such as X.509 certificates on the YubiHSM2 (up to {} bytes) and read it back.",
+                hsm::MAX_OPAQUE_LEN
+            )))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .mr_1()
+                            .child("Upload as (certificates may be DER or PEM):"),
+                    )
+                    .child(chip("X.509 certificate", self.opaque_as_certificate).on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.opaque_as_certificate = true;
+                            cx.notify();
+                        }),
+                    ))
+                    .child(chip("Data", !self.opaque_as_certificate).on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.opaque_as_certificate = false;
+                            cx.notify();
+                        }),
+                    )),
+            )
+            .child(field(
+                "Object ID (\"auto\" lets the device pick one on upload):",
+                self.opaque_id_input.clone().into_any_element(),
+            ))
+            .child(field(
+                "Label:",
+                self.opaque_label_input.clone().into_any_element(),
+            ))
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .child("Domains for uploads:"),
+                    )
+                    .child(self.opaque_domains.clone()),
+            )
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .child(
+                        button("Upload file…", can_upload, 0x007acc, 0x005a9e).when(
+                            can_upload,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.upload_opaque(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    )
+                    .child(
+                        button("Fetch", can_fetch, 0x28a745, 0x1e7e34).when(can_fetch, |el| {
+                            el.on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.fetch_opaque(window, cx);
+                                }),
+                            )
+                        }),
+                    )
+                    .child(
+                        button("Save fetched…", can_save, 0x6c757d, 0x5a6268).when(
+                            can_save,
+                            |el| {
+                                el.on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|view, _, window, cx| {
+                                        view.prompt_save_opaque(window, cx);
+                                    }),
+                                )
+                            },
+                        ),
+                    ),
+            )
+            .child(
+                div()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .p_2()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(self.opaque_output.clone()),
+            )
+            .into_any()
+    }
+}
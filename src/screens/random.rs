@@ -0,0 +1,196 @@
+use gpui::{
+    AnyElement, ClipboardItem, Context, MouseButton, ParentElement, Styled, Window, div,
+    prelude::*, px, rgb,
+};
+
+use crate::{HsmApp, config::DEFAULT_RANDOM_LEN, hsm};
+
+impl HsmApp {
+    /// Read the requested number of bytes from the device RNG in the background.
+    fn generate_random(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let input = self.random_len_input.read(cx).content();
+        let input = input.trim();
+        let len = if input.is_empty() {
+            DEFAULT_RANDOM_LEN
+        } else {
+            match input.parse::<usize>() {
+                Ok(len) => len,
+                Err(_) => {
+                    self.random_output = format!(
+                        "'{}' is not a length; enter a number of bytes from 1 to {}.",
+                        input,
+                        hsm::MAX_RANDOM_LEN
+                    )
+                    .into();
+                    cx.notify();
+                    return;
+                }
+            }
+        };
+        // Checked here too so an out-of-range length never needs a session
+        if !(1..=hsm::MAX_RANDOM_LEN).contains(&len) {
+            self.random_output = format!(
+                "Length must be between 1 and {} bytes, got {}.",
+                hsm::MAX_RANDOM_LEN,
+                len
+            )
+            .into();
+            cx.notify();
+            return;
+        }
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| client.with_session_retry(|c| hsm::get_random(c, len)),
+            |view, result, _, cx| match result {
+                Ok(bytes) => {
+                    view.random_output = format!(
+                        "{} random bytes from the device RNG:\n{}",
+                        bytes.len(),
+                        hex::encode(&bytes)
+                    )
+                    .into();
+                    view.random_bytes = Some(bytes);
+                }
+                Err(e) => {
+                    view.random_bytes = None;
+                    view.random_output = format!("Random generation failed: {}", e).into();
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.random_output = format!("Failed to use YubiHSM2 session: {}", e).into();
+        }
+
+        cx.notify();
+    }
+
+    fn copy_random(&mut self, cx: &mut Context<'_, Self>) {
+        if let Some(bytes) = &self.random_bytes {
+            cx.write_to_clipboard(ClipboardItem::new_string(hex::encode(bytes)));
+            self.random_output = format!(
+                "Copied {} random bytes as hex:\n{}",
+                bytes.len(),
+                hex::encode(bytes)
+            )
+            .into();
+            cx.notify();
+        }
+    }
+
+    pub fn render_random_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let can_copy = self.random_bytes.is_some();
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Random bytes"),
+            )
+            .child(div().text_xs().text_color(rgb(0x888888)).child(format!(
+                "Read bytes from the YubiHSM2 hardware RNG (get-pseudo-random), 1 to {} at a time.",
+                hsm::MAX_RANDOM_LEN
+            )))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .child("Length (bytes):"),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x1e1e1e))
+                            .border_1()
+                            .border_color(rgb(0x444444))
+                            .rounded_md()
+                            .p_2()
+                            .w(px(160.))
+                            .min_h(px(24.))
+                            .child(self.random_len_input.clone()),
+                    ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .bg(if self.hsm_busy {
+                                rgb(0x555555)
+                            } else {
+                                rgb(0x007acc)
+                            })
+                            .when(!self.hsm_busy, |el| {
+                                el.hover(|style| style.bg(rgb(0x005a9e)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.generate_random(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child(if self.hsm_busy {
+                                "Working…"
+                            } else {
+                                "Generate"
+                            }),
+                    )
+                    .child(
+                        div()
+                            .bg(if can_copy {
+                                rgb(0x6c757d)
+                            } else {
+                                rgb(0x555555)
+                            })
+                            .when(can_copy, |el| {
+                                el.hover(|style| style.bg(rgb(0x5a6268)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, _, cx| {
+                                            view.copy_random(cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child("Copy hex"),
+                    ),
+            )
+            .child(
+                div()
+                    .bg(rgb(0x1e1e1e))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .p_2()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(self.random_output.clone()),
+            )
+            .into_any()
+    }
+}
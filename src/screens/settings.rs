@@ -0,0 +1,270 @@
+use gpui::{
+    AnyElement, Context, Entity, MouseButton, ParentElement, SharedString, Styled, div, prelude::*,
+    px, rgb,
+};
+
+use crate::{
+    HsmApp,
+    config::{self, Settings},
+    hsm::{ConnectorKind, HashAlg},
+    screens::sign_verify::parse_key_id,
+    ui::TextArea,
+};
+
+impl HsmApp {
+    /// Apply `change` to the settings and write them out. A failed write is
+    /// logged; the change still holds for this run.
+    pub(crate) fn update_settings(&mut self, change: impl FnOnce(&mut Settings)) {
+        change(&mut self.settings);
+        if let Err(e) = self.settings.save() {
+            tracing::warn!(error = %e, "cannot save settings");
+        }
+    }
+
+    /// Turn auto-connect on or off. Turning it off also removes the stored
+    /// password from the keyring.
+    fn set_auto_connect(&mut self, enabled: bool) {
+        self.update_settings(|settings| settings.auto_connect = enabled);
+        self.settings_status = if enabled {
+            "Auto-connect is on. The password is kept in the system keyring after the next successful HTTP connect.".into()
+        } else {
+            match config::forget_password(self.settings.auth_key_id) {
+                Ok(()) => "Auto-connect is off and the stored password was removed.".into(),
+                Err(e) => format!(
+                    "Auto-connect is off, but the stored password could not be removed: {}",
+                    e
+                )
+                .into(),
+            }
+        };
+    }
+
+    /// Check the Settings fields and save them, applying what takes effect at once.
+    fn save_settings(&mut self, cx: &mut Context<'_, Self>) {
+        let read = |input: &Entity<TextArea>, cx: &Context<'_, Self>| {
+            input.read(cx).content().trim().to_string()
+        };
+
+        let auth_key = read(&self.settings_auth_key_input, cx);
+        let Some(auth_key_id) = parse_key_id(&auth_key) else {
+            self.settings_status = format!(
+                "'{}' is not an auth key ID; use hex with 0x or decimal.",
+                auth_key
+            )
+            .into();
+            cx.notify();
+            return;
+        };
+        let signing_key = read(&self.settings_signing_key_input, cx);
+        let Some(signing_key_id) = parse_key_id(&signing_key) else {
+            self.settings_status = format!(
+                "'{}' is not a signing key ID; use hex with 0x or decimal.",
+                signing_key
+            )
+            .into();
+            cx.notify();
+            return;
+        };
+        let idle = read(&self.settings_idle_input, cx);
+        let Ok(idle_timeout_minutes) = idle.parse::<u64>() else {
+            self.settings_status = format!(
+                "'{}' is not a number of minutes; 0 turns the timeout off.",
+                idle
+            )
+            .into();
+            cx.notify();
+            return;
+        };
+        let connector_address = read(&self.settings_connector_input, cx);
+        if let Err(e) = ConnectorKind::http_from_address(&connector_address) {
+            self.settings_status = format!("{}", e).into();
+            cx.notify();
+            return;
+        }
+
+        self.settings.auth_key_id = auth_key_id;
+        self.settings.signing_key_id = signing_key_id;
+        self.settings.idle_timeout_minutes = idle_timeout_minutes;
+        self.settings.connector_address = connector_address.clone();
+        self.settings_status = match self.settings.save() {
+            Ok(path) => format!("Saved to {}.", path.display()).into(),
+            Err(e) => format!("Settings apply until restart but were not saved: {}", e).into(),
+        };
+
+        self.auth_connector_input.update(cx, |input, cx| {
+            input.set_content(connector_address, cx);
+        });
+        self.idle_timeout = crate::idle_timeout_from_minutes(idle_timeout_minutes);
+        if self.idle_timeout.is_none() {
+            self.idle_timer = None;
+        } else if self.session.is_authenticated() {
+            self.start_idle_timer(cx);
+        }
+
+        cx.notify();
+    }
+
+    pub fn render_settings_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
+        let field = |caption: &'static str, input: AnyElement| {
+            div()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(div().text_sm().text_color(rgb(0xcccccc)).child(caption))
+                .child(
+                    div()
+                        .bg(rgb(0x1e1e1e))
+                        .border_1()
+                        .border_color(rgb(0x444444))
+                        .rounded_md()
+                        .p_2()
+                        .min_h(px(24.))
+                        .child(input),
+                )
+        };
+        let chip = |label: SharedString, active: bool| {
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(rgb(0xffffff))
+                .bg(if active { rgb(0x007acc) } else { rgb(0x2a2a2a) })
+                .hover(|style| style.bg(rgb(0x404040)))
+                .child(label)
+        };
+
+        div()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x2e2e2e))
+            .size_full()
+            .p_4()
+            .gap_4()
+            .child(
+                div()
+                    .flex()
+                    .justify_center()
+                    .text_2xl()
+                    .text_color(rgb(0xffffff))
+                    .child("Settings"),
+            )
+            .child(
+                div().text_xs().text_color(rgb(0x888888)).child(
+                    "Kept across restarts. The password is never written to the settings file.",
+                ),
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .mr_1()
+                            .child("Connect over:"),
+                    )
+                    .children([("USB", false), ("HTTP", true)].map(|(label, use_http)| {
+                        chip(label.into(), self.settings.use_http == use_http).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |view, _, _, cx| {
+                                view.auth_use_http = use_http;
+                                view.update_settings(|settings| settings.use_http = use_http);
+                                cx.notify();
+                            }),
+                        )
+                    })),
+            )
+            .child(field(
+                "Connector address (HTTP):",
+                self.settings_connector_input.clone().into_any_element(),
+            ))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .mr_1()
+                            .child("Auto-connect on startup (HTTP, password in keyring):"),
+                    )
+                    .children([("Off", false), ("On", true)].map(|(label, enabled)| {
+                        chip(label.into(), self.settings.auto_connect == enabled).on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |view, _, _, cx| {
+                                view.set_auto_connect(enabled);
+                                cx.notify();
+                            }),
+                        )
+                    })),
+            )
+            .child(field(
+                "Auth key ID:",
+                self.settings_auth_key_input.clone().into_any_element(),
+            ))
+            .child(field(
+                "Default signing key ID:",
+                self.settings_signing_key_input.clone().into_any_element(),
+            ))
+            .child(field(
+                "Idle timeout in minutes (0 turns it off):",
+                self.settings_idle_input.clone().into_any_element(),
+            ))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x888888))
+                            .mr_1()
+                            .child("Hash:"),
+                    )
+                    .children(HashAlg::ALL.into_iter().map(|hash_alg| {
+                        chip(hash_alg.name().into(), self.settings.hash_alg == hash_alg)
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |view, _, _, cx| {
+                                    view.hash_alg = hash_alg;
+                                    view.update_settings(|settings| settings.hash_alg = hash_alg);
+                                    cx.notify();
+                                }),
+                            )
+                    })),
+            )
+            .child(
+                div().flex().child(
+                    div()
+                        .bg(rgb(0x007acc))
+                        .hover(|style| style.bg(rgb(0x005a9e)))
+                        .cursor_pointer()
+                        .rounded_md()
+                        .px_4()
+                        .py_2()
+                        .text_color(rgb(0xffffff))
+                        .child("Save")
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|view, _, _, cx| view.save_settings(cx)),
+                        ),
+                ),
+            )
+            .when(!self.settings_status.is_empty(), |el| {
+                el.child(
+                    div()
+                        .text_sm()
+                        .text_color(rgb(0xcccccc))
+                        .child(self.settings_status.clone()),
+                )
+            })
+            .into_any()
+    }
+}
@@ -1,12 +1,1351 @@
 use gpui::{
-    AnyElement, Context, MouseButton, ParentElement, SharedString, Styled, div, prelude::*, px, rgb,
+    AnyElement, App, ClipboardItem, Context, MouseButton, ParentElement, SharedString, Styled,
+    Window, div, prelude::*, px, rgb,
 };
 
-use crate::{HsmApp, SignText, VerifyText};
+use crate::{
+    CompareSignature, ExportForOpenssl, HsmApp, RecoverPublicKey, SignAndAttest, SignJws, SignText,
+    VerifyText, check_domain,
+    config::{DEFAULT_MAX_SIGNATURE_AGE_SECS, DEFAULT_OPENSSL_EXPORT_DIR, SIGNATURE_FILE_PREFIX},
+    hsm::{
+        self, Curve, FileSignOutcome, HsmClient, SignOutcome, SignatureEncoding, TimestampStatus,
+        TimestampedVerifyResult, VerifyOutcome,
+        error::{HsmError, HsmResult},
+    },
+    ui::Modal,
+};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use yubihsm::{Capability, asymmetric, object::Type};
+
+/// Key context of the Sign & Verify screen; its shortcuts only apply inside it
+pub const KEY_CONTEXT: &str = "SignVerify";
+
+/// Parse a numeric key ID, accepting `0x`-prefixed hex or plain decimal.
+pub(crate) fn parse_key_id(input: &str) -> Option<u16> {
+    match input
+        .strip_prefix("0x")
+        .or_else(|| input.strip_prefix("0X"))
+    {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => input.parse().ok(),
+    }
+}
+
+/// How the input field is read into the bytes that are signed or verified.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum InputMode {
+    /// The UTF-8 bytes of the text as typed
+    #[default]
+    Text,
+    /// Hex digits; whitespace between them is ignored
+    Hex,
+}
+
+impl InputMode {
+    pub(crate) const ALL: [InputMode; 2] = [InputMode::Text, InputMode::Hex];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            InputMode::Text => "Text",
+            InputMode::Hex => "Hex",
+        }
+    }
+
+    /// Bytes the input stands for in this mode.
+    pub(crate) fn decode(self, input: &str) -> Result<Vec<u8>, String> {
+        match self {
+            InputMode::Text => Ok(input.as_bytes().to_vec()),
+            InputMode::Hex => {
+                let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+                hex::decode(&digits).map_err(|e| format!("The input is not valid hex: {}", e))
+            }
+        }
+    }
+
+    /// `bytes` as the output shows them: quoted text, or hex.
+    pub(crate) fn show(self, bytes: &[u8]) -> String {
+        match self {
+            InputMode::Text => format!("'{}'", String::from_utf8_lossy(bytes)),
+            InputMode::Hex => hex::encode(bytes),
+        }
+    }
+}
+
+/// Keyboard shortcut shown next to a button label.
+fn shortcut_hint(keys: &'static str) -> impl IntoElement {
+    div().text_xs().text_color(rgb(0xcce5ff)).child(keys)
+}
+
+/// How a segment of the Sign & Verify output is colored.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OutputTone {
+    /// The operation worked / the signature is valid
+    Success,
+    /// The cryptographic check ran and rejected the signature
+    Failure,
+    /// The operation could not be carried out (device, session, input problems)
+    Error,
+    /// Supporting data such as inputs, hex dumps and timings
+    Detail,
+    /// Worth attention but not a failure, e.g. a valid but non-canonical signature
+    Warning,
+}
+
+/// One styled block of the Sign & Verify output.
+#[derive(Clone)]
+pub(crate) struct OutputSegment {
+    pub tone: OutputTone,
+    pub text: SharedString,
+}
+
+impl OutputSegment {
+    pub fn success(text: impl Into<SharedString>) -> Self {
+        Self {
+            tone: OutputTone::Success,
+            text: text.into(),
+        }
+    }
+
+    pub fn failure(text: impl Into<SharedString>) -> Self {
+        Self {
+            tone: OutputTone::Failure,
+            text: text.into(),
+        }
+    }
+
+    pub fn error(text: impl Into<SharedString>) -> Self {
+        Self {
+            tone: OutputTone::Error,
+            text: text.into(),
+        }
+    }
+
+    pub fn detail(text: impl Into<SharedString>) -> Self {
+        Self {
+            tone: OutputTone::Detail,
+            text: text.into(),
+        }
+    }
+
+    pub fn warning(text: impl Into<SharedString>) -> Self {
+        Self {
+            tone: OutputTone::Warning,
+            text: text.into(),
+        }
+    }
+}
+
+/// Output segments reporting a signing operation.
+pub(crate) fn format_sign_outcome(outcome: &SignOutcome, mode: InputMode) -> Vec<OutputSegment> {
+    let signature_form = match outcome.algorithm {
+        Some(asymmetric::Algorithm::Ed25519) => "Ed25519 R||S".to_string(),
+        Some(
+            asymmetric::Algorithm::Rsa2048
+            | asymmetric::Algorithm::Rsa3072
+            | asymmetric::Algorithm::Rsa4096,
+        ) => "RSA PKCS#1 v1.5".to_string(),
+        _ => match outcome.hash_alg {
+            Some(hash_alg) => format!("DER over {}", hash_alg.name()),
+            None => "DER over the given digest".to_string(),
+        },
+    };
+
+    let mut output = vec![
+        OutputSegment::success(format!(
+            "✓ Successfully signed text with key 0x{:04x} in {} ms",
+            outcome.key_id,
+            outcome.elapsed.as_millis()
+        )),
+        OutputSegment::detail(format!(
+            "Input ({}, {} bytes): {}\n\n\
+             Signed payload: {}\n\n\
+             Signature ({}, hex):\n{}\n\n\
+             Length: {} bytes\n\n\
+             Signature (raw r||s, hex):\n{}",
+            mode.label(),
+            outcome.message.len(),
+            mode.show(&outcome.message),
+            mode.show(&outcome.payload),
+            signature_form,
+            hex::encode(&outcome.signature),
+            outcome.signature.len(),
+            outcome
+                .signature_raw
+                .as_ref()
+                .map_or_else(|| "(not convertible)".to_string(), hex::encode),
+        )),
+    ];
+
+    if let Some(components) = &outcome.ecdsa_components {
+        output.push(OutputSegment::detail(format!(
+            "Encoding: {}, {} bytes in total
+
+r ({} bytes):
+{}
+
+s ({} bytes):
+{}",
+            components.encoding.label(),
+            components.len,
+            components.r.len(),
+            hex::encode(&components.r),
+            components.s.len(),
+            hex::encode(&components.s),
+        )));
+        output.push(if components.is_low_s {
+            OutputSegment::detail("s is in the lower half of the curve order (low-S).")
+        } else {
+            OutputSegment::warning(
+                "s is in the upper half of the curve order (high-S). Verifiers that require \
+                 low-S reject it; use \"Normalize to low-S\" first.",
+            )
+        });
+    }
+
+    output
+}
+
+/// Output segments reporting a file signature.
+pub(crate) fn format_file_sign_outcome(outcome: &FileSignOutcome) -> Vec<OutputSegment> {
+    vec![
+        OutputSegment::success(format!(
+            "✓ Signed '{}' with key 0x{:04x} in {} ms",
+            outcome.path.display(),
+            outcome.key_id,
+            outcome.elapsed.as_millis()
+        )),
+        OutputSegment::detail(format!(
+            "Size: {} bytes\n\n\
+             {} of the file:\n{}\n\n\
+             Signature (DER, hex):\n{}",
+            outcome.size,
+            outcome.hash_alg.name(),
+            hex::encode(&outcome.digest),
+            hex::encode(&outcome.signature),
+        )),
+    ]
+}
+
+/// Output segments reporting a batch of input lines signed one by one: a
+/// summary, then each line's signature or the reason it failed, in input order.
+/// `lines` and `results` pair up by position; `rejected` lines never reached
+/// the device.
+pub(crate) fn format_batch_outcome(
+    key_id: u16,
+    mode: InputMode,
+    lines: &[(usize, Vec<u8>)],
+    results: Vec<HsmResult<Vec<u8>>>,
+    rejected: &[(usize, String)],
+    elapsed: Duration,
+) -> Vec<OutputSegment> {
+    let mut entries: Vec<(usize, OutputSegment)> = lines
+        .iter()
+        .zip(results)
+        .map(|((line, message), result)| {
+            let segment = match result {
+                Ok(signature) => OutputSegment::detail(format!(
+                    "{}. {} → {}",
+                    line,
+                    mode.show(message),
+                    hex::encode(signature)
+                )),
+                Err(e) => OutputSegment::failure(format!("{}. ✗ {}", line, e)),
+            };
+            (*line, segment)
+        })
+        .collect();
+    entries.extend(rejected.iter().map(|(line, reason)| {
+        (
+            *line,
+            OutputSegment::failure(format!("{}. ✗ {}", line, reason)),
+        )
+    }));
+    entries.sort_by_key(|(line, _)| *line);
+
+    let total = entries.len();
+    let failed = entries
+        .iter()
+        .filter(|(_, segment)| segment.tone == OutputTone::Failure)
+        .count();
+    let headline = if failed == 0 {
+        OutputSegment::success(format!(
+            "✓ Signed {} line(s) with key 0x{:04x} in {} ms",
+            total,
+            key_id,
+            elapsed.as_millis()
+        ))
+    } else if failed == total {
+        OutputSegment::failure(format!("✗ None of the {} line(s) could be signed", total))
+    } else {
+        OutputSegment::warning(format!(
+            "⚠ Signed {} of {} line(s) with key 0x{:04x} in {} ms; {} failed",
+            total - failed,
+            total,
+            key_id,
+            elapsed.as_millis(),
+            failed
+        ))
+    };
+
+    std::iter::once(headline)
+        .chain(entries.into_iter().map(|(_, segment)| segment))
+        .collect()
+}
+
+/// Output segments reporting a verification.
+pub(crate) fn format_verify_outcome(
+    message: &[u8],
+    mode: InputMode,
+    outcome: &VerifyOutcome,
+) -> Vec<OutputSegment> {
+    let headline = if outcome.valid {
+        OutputSegment::success(format!(
+            "✓ Signature verification SUCCESSFUL: the {} signature is valid",
+            outcome.encoding.label()
+        ))
+    } else if outcome.key_changed {
+        OutputSegment::failure(
+            "✗ Signature verification FAILED: the key at this ID has changed since signing",
+        )
+    } else {
+        OutputSegment::failure(format!(
+            "✗ Signature verification FAILED: the {} signature does not match the text",
+            outcome.encoding.label()
+        ))
+    };
+
+    let mut output = vec![headline];
+    if outcome.high_s {
+        output.push(OutputSegment::warning(
+            "⚠ The signature is non-canonical (high-S). Ecosystems such as Bitcoin and Ethereum \
+             reject it; use \"Normalize to low-S\" to rewrite it.",
+        ));
+    }
+    output.push(OutputSegment::detail(format!(
+        "Input ({}, {} bytes): {}\n\nKey: 0x{:04x}\n\nTook {} ms",
+        mode.label(),
+        message.len(),
+        mode.show(message),
+        outcome.key_id,
+        outcome.elapsed.as_millis()
+    )));
+    output
+}
+
+/// Segment stating whether a timestamped signature is still within its window.
+pub(crate) fn format_freshness(result: &TimestampedVerifyResult) -> OutputSegment {
+    let age_secs = result.age.num_seconds();
+    let age = if age_secs < 0 {
+        format!("{} s in the future", -age_secs)
+    } else {
+        format!("{} s old", age_secs)
+    };
+
+    match result.status {
+        TimestampStatus::Fresh => OutputSegment::success(format!(
+            "✓ Timestamp {} is fresh: {} (max {} s)",
+            result.timestamp.to_rfc3339(),
+            age,
+            result.max_age.as_secs()
+        )),
+        TimestampStatus::Stale => OutputSegment::warning(format!(
+            "⚠ Timestamp {} is STALE: {} (max {} s). The signature is valid but too old to accept.",
+            result.timestamp.to_rfc3339(),
+            age,
+            result.max_age.as_secs()
+        )),
+        TimestampStatus::InvalidSignature => OutputSegment::failure(format!(
+            "✗ Timestamp {} is not trustworthy: the signature over it is invalid",
+            result.timestamp.to_rfc3339()
+        )),
+    }
+}
+
+/// Error segment shown when there is no usable session.
+pub(crate) fn session_error_output(e: &HsmError) -> Vec<OutputSegment> {
+    vec![
+        OutputSegment::error(format!("Failed to use YubiHSM2 session: {}", e)),
+        OutputSegment::detail("Go to the Auth screen and authenticate first."),
+    ]
+}
+
+/// Whether `summary` is a key the Sign & Verify screen can sign with.
+fn is_signing_key(summary: &hsm::ObjectSummary) -> bool {
+    summary.object_type == Type::AsymmetricKey
+        && summary
+            .capabilities
+            .intersects(Capability::SIGN_ECDSA | Capability::SIGN_EDDSA | Capability::SIGN_PKCS)
+}
+
+/// The signing key input as captured on the UI thread, so that resolving it,
+/// which may need the device, can happen in a background task.
+pub(crate) struct SigningKeyRef {
+    key_ref: String,
+    /// Key used when the input is empty
+    fallback: u16,
+    /// Whether `fallback` is the built-in default rather than a key the user picked
+    fallback_is_default: bool,
+    active_domain: Option<u8>,
+}
+
+impl SigningKeyRef {
+    /// Note for the output when signing falls back to the built-in default key.
+    pub(crate) fn default_key_note(&self) -> Option<OutputSegment> {
+        (self.key_ref.is_empty() && self.fallback_is_default).then(|| {
+            OutputSegment::warning(format!(
+                "No signing key selected; using the default key 0x{:04x}.",
+                self.fallback
+            ))
+        })
+    }
+
+    /// Empty means `fallback`, a number is used as the key ID, anything else is
+    /// looked up by label. The key must be in the active domain.
+    pub(crate) fn resolve(&self, client: &HsmClient) -> HsmResult<u16> {
+        let key_id = if self.key_ref.is_empty() {
+            self.fallback
+        } else {
+            match parse_key_id(&self.key_ref) {
+                Some(key_id) => key_id,
+                None => hsm::resolve_key_by_label(client, &self.key_ref)?,
+            }
+        };
+
+        check_domain(client, self.active_domain, key_id, Type::AsymmetricKey)?;
+        Ok(key_id)
+    }
+}
 
 impl HsmApp {
+    /// Capture the signing key input; empty falls back to the key picked in the
+    /// list, then the scratch key if one exists, otherwise the default key.
+    pub(crate) fn signing_key_ref(&self, cx: &mut Context<'_, Self>) -> SigningKeyRef {
+        let picked = self.selected_signing_key.or(self.scratch_key);
+        SigningKeyRef {
+            key_ref: self.signing_key_input.read(cx).content().trim().to_string(),
+            fallback: picked.unwrap_or(self.settings.signing_key_id),
+            fallback_is_default: picked.is_none(),
+            active_domain: self.active_domain,
+        }
+    }
+
+    /// Keep the signing keys out of `rows` for the key picker, dropping the
+    /// selection if its key is gone.
+    pub(crate) fn set_signing_keys(&mut self, rows: &[hsm::ObjectSummary]) {
+        self.signing_keys = rows
+            .iter()
+            .filter(|row| is_signing_key(row))
+            .cloned()
+            .collect();
+        let keys = &self.signing_keys;
+        if self
+            .selected_signing_key
+            .is_some_and(|key_id| !keys.iter().any(|key| key.object_id == key_id))
+        {
+            self.selected_signing_key = None;
+        }
+    }
+
+    /// List the device's signing keys in the background for the key picker.
+    pub(crate) fn refresh_signing_keys(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            |client| client.with_session_retry(|c| hsm::list_object_summaries(c, &[])),
+            |view, result, _, cx| match result {
+                Ok(rows) => {
+                    view.set_signing_keys(&rows);
+                    view.set_hmac_keys(&rows);
+                }
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!(
+                        "Failed to list signing keys: {}",
+                        e
+                    ))];
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.output = session_error_output(&e);
+        }
+
+        cx.notify();
+    }
+
+    /// Chips choosing the signing key from the last listing; the first one
+    /// leaves the choice to the fallback key.
+    fn render_signing_key_picker(&self, cx: &mut Context<'_, Self>) -> impl IntoElement {
+        let chip = |label: String, value: Option<u16>, cx: &mut Context<'_, Self>| {
+            let is_active = self.selected_signing_key == value;
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(rgb(0xffffff))
+                .bg(if is_active {
+                    rgb(0x007acc)
+                } else {
+                    rgb(0x2a2a2a)
+                })
+                .hover(|style| style.bg(rgb(0x404040)))
+                .child(label)
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(move |view, _, _, cx| {
+                        view.selected_signing_key = value;
+                        // The typed reference wins over the picker, so clear it
+                        view.signing_key_input.update(cx, |input, cx| {
+                            input.set_content(String::new(), cx);
+                        });
+                        cx.notify();
+                    }),
+                )
+        };
+
+        div()
+            .flex()
+            .flex_wrap()
+            .items_center()
+            .gap_1()
+            .child(
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x888888))
+                    .mr_1()
+                    .child("Key:"),
+            )
+            .child(chip(
+                format!(
+                    "default (0x{:04x})",
+                    self.scratch_key.unwrap_or(self.settings.signing_key_id)
+                ),
+                None,
+                cx,
+            ))
+            .children(self.signing_keys.iter().map(|key| {
+                chip(
+                    format!(
+                        "0x{:04x} {:?} ({:?})",
+                        key.object_id, key.label, key.algorithm
+                    ),
+                    Some(key.object_id),
+                    cx,
+                )
+            }))
+            .child(
+                div()
+                    .ml_2()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .text_xs()
+                    .text_color(rgb(0xffffff))
+                    .bg(rgb(0x6c757d))
+                    .when(!self.hsm_busy, |el| {
+                        el.hover(|style| style.bg(rgb(0x5a6268)))
+                            .cursor_pointer()
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.refresh_signing_keys(window, cx);
+                                }),
+                            )
+                    })
+                    .child("Refresh"),
+            )
+    }
+
+    /// Resolve the signing key from the key input on the calling thread.
+    pub(crate) fn resolve_signing_key(
+        &self,
+        client: &HsmClient,
+        cx: &mut Context<'_, Self>,
+    ) -> HsmResult<u16> {
+        self.signing_key_ref(cx).resolve(client)
+    }
+
+    /// Algorithm of the key the signing key field refers to, if the session can
+    /// tell; a loaded signature is taken to come from that key.
+    pub(crate) fn signing_key_algorithm(
+        &self,
+        cx: &mut Context<'_, Self>,
+    ) -> Option<asymmetric::Algorithm> {
+        let client = self.session.active_client().ok()?;
+        let key_id = self.resolve_signing_key(client, cx).ok()?;
+        client
+            .with_session_retry(|c| hsm::get_public_key(c, key_id))
+            .ok()
+            .map(|public_key| public_key.algorithm)
+    }
+
+    /// Freshness window from the max-age field; empty means the default.
+    pub(crate) fn max_signature_age(&self, cx: &mut Context<'_, Self>) -> HsmResult<Duration> {
+        let value = self.max_age_input.read(cx).content();
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(Duration::from_secs(DEFAULT_MAX_SIGNATURE_AGE_SECS));
+        }
+
+        value.parse().map(Duration::from_secs).map_err(|_| {
+            HsmError::InvalidInput(format!(
+                "Max age must be a whole number of seconds, got '{}'",
+                value
+            ))
+        })
+    }
+
+    /// The input field decoded in the current input mode. Empty input and
+    /// invalid hex are reported here, before anything goes to the device.
+    pub(crate) fn input_bytes(&self, cx: &App) -> Result<Vec<u8>, String> {
+        let message = self
+            .input_mode
+            .decode(&self.text_input.read(cx).content())?;
+        if message.is_empty() {
+            return Err("Input text is empty".to_string());
+        }
+        Ok(message)
+    }
+
+    /// Hash Sign and Verify apply to the input, or None when the input is
+    /// signed as a precomputed digest.
+    pub(crate) fn message_hash(&self) -> Option<hsm::HashAlg> {
+        (!self.sign_prehashed).then_some(self.hash_alg)
+    }
+
+    /// Bytes covered by the current signature for the given input,
+    /// including the timestamp prefix if it was signed with one.
+    pub(crate) fn signed_payload(&self, message: &[u8]) -> Vec<u8> {
+        match self.signature_timestamp {
+            Some(timestamp) => hsm::timestamped_payload(timestamp, message),
+            None => message.to_vec(),
+        }
+    }
+
+    /// Store a freshly produced signature together with its raw `r || s` form
+    /// and the algorithm and fingerprint of the key that made it.
+    pub(crate) fn set_signature(
+        &mut self,
+        signature: Vec<u8>,
+        algorithm: Option<asymmetric::Algorithm>,
+        timestamp: Option<DateTime<Utc>>,
+        key_fingerprint: Option<String>,
+    ) {
+        self.signature_raw =
+            algorithm.and_then(|algorithm| hsm::to_raw(&signature, algorithm).ok());
+        self.signature = Some(signature);
+        self.signature_algorithm = algorithm;
+        self.signature_timestamp = timestamp;
+        self.signature_key_fingerprint = key_fingerprint;
+        self.signature_copied = false;
+    }
+
+    /// Forget the current signature in both encodings.
+    pub(crate) fn clear_signature(&mut self) {
+        self.signature = None;
+        self.signature_raw = None;
+        self.signature_algorithm = None;
+        self.signature_timestamp = None;
+        self.signature_key_fingerprint = None;
+        self.signature_copied = false;
+    }
+
+    /// Put the full signature, as the device returned it, on the clipboard as hex.
+    fn copy_signature(&mut self, cx: &mut Context<'_, Self>) {
+        if let Some(signature) = &self.signature {
+            cx.write_to_clipboard(ClipboardItem::new_string(hex::encode(signature)));
+            self.signature_copied = true;
+            cx.notify();
+        }
+    }
+
+    /// Sign every non-empty line of the input on its own, in one batch. Lines are
+    /// numbered as in the input box; a line that fails is reported and the rest
+    /// are still signed.
+    pub(crate) fn sign_lines(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(hash_alg) = self.message_hash() else {
+            self.output = vec![OutputSegment::error(
+                "Error: Each line is hashed before signing; turn off \"Sign as prehash\" first",
+            )];
+            cx.notify();
+            return;
+        };
+
+        let input = self.text_input.read(cx).content();
+        let mode = self.input_mode;
+        let mut lines = Vec::new();
+        let mut rejected = Vec::new();
+        for (index, line) in input.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match mode.decode(line) {
+                Ok(message) => lines.push((index + 1, message)),
+                Err(e) => rejected.push((index + 1, e)),
+            }
+        }
+        if lines.is_empty() {
+            self.output = if rejected.is_empty() {
+                vec![OutputSegment::error("Error: Input text is empty")]
+            } else {
+                rejected
+                    .iter()
+                    .map(|(line, e)| OutputSegment::error(format!("Error: Line {}: {}", line, e)))
+                    .collect()
+            };
+            cx.notify();
+            return;
+        }
+
+        let key_ref = self.signing_key_ref(cx);
+        let default_key_note = key_ref.default_key_note();
+        let messages: Vec<Vec<u8>> = lines.iter().map(|(_, message)| message.clone()).collect();
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                key_ref.resolve(client).and_then(|key_id| {
+                    let started = Instant::now();
+                    let results = client
+                        .with_session_retry(|c| hsm::sign_batch(c, key_id, &messages, hash_alg))?;
+                    Ok((key_id, results, started.elapsed()))
+                })
+            },
+            move |view, result, _, cx| match result {
+                Ok((key_id, results, elapsed)) => {
+                    view.output =
+                        format_batch_outcome(key_id, mode, &lines, results, &rejected, elapsed);
+                    view.output.extend(default_key_note);
+                }
+                Err(e) => {
+                    view.output =
+                        vec![OutputSegment::error(format!("Batch signing failed: {}", e))];
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.output = session_error_output(&e);
+        }
+
+        cx.notify();
+    }
+
+    /// Ask for a file, then sign its contents in the background. The file is
+    /// hashed while it is read, so large artifacts are not loaded into memory.
+    pub(crate) fn prompt_sign_file(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        self.prompt_open_path("Sign file", window, cx, |view, path, window, cx| {
+            let path = match path {
+                Ok(path) => path,
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!(
+                        "Error: Cannot open the file dialog: {}",
+                        e
+                    ))];
+                    return;
+                }
+            };
+
+            let key_ref = view.signing_key_ref(cx);
+            let default_key_note = key_ref.default_key_note();
+            let hash_alg = view.hash_alg;
+
+            let spawned = view.spawn_hsm_task(
+                window,
+                cx,
+                move |client| {
+                    key_ref.resolve(client).and_then(|key_id| {
+                        client.with_session_retry(|c| hsm::sign_file(c, key_id, &path, hash_alg))
+                    })
+                },
+                move |view, result, _, cx| match result {
+                    Ok(outcome) => {
+                        view.output = format_file_sign_outcome(&outcome);
+                        view.output.extend(default_key_note);
+                    }
+                    Err(e) => {
+                        view.output = vec![OutputSegment::error(format!(
+                            "Signing the file failed: {}",
+                            e
+                        ))];
+                        view.handle_device_disconnected(&e, cx);
+                    }
+                },
+            );
+            if let Err(e) = spawned {
+                view.output = session_error_output(&e);
+            }
+
+            cx.notify();
+        });
+    }
+
+    /// Ask for a file, then write the current signature to it in the form picked
+    /// next to the "Save signature…" button.
+    fn prompt_save_signature(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        let Some(signature) = self.signature.clone() else {
+            return;
+        };
+        let Some(algorithm) = self.signature_algorithm else {
+            self.output = vec![OutputSegment::error(
+                "Error: The curve of this signature is unknown, so it cannot be converted. Sign again, or load it with its signing key selected.",
+            )];
+            cx.notify();
+            return;
+        };
+
+        let encoding = self.signature_file_encoding;
+        let suggested_name = match encoding {
+            SignatureEncoding::Der => format!("{}.der", SIGNATURE_FILE_PREFIX),
+            SignatureEncoding::Raw => format!("{}.bin", SIGNATURE_FILE_PREFIX),
+        };
+
+        self.prompt_save_path(
+            &suggested_name,
+            window,
+            cx,
+            move |view, path, _window, _cx| {
+                // Converted from whatever form the device returned
+                let bytes = match encoding {
+                    SignatureEncoding::Der => hsm::to_der(&signature, algorithm),
+                    SignatureEncoding::Raw => hsm::to_raw(&signature, algorithm),
+                };
+                let path = match path {
+                    Ok(path) => path,
+                    Err(e) => {
+                        view.output = vec![OutputSegment::error(format!(
+                            "Error: Cannot open the save dialog: {}",
+                            e
+                        ))];
+                        return;
+                    }
+                };
+                let result = bytes
+                    .and_then(|bytes| hsm::export::write_file(&path, &bytes).map(|()| bytes.len()));
+
+                view.output = match result {
+                    Ok(len) => vec![OutputSegment::success(format!(
+                        "✓ Wrote {} bytes ({} signature) to '{}'",
+                        len,
+                        encoding.label(),
+                        path.display()
+                    ))],
+                    Err(e) => vec![OutputSegment::error(format!(
+                        "Error: Saving the signature failed: {}",
+                        e
+                    ))],
+                };
+            },
+        );
+    }
+
+    /// Ask for a signature file made elsewhere and make it the current signature,
+    /// so Verify checks it against the text and key on screen. DER and raw
+    /// r||s are told apart by their contents.
+    fn prompt_load_signature(&mut self, window: &mut Window, cx: &mut Context<'_, Self>) {
+        self.prompt_open_path("Load signature", window, cx, |view, path, _window, cx| {
+            let path = match path {
+                Ok(path) => path,
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!(
+                        "Error: Cannot open the file dialog: {}",
+                        e
+                    ))];
+                    return;
+                }
+            };
+
+            match hsm::read_signature(&path) {
+                Ok(signature) => {
+                    let encoding = SignatureEncoding::detect(&signature);
+                    let len = signature.len();
+                    // Drop timestamp and key fingerprint from any earlier signature
+                    view.clear_signature();
+                    let algorithm = view.signing_key_algorithm(cx);
+                    view.set_signature(signature, algorithm, None, None);
+                    view.output = vec![
+                        OutputSegment::success(format!(
+                            "✓ Loaded a {}-byte {} signature from '{}'",
+                            len,
+                            encoding.label(),
+                            path.display()
+                        )),
+                        OutputSegment::detail(
+                            "Click Verify to check it against the current text and signing key.",
+                        ),
+                    ];
+                }
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!(
+                        "Error: Loading the signature failed: {}",
+                        e
+                    ))];
+                }
+            }
+            cx.notify();
+        });
+    }
+
+    /// Ask for a directory, then write message, DER signature and PEM public key
+    /// there so the signature can be checked with plain `openssl dgst`.
+    pub(crate) fn export_for_openssl(
+        &mut self,
+        _: &ExportForOpenssl,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        if self.signature.is_none() {
+            self.output = vec![OutputSegment::error(
+                "Error: No signature to export. Sign text first.",
+            )];
+            cx.notify();
+            return;
+        }
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Export for openssl",
+                "Writes the signed message, sig.der, pub.pem and a README with the openssl verify command.",
+            )
+            .with_input(
+                cx,
+                format!("Directory (default: {})", DEFAULT_OPENSSL_EXPORT_DIR),
+            )
+            .confirm_label("Export")
+        });
+
+        self.open_modal(modal, window, cx, |view, dir, _window, cx| {
+            let dir = dir
+                .map(|dir| dir.trim().to_string())
+                .filter(|dir| !dir.is_empty())
+                .unwrap_or_else(|| DEFAULT_OPENSSL_EXPORT_DIR.to_string());
+            let Some(signature) = view.signature.clone() else {
+                return;
+            };
+            let payload = match view.input_bytes(cx) {
+                Ok(message) => view.signed_payload(&message),
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                    return;
+                }
+            };
+
+            let result = view.session.active_client().and_then(|client| {
+                let key_id = view.resolve_signing_key(client, cx)?;
+                client.with_session_retry(|c| {
+                    hsm::export_for_openssl(c, key_id, &payload, &signature, Path::new(&dir))
+                })
+            });
+
+            match result {
+                Ok(command) => {
+                    view.output = vec![
+                        OutputSegment::success(format!("✓ Exported to '{}'", dir)),
+                        OutputSegment::detail(format!("Verify with:\n  cd {}\n  {}", dir, command)),
+                    ];
+                }
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!("Export failed: {}", e))];
+                    view.handle_device_disconnected(&e, cx);
+                }
+            }
+        });
+    }
+
+    /// Sign on the device and verify on the host, showing each step of the check.
+    pub(crate) fn compare_signature(
+        &mut self,
+        _: &CompareSignature,
+        _window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let message = match self.input_bytes(cx) {
+            Ok(message) => message,
+            Err(e) => {
+                self.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                cx.notify();
+                return;
+            }
+        };
+        let mode = self.input_mode;
+
+        match self.session.active_client() {
+            Ok(client) => match self.resolve_signing_key(client, cx).and_then(|key_id| {
+                client.with_session_retry(|c| hsm::compare_signature(c, key_id, &message))
+            }) {
+                Ok(breakdown) => {
+                    self.output = vec![OutputSegment::detail(format!(
+                        "Device vs host ECDSA comparison ({:?})\n\n\
+                         1. Message ({}, {} bytes): {}\n\
+                         2. {} digest sent to the device:\n{}\n\n\
+                         3. Device signature (DER, {} bytes):\n{}\n\n\
+                         4. Signature components:\n   r = {}\n   s = {}\n   {}\n\n\
+                         5. Public key fetched from the device (SEC1 uncompressed):\n{}",
+                        breakdown.algorithm,
+                        mode.label(),
+                        message.len(),
+                        mode.show(&message),
+                        breakdown.hash_alg.name(),
+                        hex::encode(&breakdown.digest),
+                        breakdown.der.len(),
+                        hex::encode(&breakdown.der),
+                        hex::encode(&breakdown.r),
+                        hex::encode(&breakdown.s),
+                        if breakdown.is_low_s {
+                            "✓ s is low (canonical form)"
+                        } else {
+                            "✗ s is high (not in canonical low-S form)"
+                        },
+                        hex::encode(&breakdown.public_key),
+                    ))];
+                    // The verdict gets its own segment so it stands out from the dump
+                    self.output.push(if breakdown.verified {
+                        OutputSegment::success(format!(
+                            "6. Host-side verification with the {} crate: ✓ signature is valid",
+                            breakdown.verifier
+                        ))
+                    } else {
+                        OutputSegment::failure(format!(
+                            "6. Host-side verification with the {} crate: ✗ signature does NOT verify",
+                            breakdown.verifier
+                        ))
+                    });
+                    self.set_signature(breakdown.der, Some(breakdown.algorithm), None, None);
+                }
+                Err(e) => {
+                    self.output = vec![OutputSegment::error(format!("Comparison failed: {}", e))];
+                    self.handle_device_disconnected(&e, cx);
+                }
+            },
+            Err(e) => {
+                self.output = session_error_output(&e);
+            }
+        }
+
+        cx.notify();
+    }
+
+    /// Recover a public key from a signature with a recovery id and compare it with
+    /// the signing key on the device. Without input, the current device signature
+    /// is used and the matching recovery id is searched for.
+    pub(crate) fn recover_public_key(
+        &mut self,
+        _: &RecoverPublicKey,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Recover public key",
+                "Paste a signature over the input text as hex r || s || v (v = 0-3 or 27/28), \
+                 hashed with SHA-256. Leave empty to use the current signature.",
+            )
+            .with_input(cx, "Signature with recovery id (hex)")
+            .confirm_label("Recover")
+        });
+
+        self.open_modal(modal, window, cx, |view, signature_hex, _window, cx| {
+            let signature_hex = signature_hex.unwrap_or_default().trim().to_string();
+            let message = match view.input_bytes(cx) {
+                Ok(message) => message,
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                    return;
+                }
+            };
+
+            let result = view.session.active_client().and_then(|client| {
+                let key_id = view.resolve_signing_key(client, cx)?;
+                let public_key = client.with_session_retry(|c| hsm::get_public_key(c, key_id))?;
+                let curve = match public_key.algorithm {
+                    asymmetric::Algorithm::EcP256 => Curve::P256,
+                    asymmetric::Algorithm::EcP384 => Curve::P384,
+                    asymmetric::Algorithm::EcK256 => Curve::K256,
+                    other => {
+                        return Err(HsmError::InvalidKey(format!(
+                            "Key 0x{:04x} uses {:?}; recovery needs a P-256, P-384 or secp256k1 key",
+                            key_id, other
+                        )));
+                    }
+                };
+
+                let (payload, signature) = if signature_hex.is_empty() {
+                    // Work out which recovery id the device signature would carry
+                    let payload = view.signed_payload(&message);
+                    let raw = view.signature_raw.clone().ok_or_else(|| {
+                        HsmError::InvalidInput(
+                            "No signature to recover from. Sign text first or paste one."
+                                .to_string(),
+                        )
+                    })?;
+                    let v = hsm::find_recovery_id(&payload, &raw, &public_key.bytes, curve)?
+                        .ok_or_else(|| {
+                            HsmError::VerificationFailed(
+                                "No recovery id maps the current signature to the device key"
+                                    .to_string(),
+                            )
+                        })?;
+                    let mut signature = raw;
+                    signature.push(v);
+                    (payload, signature)
+                } else {
+                    let signature = hex::decode(&signature_hex).map_err(|e| {
+                        HsmError::InvalidInput(format!("Signature is not valid hex: {}", e))
+                    })?;
+                    (message.clone(), signature)
+                };
+
+                let recovered = hsm::recover_public_key(&payload, &signature, curve)?;
+                let mut device_key = vec![0x04];
+                device_key.extend_from_slice(&public_key.bytes);
+                Ok((key_id, signature, recovered, device_key))
+            });
+
+            view.output = match result {
+                Ok((key_id, signature, recovered, device_key)) => {
+                    let verdict = if recovered == device_key {
+                        OutputSegment::success(format!(
+                            "✓ Recovered public key matches device key 0x{:04x}",
+                            key_id
+                        ))
+                    } else {
+                        OutputSegment::failure(format!(
+                            "✗ Recovered public key differs from device key 0x{:04x}",
+                            key_id
+                        ))
+                    };
+                    vec![
+                        verdict,
+                        OutputSegment::detail(format!(
+                            "Signature (r || s || v, hex):\n{}\n\n\
+                             Recovered key (SEC1 uncompressed):\n{}\n\n\
+                             Device key (SEC1 uncompressed):\n{}",
+                            hex::encode(&signature),
+                            hex::encode(&recovered),
+                            hex::encode(&device_key)
+                        )),
+                    ]
+                }
+                Err(e) => {
+                    view.handle_device_disconnected(&e, cx);
+                    vec![OutputSegment::error(format!("Recovery failed: {}", e))]
+                }
+            };
+        });
+    }
+
+    /// Sign the input with the selected key, then have a second key attest the
+    /// selected key and check that certificate, showing both results together.
+    pub(crate) fn sign_and_attest(
+        &mut self,
+        _: &SignAndAttest,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let message = match self.input_bytes(cx) {
+            Ok(message) => message,
+            Err(e) => {
+                self.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                cx.notify();
+                return;
+            }
+        };
+        let mode = self.input_mode;
+
+        let modal = cx.new(|cx| {
+            Modal::new(
+                cx,
+                "Sign and attest",
+                "Signs the input with the selected key, then asks the attesting key to issue an \
+                 X.509 certificate for it. The attesting key needs the sign-attestation-certificate \
+                 capability and a template certificate stored as an opaque object under its ID.",
+            )
+            .with_input(cx, "Attesting key ID (e.g. 0x0010)")
+            .confirm_label("Sign and attest")
+        });
+
+        self.open_modal(modal, window, cx, move |view, attesting_key, _window, cx| {
+            let Some(attesting_key_id) = parse_key_id(attesting_key.unwrap_or_default().trim())
+            else {
+                view.output = vec![OutputSegment::error(
+                    "Error: Enter the attesting key as a numeric ID (hex with 0x, or decimal)",
+                )];
+                cx.notify();
+                return;
+            };
+
+            let timestamp = view.sign_with_timestamp.then(|| view.clock.now());
+            let hash_alg = view.message_hash();
+            let result = view.session.active_client().and_then(|client| {
+                let key_id = view.resolve_signing_key(client, cx)?;
+                client.with_session_retry(|c| {
+                    let signed =
+                        hsm::sign_message(c, key_id, &message, timestamp, hash_alg)?;
+                    let attested = hsm::attest_key(c, key_id, attesting_key_id)?;
+                    Ok((signed, attested))
+                })
+            });
+
+            match result {
+                Ok((signed, attested)) => {
+                    let mut output = format_sign_outcome(&signed, mode);
+                    output.push(
+                        if attested.subject_key_matches && attested.signature_valid {
+                            OutputSegment::success(format!(
+                                "✓ Key 0x{:04x} attested by key 0x{:04x}: the certificate names this key and its signature is valid",
+                                attested.key_id, attested.attesting_key_id
+                            ))
+                        } else if !attested.signature_valid {
+                            OutputSegment::failure(format!(
+                                "✗ Attestation by key 0x{:04x} does NOT verify under the attesting key",
+                                attested.attesting_key_id
+                            ))
+                        } else {
+                            OutputSegment::failure(format!(
+                                "✗ Attestation certificate does not name the current public key of 0x{:04x}",
+                                attested.key_id
+                            ))
+                        },
+                    );
+                    output.push(OutputSegment::detail(format!(
+                        "Attestation certificate ({} bytes DER)\nSHA-256: {}\n\n{}",
+                        attested.certificate.len(),
+                        attested.certificate_fingerprint,
+                        hex::encode(&attested.certificate)
+                    )));
+                    view.output = output;
+                    view.set_signature(
+                        signed.signature,
+                        signed.algorithm,
+                        signed.timestamp,
+                        signed.key_fingerprint,
+                    );
+                }
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!(
+                        "Sign and attest failed: {}",
+                        e
+                    ))];
+                    view.handle_device_disconnected(&e, cx);
+                }
+            }
+        });
+    }
+
+    /// Replace the current signature with its low-S form; both verify.
+    pub(crate) fn normalize_signature(&mut self, cx: &mut Context<'_, Self>) {
+        let Some(signature) = self.signature.clone() else {
+            self.output = vec![OutputSegment::error(
+                "Error: No signature to normalize. Sign text first.",
+            )];
+            cx.notify();
+            return;
+        };
+
+        // The curve order decides what low-S means, so never guess it
+        let Some(algorithm) = self.signature_algorithm else {
+            self.output = vec![OutputSegment::error(
+                "Error: The curve of this signature is unknown, so it cannot be normalized. Sign again, or load it with its signing key selected.",
+            )];
+            cx.notify();
+            return;
+        };
+
+        self.output = match hsm::to_der(&signature, algorithm)
+            .and_then(|der| Ok((der, hsm::normalize_low_s(&signature, algorithm)?)))
+        {
+            Ok((der, normalized)) if der == normalized => vec![OutputSegment::success(
+                "✓ The signature is already canonical (low-S); nothing to change.",
+            )],
+            Ok((_, normalized)) => {
+                let output = vec![
+                    OutputSegment::success("✓ Rewrote the signature with s = n - s (low-S)."),
+                    OutputSegment::detail(format!(
+                        "Signature (DER, hex):\n{}",
+                        hex::encode(&normalized)
+                    )),
+                ];
+                self.set_signature(
+                    normalized,
+                    self.signature_algorithm,
+                    self.signature_timestamp,
+                    self.signature_key_fingerprint.clone(),
+                );
+                output
+            }
+            Err(e) => vec![OutputSegment::error(format!("Normalization failed: {}", e))],
+        };
+
+        cx.notify();
+    }
+
+    /// Sign the input as a compact JWS in the background and show the token.
+    pub(crate) fn sign_jws(
+        &mut self,
+        _: &SignJws,
+        window: &mut Window,
+        cx: &mut Context<'_, Self>,
+    ) {
+        let message = match self.input_bytes(cx) {
+            Ok(message) => message,
+            Err(e) => {
+                self.output = vec![OutputSegment::error(format!("Error: {}", e))];
+                cx.notify();
+                return;
+            }
+        };
+        let payload = format!(
+            "Payload ({}, {} bytes): {}",
+            self.input_mode.label(),
+            message.len(),
+            self.input_mode.show(&message)
+        );
+        let key_ref = self.signing_key_ref(cx);
+
+        let spawned = self.spawn_hsm_task(
+            window,
+            cx,
+            move |client| {
+                key_ref.resolve(client).and_then(|key_id| {
+                    client.with_session_retry(|c| hsm::sign_jws(c, key_id, &message))
+                })
+            },
+            move |view, result, _, cx| match result {
+                Ok(jws) => {
+                    view.output = vec![
+                        OutputSegment::success(format!(
+                            "✓ Signed as JWS ({}, compact serialization)",
+                            jws.alg
+                        )),
+                        OutputSegment::detail(format!("{}\n\nJWS:\n{}", payload, jws.token)),
+                    ];
+                }
+                Err(e) => {
+                    view.output = vec![OutputSegment::error(format!("JWS signing failed: {}", e))];
+                    view.handle_device_disconnected(&e, cx);
+                }
+            },
+        );
+        if let Err(e) = spawned {
+            self.output = session_error_output(&e);
+        }
+
+        cx.notify();
+    }
+
     pub fn render_sign_verify_screen(&mut self, cx: &mut Context<'_, Self>) -> AnyElement {
         div()
+            .key_context(KEY_CONTEXT)
+            .track_focus(&self.sign_verify_focus)
+            .on_action(cx.listener(Self::sign_text))
+            .on_action(cx.listener(Self::verify_text))
             .flex()
             .flex_col()
             .bg(rgb(0x2e2e2e))
@@ -32,7 +1371,7 @@ impl HsmApp {
                     ),
             )
             .child(
-                // Input section
+                // Key selection
                 div()
                     .flex()
                     .flex_col()
@@ -41,7 +1380,61 @@ impl HsmApp {
                         div()
                             .text_sm()
                             .text_color(rgb(0xcccccc))
-                            .child("Input Text:"),
+                            .child("Signing key (pick one, or type an ID or label):"),
+                    )
+                    .child(self.render_signing_key_picker(cx))
+                    .child(
+                        div()
+                            .bg(rgb(0x1e1e1e))
+                            .border_1()
+                            .border_color(rgb(0x444444))
+                            .rounded_md()
+                            .p_2()
+                            .min_h(px(24.))
+                            .child(self.signing_key_input.clone()),
+                    ),
+            )
+            .child(
+                // Input section
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_1()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(rgb(0xcccccc))
+                                    .mr_1()
+                                    .child("Input:"),
+                            )
+                            .children(InputMode::ALL.into_iter().map(|mode| {
+                                div()
+                                    .px_2()
+                                    .py_1()
+                                    .rounded_md()
+                                    .text_xs()
+                                    .cursor_pointer()
+                                    .text_color(rgb(0xffffff))
+                                    .bg(if self.input_mode == mode {
+                                        rgb(0x007acc)
+                                    } else {
+                                        rgb(0x2a2a2a)
+                                    })
+                                    .hover(|style| style.bg(rgb(0x404040)))
+                                    .child(mode.label())
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(move |view, _, _, cx| {
+                                            view.input_mode = mode;
+                                            cx.notify();
+                                        }),
+                                    )
+                            })),
                     )
                     .child(
                         div()
@@ -54,42 +1447,421 @@ impl HsmApp {
                             .child(self.text_input.clone()),
                     ),
             )
+            .child(
+                // Timestamp option
+                div()
+                    .flex()
+                    .gap_2()
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(if self.sign_with_timestamp {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    })
+                    .child("Prefix the current UTC time (RFC3339) to the signed payload")
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.sign_with_timestamp = !view.sign_with_timestamp;
+                            cx.notify();
+                        }),
+                    ),
+            )
+            .child(
+                // Prehash option
+                div()
+                    .flex()
+                    .gap_2()
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(if self.sign_prehashed { "[x]" } else { "[ ]" })
+                    .child(
+                        "Sign as prehash: the input is the digest and is not hashed again \
+                         (ECDSA only; use Hex input)",
+                    )
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.sign_prehashed = !view.sign_prehashed;
+                            cx.notify();
+                        }),
+                    ),
+            )
+            .child(
+                // Where hashing happens, and what each path trusts
+                div()
+                    .text_xs()
+                    .text_color(rgb(0x888888))
+                    .child(
+                        "For ECDSA keys the hash always runs on this machine: the YubiHSM2 ECDSA command accepts a digest only, \
+                         so the device signs whatever digest it is sent and cannot vouch for what was hashed. \
+                         Unchecked, the input is hashed here with the hash below (it must match the curve, SHA-384 for P-384), \
+                         so this app controls what is signed. Checked, whoever computed the digest does; a wrong or double-hashed \
+                         digest still yields a valid signature. Verify follows the same setting.",
+                    ),
+            )
+            .child(
+                // Freshness window for timestamped signatures
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .child("Max signature age when verifying timestamped signatures:"),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x1e1e1e))
+                            .border_1()
+                            .border_color(rgb(0x444444))
+                            .rounded_md()
+                            .p_2()
+                            .w(px(160.))
+                            .min_h(px(24.))
+                            .child(self.max_age_input.clone()),
+                    ),
+            )
+            .child(
+                // Signature form used by Verify
+                div()
+                    .flex()
+                    .gap_2()
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(rgb(0xcccccc))
+                    .child(if self.verify_encoding == SignatureEncoding::Raw {
+                        "[x]"
+                    } else {
+                        "[ ]"
+                    })
+                    .child("Verify the raw r||s form instead of the device's DER signature")
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|view, _, _, cx| {
+                            view.verify_encoding = match view.verify_encoding {
+                                SignatureEncoding::Der => SignatureEncoding::Raw,
+                                SignatureEncoding::Raw => SignatureEncoding::Der,
+                            };
+                            cx.notify();
+                        }),
+                    ),
+            )
+            .child(
+                // Hash used for ECDSA
+                div()
+                    .flex()
+                    .flex_wrap()
+                    .items_center()
+                    .gap_1()
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .mr_1()
+                            .child("Hash:"),
+                    )
+                    .children(hsm::HashAlg::ALL.into_iter().map(|hash_alg| {
+                        div()
+                            .px_2()
+                            .py_1()
+                            .rounded_md()
+                            .text_xs()
+                            .cursor_pointer()
+                            .text_color(rgb(0xffffff))
+                            .bg(if self.hash_alg == hash_alg {
+                                rgb(0x007acc)
+                            } else {
+                                rgb(0x2a2a2a)
+                            })
+                            .hover(|style| style.bg(rgb(0x404040)))
+                            .child(hash_alg.name())
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |view, _, _, cx| {
+                                    view.hash_alg = hash_alg;
+                                    view.update_settings(|settings| settings.hash_alg = hash_alg);
+                                    cx.notify();
+                                }),
+                            )
+                    })),
+            )
             .child(
                 // Buttons
                 div()
                     .flex()
+                    .flex_wrap()
                     .gap_2()
                     .child(
                         div()
-                            .bg(rgb(0x007acc))
-                            .hover(|style| style.bg(rgb(0x005a9e)))
+                            .bg(if self.hsm_busy {
+                                rgb(0x555555)
+                            } else {
+                                rgb(0x007acc)
+                            })
+                            .when(!self.hsm_busy, |el| {
+                                el.hover(|style| style.bg(rgb(0x005a9e)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.sign_text(&SignText, window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .text_color(rgb(0xffffff))
+                            .child(if self.hsm_busy { "Working…" } else { "Sign" })
+                            .child(shortcut_hint("⌘S")),
+                    )
+                    .child(
+                        div()
+                            .bg(if self.hsm_busy {
+                                rgb(0x555555)
+                            } else {
+                                rgb(0x28a745)
+                            })
+                            .when(!self.hsm_busy, |el| {
+                                el.hover(|style| style.bg(rgb(0x1e7e34)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.verify_text(&VerifyText, window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .text_color(rgb(0xffffff))
+                            .child(if self.hsm_busy { "Working…" } else { "Verify" })
+                            .child(shortcut_hint("⌘⇧V")),
+                    )
+                    .child(
+                        div()
+                            .bg(if self.hsm_busy {
+                                rgb(0x555555)
+                            } else {
+                                rgb(0x007acc)
+                            })
+                            .when(!self.hsm_busy, |el| {
+                                el.hover(|style| style.bg(rgb(0x005a9e)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.prompt_sign_file(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child("Sign file…"),
+                    )
+                    .child(
+                        div()
+                            .bg(if self.hsm_busy {
+                                rgb(0x555555)
+                            } else {
+                                rgb(0x007acc)
+                            })
+                            .when(!self.hsm_busy, |el| {
+                                el.hover(|style| style.bg(rgb(0x005a9e)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.sign_lines(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child("Sign each line"),
+                    )
+                    .child({
+                        let can_save = self.signature.is_some();
+                        div()
+                            .bg(if can_save {
+                                rgb(0x6c757d)
+                            } else {
+                                rgb(0x555555)
+                            })
+                            .when(can_save, |el| {
+                                el.hover(|style| style.bg(rgb(0x5a6268)))
+                                    .cursor_pointer()
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|view, _, window, cx| {
+                                            view.prompt_save_signature(window, cx);
+                                        }),
+                                    )
+                            })
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .child("Save signature…")
+                    })
+                    .child(
+                        div()
+                            .bg(rgb(0x6c757d))
+                            .hover(|style| style.bg(rgb(0x5a6268)))
                             .rounded_md()
                             .px_4()
                             .py_2()
                             .text_color(rgb(0xffffff))
                             .cursor_pointer()
-                            .child("Sign")
+                            .child("Load signature…")
                             .on_mouse_down(
                                 MouseButton::Left,
                                 cx.listener(|view, _, window, cx| {
-                                    view.sign_text(&SignText, window, cx);
+                                    view.prompt_load_signature(window, cx);
                                 }),
                             ),
                     )
+                    .children(
+                        [SignatureEncoding::Der, SignatureEncoding::Raw].map(|encoding| {
+                            div()
+                                .px_2()
+                                .py_1()
+                                .rounded_md()
+                                .text_xs()
+                                .cursor_pointer()
+                                .text_color(rgb(0xffffff))
+                                .bg(if self.signature_file_encoding == encoding {
+                                    rgb(0x007acc)
+                                } else {
+                                    rgb(0x2a2a2a)
+                                })
+                                .hover(|style| style.bg(rgb(0x404040)))
+                                .child(encoding.label())
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(move |view, _, _, cx| {
+                                        view.signature_file_encoding = encoding;
+                                        cx.notify();
+                                    }),
+                                )
+                        }),
+                    )
                     .child(
                         div()
-                            .bg(rgb(0x28a745))
-                            .hover(|style| style.bg(rgb(0x1e7e34)))
+                            .bg(rgb(0x6f42c1))
+                            .hover(|style| style.bg(rgb(0x59339d)))
                             .rounded_md()
                             .px_4()
                             .py_2()
                             .text_color(rgb(0xffffff))
                             .cursor_pointer()
-                            .child("Verify")
+                            .child("Compare")
                             .on_mouse_down(
                                 MouseButton::Left,
                                 cx.listener(|view, _, window, cx| {
-                                    view.verify_text(&VerifyText, window, cx);
+                                    view.compare_signature(&CompareSignature, window, cx);
+                                }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0xfd7e14))
+                            .hover(|style| style.bg(rgb(0xdc6a0c)))
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .cursor_pointer()
+                            .child("Sign as JWS")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.sign_jws(&SignJws, window, cx);
+                                }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x6f42c1))
+                            .hover(|style| style.bg(rgb(0x59339d)))
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .cursor_pointer()
+                            .child("Sign and attest")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.sign_and_attest(&SignAndAttest, window, cx);
+                                }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x6c757d))
+                            .hover(|style| style.bg(rgb(0x5a6268)))
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .cursor_pointer()
+                            .child("Normalize to low-S")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, _, cx| {
+                                    view.normalize_signature(cx);
+                                }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x20c997))
+                            .hover(|style| style.bg(rgb(0x199d76)))
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .cursor_pointer()
+                            .child("Recover public key")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.recover_public_key(&RecoverPublicKey, window, cx);
+                                }),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .bg(rgb(0x17a2b8))
+                            .hover(|style| style.bg(rgb(0x117a8b)))
+                            .rounded_md()
+                            .px_4()
+                            .py_2()
+                            .text_color(rgb(0xffffff))
+                            .cursor_pointer()
+                            .child("Export for openssl")
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(|view, _, window, cx| {
+                                    view.export_for_openssl(&ExportForOpenssl, window, cx);
                                 }),
                             ),
                     )
@@ -109,9 +1881,10 @@ impl HsmApp {
                                     view.text_input.update(cx, |input, cx| {
                                         input.set_content(String::new(), cx);
                                     });
-                                    view.signature = None;
-                                    view.output_text =
-                                        SharedString::from("Cleared. Ready to sign new text.");
+                                    view.clear_signature();
+                                    view.output = vec![OutputSegment::detail(
+                                        "Cleared. Ready to sign new text.",
+                                    )];
                                     cx.notify();
                                 }),
                             ),
@@ -124,23 +1897,71 @@ impl HsmApp {
                     .flex_col()
                     .gap_2()
                     .flex_grow()
-                    .child(
+                    .child({
+                        let can_copy = self.signature.is_some();
                         div()
-                            .text_sm()
-                            .text_color(rgb(0xcccccc))
-                            .child("Output:"),
-                    )
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .text_sm()
+                                    .text_color(rgb(0xcccccc))
+                                    .child("Output:"),
+                            )
+                            .child(
+                                div()
+                                    .px_2()
+                                    .py_1()
+                                    .rounded_md()
+                                    .text_xs()
+                                    .text_color(rgb(0xffffff))
+                                    .bg(if can_copy {
+                                        rgb(0x6c757d)
+                                    } else {
+                                        rgb(0x555555)
+                                    })
+                                    .when(can_copy, |el| {
+                                        el.hover(|style| style.bg(rgb(0x5a6268)))
+                                            .cursor_pointer()
+                                            .on_mouse_down(
+                                                MouseButton::Left,
+                                                cx.listener(|view, _, _, cx| {
+                                                    view.copy_signature(cx);
+                                                }),
+                                            )
+                                    })
+                                    .child("Copy signature"),
+                            )
+                            .when(self.signature_copied, |el| {
+                                el.child(
+                                    div().text_xs().text_color(rgb(0x00ff00)).child("Copied!"),
+                                )
+                            })
+                    })
                     .child(
                         div()
+                            .flex()
+                            .flex_col()
+                            .gap_2()
                             .bg(rgb(0x1e1e1e))
                             .border_1()
                             .border_color(rgb(0x444444))
                             .rounded_md()
                             .p_2()
                             .flex_grow()
-                            .text_color(rgb(0x00ff00))
                             .text_sm()
-                            .child(self.output_text.clone()),
+                            .children(self.output.iter().map(|segment| {
+                                div()
+                                    .text_color(match segment.tone {
+                                        OutputTone::Success => rgb(0x00ff00),
+                                        OutputTone::Failure => rgb(0xff6b6b),
+                                        OutputTone::Error => rgb(0xfd7e14),
+                                        OutputTone::Detail => rgb(0xaaaaaa),
+                                        OutputTone::Warning => rgb(0xffc107),
+                                    })
+                                    .child(segment.text.clone())
+                            })),
                     ),
             )
             .into_any()
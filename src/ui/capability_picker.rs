@@ -0,0 +1,103 @@
+use gpui::{
+    Context, InteractiveElement, IntoElement, MouseButton, ParentElement, Render, Styled, Window,
+    div, rgb,
+};
+use yubihsm::Capability;
+
+use crate::hsm;
+
+/// Checkbox list over a fixed set of capabilities. The owner reads the
+/// combined flags with [`CapabilityPicker::selected`] and observes the entity
+/// to react to changes.
+pub struct CapabilityPicker {
+    options: Vec<Capability>,
+    selected: Capability,
+}
+
+impl CapabilityPicker {
+    /// Offer each capability in `options`, with `selected` ticked initially.
+    pub fn new(options: &[Capability], selected: Capability) -> Self {
+        Self {
+            options: options.to_vec(),
+            selected,
+        }
+    }
+
+    /// Union of the ticked capabilities.
+    pub fn selected(&self) -> Capability {
+        self.selected
+    }
+
+    /// Replace the selection, e.g. with the defaults for another algorithm.
+    pub fn set_selected(&mut self, selected: Capability, cx: &mut Context<Self>) {
+        self.selected = selected;
+        cx.notify();
+    }
+
+    fn toggle(&mut self, capability: Capability, cx: &mut Context<Self>) {
+        self.flip(capability);
+        cx.notify();
+    }
+
+    /// Tick `capability` if it is unticked and the other way round.
+    fn flip(&mut self, capability: Capability) {
+        self.selected.toggle(capability);
+    }
+}
+
+impl Render for CapabilityPicker {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_wrap()
+            .gap_x_4()
+            .gap_y_1()
+            .children(self.options.iter().map(|&capability| {
+                let checked = self.selected.contains(capability);
+                div()
+                    .flex()
+                    .gap_1()
+                    .cursor_pointer()
+                    .text_sm()
+                    .text_color(if checked {
+                        rgb(0xffffff)
+                    } else {
+                        rgb(0xaaaaaa)
+                    })
+                    .child(if checked { "[x]" } else { "[ ]" })
+                    .child(hsm::format_capabilities(capability))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |picker, _, _, cx| picker.toggle(capability, cx)),
+                    )
+            }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_ticked_flags_combine_into_one_bitmask() {
+        let options = [
+            Capability::SIGN_ECDSA,
+            Capability::SIGN_EDDSA,
+            Capability::EXPORTABLE_UNDER_WRAP,
+        ];
+        let mut picker = CapabilityPicker::new(&options, Capability::empty());
+
+        picker.flip(Capability::SIGN_ECDSA);
+        picker.flip(Capability::EXPORTABLE_UNDER_WRAP);
+
+        // sign-ecdsa is bit 7 and exportable-under-wrap bit 16 on the device
+        assert_eq!(picker.selected().bits(), 0x0001_0080);
+        assert_eq!(
+            picker.selected(),
+            Capability::SIGN_ECDSA | Capability::EXPORTABLE_UNDER_WRAP
+        );
+
+        picker.flip(Capability::SIGN_ECDSA);
+        assert_eq!(picker.selected(), Capability::EXPORTABLE_UNDER_WRAP);
+    }
+}
@@ -0,0 +1,172 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, MouseButton, ParentElement, Render, SharedString, StatefulInteractiveElement,
+    Styled, Subscription, Window, actions, div, hsla, prelude::FluentBuilder, px, rgb,
+};
+
+use super::TextArea;
+
+actions!(command_palette, [SelectPrev, SelectNext, Confirm, Dismiss]);
+
+/// Key context used to scope the palette's navigation bindings
+pub const KEY_CONTEXT: &str = "CommandPalette";
+
+/// Events emitted by a [`CommandPalette`] for the owner to handle.
+pub enum CommandPaletteEvent {
+    /// The user picked the item at this index of the original item list.
+    Selected(usize),
+    /// The user closed the palette without picking anything.
+    Dismissed,
+}
+
+/// Overlay listing commands that are filtered as the user types. The owner
+/// supplies the labels and maps the selected index back to its own handlers.
+pub struct CommandPalette {
+    query: Entity<TextArea>,
+    items: Vec<SharedString>,
+    selected: usize,
+    _query_changed: Subscription,
+}
+
+impl CommandPalette {
+    pub fn new(cx: &mut Context<Self>, items: Vec<SharedString>) -> Self {
+        let query = cx.new(|cx| TextArea::new(cx, "Type a command...".to_string()));
+        // Re-filter whenever the query text changes
+        let _query_changed = cx.observe(&query, |palette, _, cx| {
+            palette.selected = 0;
+            cx.notify();
+        });
+
+        Self {
+            query,
+            items,
+            selected: 0,
+            _query_changed,
+        }
+    }
+
+    /// Indices of items matching every whitespace-separated term of the query.
+    fn matches(&self, cx: &App) -> Vec<usize> {
+        let query = self.query.read(cx).content().to_lowercase();
+        let terms: Vec<&str> = query.split_whitespace().collect();
+
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| {
+                let label = label.to_lowercase();
+                terms.iter().all(|term| label.contains(term))
+            })
+            .map(|(ix, _)| ix)
+            .collect()
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, _: &mut Window, cx: &mut Context<Self>) {
+        self.selected = self.selected.saturating_sub(1);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let count = self.matches(cx).len();
+        if self.selected + 1 < count {
+            self.selected += 1;
+        }
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(&ix) = self.matches(cx).get(self.selected) {
+            cx.emit(CommandPaletteEvent::Selected(ix));
+        }
+    }
+
+    fn dismiss(&mut self, _: &Dismiss, _: &mut Window, cx: &mut Context<Self>) {
+        cx.emit(CommandPaletteEvent::Dismissed);
+    }
+}
+
+impl EventEmitter<CommandPaletteEvent> for CommandPalette {}
+
+impl Focusable for CommandPalette {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.query.focus_handle(cx)
+    }
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let matches = self.matches(cx);
+
+        // Full-size backdrop; clicking outside the list closes the palette
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full()
+            .flex()
+            .justify_center()
+            .pt(px(80.))
+            .bg(hsla(0., 0., 0., 0.4))
+            .occlude()
+            .key_context(KEY_CONTEXT)
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::dismiss))
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|_, _, _, cx| cx.emit(CommandPaletteEvent::Dismissed)),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .w(px(420.))
+                    .max_h(px(360.))
+                    .bg(rgb(0x252526))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .overflow_hidden()
+                    // Keep clicks inside the palette from reaching the backdrop
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .child(
+                        div()
+                            .p_2()
+                            .border_b_1()
+                            .border_color(rgb(0x444444))
+                            .child(self.query.clone()),
+                    )
+                    .children(matches.iter().enumerate().map(|(row, &ix)| {
+                        let is_selected = row == self.selected;
+                        div()
+                            .id(ix)
+                            .px_3()
+                            .py_2()
+                            .cursor_pointer()
+                            .text_sm()
+                            .text_color(rgb(0xffffff))
+                            .bg(if is_selected {
+                                rgb(0x094771)
+                            } else {
+                                rgb(0x252526)
+                            })
+                            .hover(|style| style.bg(rgb(0x2a2d2e)))
+                            .child(self.items[ix].clone())
+                            .on_click(cx.listener(move |_, _, _, cx| {
+                                cx.emit(CommandPaletteEvent::Selected(ix));
+                            }))
+                    }))
+                    .when(matches.is_empty(), |el| {
+                        el.child(
+                            div()
+                                .px_3()
+                                .py_2()
+                                .text_sm()
+                                .text_color(rgb(0x888888))
+                                .child("No matching commands"),
+                        )
+                    }),
+            )
+    }
+}
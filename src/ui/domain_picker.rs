@@ -0,0 +1,61 @@
+use gpui::{
+    Context, InteractiveElement, IntoElement, MouseButton, ParentElement, Render, Styled, Window,
+    div, px, rgb,
+};
+use yubihsm::Domain;
+
+/// Toggle chips for domains 1-16. The owner reads the combined flags with
+/// [`DomainPicker::selected`] and observes the entity to react to changes.
+pub struct DomainPicker {
+    selected: Domain,
+}
+
+impl DomainPicker {
+    pub fn new(selected: Domain) -> Self {
+        Self { selected }
+    }
+
+    /// Union of the picked domains.
+    pub fn selected(&self) -> Domain {
+        self.selected
+    }
+
+    /// Replace the selection, e.g. with the auth key's domains after logging in.
+    pub fn set_selected(&mut self, selected: Domain, cx: &mut Context<Self>) {
+        self.selected = selected;
+        cx.notify();
+    }
+
+    fn toggle(&mut self, domain: Domain, cx: &mut Context<Self>) {
+        self.selected.toggle(domain);
+        cx.notify();
+    }
+}
+
+impl Render for DomainPicker {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_wrap()
+            .gap_1()
+            .children((1..=16u8).map(|number| {
+                let domain = Domain::from_bits_truncate(1 << (number - 1));
+                let picked = self.selected.contains(domain);
+                div()
+                    .w(px(30.))
+                    .py_1()
+                    .rounded_md()
+                    .text_center()
+                    .text_xs()
+                    .cursor_pointer()
+                    .text_color(rgb(0xffffff))
+                    .bg(if picked { rgb(0x007acc) } else { rgb(0x2a2a2a) })
+                    .hover(|style| style.bg(rgb(0x404040)))
+                    .child(number.to_string())
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |picker, _, _, cx| picker.toggle(domain, cx)),
+                    )
+            }))
+    }
+}
@@ -1,4 +1,12 @@
+pub mod capability_picker;
+pub mod command_palette;
+pub mod domain_picker;
+pub mod modal;
 pub mod textarea;
 
 // Re-export for convenience
+pub use capability_picker::CapabilityPicker;
+pub use command_palette::{CommandPalette, CommandPaletteEvent};
+pub use domain_picker::DomainPicker;
+pub use modal::{Modal, ModalEvent};
 pub use textarea::TextArea;
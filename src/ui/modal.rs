@@ -0,0 +1,305 @@
+use gpui::{
+    App, AppContext, Context, Entity, EventEmitter, FocusHandle, Focusable, InteractiveElement,
+    IntoElement, MouseButton, ParentElement, Render, SharedString, Styled, Subscription, Window,
+    div, hsla, prelude::FluentBuilder, px, rgb,
+};
+
+use super::{CapabilityPicker, DomainPicker, TextArea};
+
+/// Events emitted by a [`Modal`] for the owner to handle.
+pub enum ModalEvent {
+    /// The user confirmed; carries the input text if the modal has an input.
+    Confirmed(Option<String>),
+    /// The user dismissed the modal.
+    Cancelled,
+}
+
+/// Overlay dialog with a title, a message, an optional text input and
+/// confirm/cancel buttons. The owner renders it on top of the content area
+/// and subscribes to [`ModalEvent`] to act on the outcome.
+pub struct Modal {
+    focus_handle: FocusHandle,
+    title: SharedString,
+    message: SharedString,
+    input: Option<Entity<TextArea>>,
+    /// Upper bound on the input length in UTF-8 bytes, shown as a live counter
+    max_input_bytes: Option<usize>,
+    /// Capabilities to pick below the input; confirming needs at least one
+    capabilities: Option<Entity<CapabilityPicker>>,
+    /// Domains to pick below the input; confirming needs at least one
+    domains: Option<Entity<DomainPicker>>,
+    confirm_label: SharedString,
+    destructive: bool,
+    _input_changed: Option<Subscription>,
+    _capabilities_changed: Option<Subscription>,
+    _domains_changed: Option<Subscription>,
+}
+
+impl Modal {
+    pub fn new(
+        cx: &mut Context<Self>,
+        title: impl Into<SharedString>,
+        message: impl Into<SharedString>,
+    ) -> Self {
+        Self {
+            focus_handle: cx.focus_handle(),
+            title: title.into(),
+            message: message.into(),
+            input: None,
+            max_input_bytes: None,
+            capabilities: None,
+            domains: None,
+            confirm_label: SharedString::from("Confirm"),
+            destructive: false,
+            _input_changed: None,
+            _capabilities_changed: None,
+            _domains_changed: None,
+        }
+    }
+
+    /// Add a single-line text input below the message.
+    pub fn with_input(mut self, cx: &mut Context<Self>, placeholder: impl Into<String>) -> Self {
+        let placeholder = placeholder.into();
+        let input = cx.new(|cx| TextArea::new(cx, placeholder));
+        // Re-render on every edit so the byte counter stays live
+        self._input_changed = Some(cx.observe(&input, |_, _, cx| cx.notify()));
+        self.input = Some(input);
+        self
+    }
+
+    /// Limit the input to `max` UTF-8 bytes; confirming is blocked above it.
+    pub fn max_input_bytes(mut self, max: usize) -> Self {
+        self.max_input_bytes = Some(max);
+        self
+    }
+
+    /// Show `picker` below the input. The owner keeps its own handle to read
+    /// the selection when the modal is confirmed.
+    pub fn with_capabilities(
+        mut self,
+        cx: &mut Context<Self>,
+        picker: Entity<CapabilityPicker>,
+    ) -> Self {
+        // Re-render so the confirm button follows the selection
+        self._capabilities_changed = Some(cx.observe(&picker, |_, _, cx| cx.notify()));
+        self.capabilities = Some(picker);
+        self
+    }
+
+    /// Show `picker` below the input, like [`Modal::with_capabilities`].
+    pub fn with_domains(mut self, cx: &mut Context<Self>, picker: Entity<DomainPicker>) -> Self {
+        self._domains_changed = Some(cx.observe(&picker, |_, _, cx| cx.notify()));
+        self.domains = Some(picker);
+        self
+    }
+
+    /// Whether confirming is blocked: the input is over its limit, or no
+    /// capability or domain is picked.
+    fn confirm_blocked(&self, cx: &App) -> bool {
+        self.input_len(cx).is_some_and(|(_, over)| over)
+            || self
+                .capabilities
+                .as_ref()
+                .is_some_and(|picker| picker.read(cx).selected().is_empty())
+            || self
+                .domains
+                .as_ref()
+                .is_some_and(|picker| picker.read(cx).selected().is_empty())
+    }
+
+    /// Byte length of the input and whether it exceeds the limit.
+    fn input_len(&self, cx: &App) -> Option<(usize, bool)> {
+        let input = self.input.as_ref()?;
+        let len = input.read(cx).content().len();
+        let over = self.max_input_bytes.is_some_and(|max| len > max);
+        Some((len, over))
+    }
+
+    /// Label for the confirm button (defaults to "Confirm").
+    pub fn confirm_label(mut self, label: impl Into<SharedString>) -> Self {
+        self.confirm_label = label.into();
+        self
+    }
+
+    /// Render the confirm button in red for irreversible actions.
+    pub fn destructive(mut self, destructive: bool) -> Self {
+        self.destructive = destructive;
+        self
+    }
+
+    /// Focus handle of the input if there is one, otherwise of the modal itself.
+    pub fn initial_focus(&self, cx: &App) -> FocusHandle {
+        match self.input.as_ref() {
+            Some(input) => input.focus_handle(cx),
+            None => self.focus_handle.clone(),
+        }
+    }
+
+    fn confirm(&mut self, cx: &mut Context<Self>) {
+        if self.confirm_blocked(cx) {
+            return;
+        }
+
+        let value = self.input.as_ref().map(|input| input.read(cx).content());
+        cx.emit(ModalEvent::Confirmed(value));
+    }
+
+    fn cancel(&mut self, cx: &mut Context<Self>) {
+        cx.emit(ModalEvent::Cancelled);
+    }
+}
+
+impl EventEmitter<ModalEvent> for Modal {}
+
+impl Focusable for Modal {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for Modal {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let input_len = self.input_len(cx);
+        let (confirm_bg, confirm_hover) = if self.confirm_blocked(cx) {
+            // Over the input limit or nothing picked: render the confirm button as disabled
+            (rgb(0x555555), rgb(0x555555))
+        } else if self.destructive {
+            (rgb(0xdc3545), rgb(0xc82333))
+        } else {
+            (rgb(0x007acc), rgb(0x005a9e))
+        };
+
+        // Full-size backdrop that swallows clicks to the content underneath
+        div()
+            .absolute()
+            .top_0()
+            .left_0()
+            .size_full()
+            .flex()
+            .items_center()
+            .justify_center()
+            .bg(hsla(0., 0., 0., 0.6))
+            .occlude()
+            .track_focus(&self.focus_handle)
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_4()
+                    .w(px(420.))
+                    .p_4()
+                    .bg(rgb(0x252526))
+                    .border_1()
+                    .border_color(rgb(0x444444))
+                    .rounded_md()
+                    .child(
+                        div()
+                            .text_lg()
+                            .text_color(rgb(0xffffff))
+                            .child(self.title.clone()),
+                    )
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(rgb(0xcccccc))
+                            .child(self.message.clone()),
+                    )
+                    .when_some(self.input.clone(), |el, input| {
+                        el.child(
+                            div()
+                                .bg(rgb(0x1e1e1e))
+                                .border_1()
+                                .border_color(rgb(0x444444))
+                                .rounded_md()
+                                .p_2()
+                                .min_h(px(24.))
+                                .child(input),
+                        )
+                    })
+                    .when_some(
+                        self.max_input_bytes.zip(input_len),
+                        |el, (max, (len, over))| {
+                            el.child(
+                                div()
+                                    .flex()
+                                    .justify_between()
+                                    .text_xs()
+                                    .text_color(if over { rgb(0xff6b6b) } else { rgb(0x888888) })
+                                    .child(if over {
+                                        format!("Too long: the limit is {} bytes", max)
+                                    } else {
+                                        String::new()
+                                    })
+                                    .child(format!("{}/{} bytes", len, max)),
+                            )
+                        },
+                    )
+                    .when_some(self.capabilities.clone(), |el, picker| {
+                        el.child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .gap_1()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(rgb(0x888888))
+                                        .child("Capabilities (at least one):"),
+                                )
+                                .child(picker),
+                        )
+                    })
+                    .when_some(self.domains.clone(), |el, picker| {
+                        el.child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .gap_1()
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(rgb(0x888888))
+                                        .child("Domains (at least one):"),
+                                )
+                                .child(picker),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .justify_end()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .bg(rgb(0x6c757d))
+                                    .hover(|style| style.bg(rgb(0x5a6268)))
+                                    .rounded_md()
+                                    .px_4()
+                                    .py_2()
+                                    .text_color(rgb(0xffffff))
+                                    .cursor_pointer()
+                                    .child("Cancel")
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|modal, _, _, cx| modal.cancel(cx)),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .bg(confirm_bg)
+                                    .hover(move |style| style.bg(confirm_hover))
+                                    .rounded_md()
+                                    .px_4()
+                                    .py_2()
+                                    .text_color(rgb(0xffffff))
+                                    .cursor_pointer()
+                                    .child(self.confirm_label.clone())
+                                    .on_mouse_down(
+                                        MouseButton::Left,
+                                        cx.listener(|modal, _, _, cx| modal.confirm(cx)),
+                                    ),
+                            ),
+                    ),
+            )
+    }
+}
@@ -14,10 +14,15 @@ actions!(
     [Backspace, Delete, Left, Right, SelectAll, Paste, Copy, Cut]
 );
 
+/// Glyph drawn for every character of a masked input
+const MASK_CHAR: char = '\u{2022}';
+
 pub struct TextArea {
     focus_handle: FocusHandle,
     content: SharedString,
     placeholder: SharedString,
+    /// Draw every character as `MASK_CHAR` and keep the content off the clipboard
+    masked: bool,
     selected_range: Range<usize>,
     selection_reversed: bool,
     marked_range: Option<Range<usize>>,
@@ -32,6 +37,7 @@ impl TextArea {
             focus_handle: cx.focus_handle(),
             content: "".into(),
             placeholder: placeholder.into(),
+            masked: false,
             selected_range: 0..0,
             selection_reversed: false,
             marked_range: None,
@@ -41,6 +47,15 @@ impl TextArea {
         }
     }
 
+    /// Input for secrets: shows dots instead of the text, and Copy/Cut do nothing.
+    /// `content()` still returns the real text.
+    pub fn new_masked(cx: &mut Context<Self>, placeholder: String) -> Self {
+        Self {
+            masked: true,
+            ..Self::new(cx, placeholder)
+        }
+    }
+
     pub fn content(&self) -> String {
         self.content.to_string()
     }
@@ -93,7 +108,7 @@ impl TextArea {
     }
 
     fn copy(&mut self, _: &Copy, _: &mut Window, cx: &mut Context<Self>) {
-        if !self.selected_range.is_empty() {
+        if !self.masked && !self.selected_range.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(
                 self.content[self.selected_range.clone()].to_string(),
             ));
@@ -101,7 +116,7 @@ impl TextArea {
     }
 
     fn cut(&mut self, _: &Cut, window: &mut Window, cx: &mut Context<Self>) {
-        if !self.selected_range.is_empty() {
+        if !self.masked && !self.selected_range.is_empty() {
             cx.write_to_clipboard(ClipboardItem::new_string(
                 self.content[self.selected_range.clone()].to_string(),
             ));
@@ -161,7 +176,27 @@ impl TextArea {
         if position.y > bounds.bottom() {
             return self.content.len();
         }
-        line.closest_index_for_x(position.x - bounds.left())
+        self.content_offset(line.closest_index_for_x(position.x - bounds.left()))
+    }
+
+    /// Map an offset into `content` to the matching offset in the drawn text.
+    /// Masking draws one `MASK_CHAR` per character, so offsets differ in bytes.
+    fn display_offset(&self, offset: usize) -> usize {
+        if !self.masked {
+            return offset;
+        }
+        self.content[..offset].chars().count() * MASK_CHAR.len_utf8()
+    }
+
+    /// Inverse of `display_offset`.
+    fn content_offset(&self, display_offset: usize) -> usize {
+        if !self.masked {
+            return display_offset;
+        }
+        self.content
+            .char_indices()
+            .nth(display_offset / MASK_CHAR.len_utf8())
+            .map_or(self.content.len(), |(idx, _)| idx)
     }
 
     fn select_to(&mut self, offset: usize, cx: &mut Context<Self>) {
@@ -333,11 +368,11 @@ impl EntityInputHandler for TextArea {
         let range = self.range_from_utf16(&range_utf16);
         Some(Bounds::from_corners(
             point(
-                bounds.left() + last_layout.x_for_index(range.start),
+                bounds.left() + last_layout.x_for_index(self.display_offset(range.start)),
                 bounds.top(),
             ),
             point(
-                bounds.left() + last_layout.x_for_index(range.end),
+                bounds.left() + last_layout.x_for_index(self.display_offset(range.end)),
                 bounds.bottom(),
             ),
         ))
@@ -352,7 +387,7 @@ impl EntityInputHandler for TextArea {
         let line_point = self.last_bounds?.localize(&point)?;
         let last_layout = self.last_layout.as_ref()?;
 
-        let utf8_index = last_layout.index_for_x(point.x - line_point.x)?;
+        let utf8_index = self.content_offset(last_layout.index_for_x(point.x - line_point.x)?);
         Some(self.offset_to_utf16(utf8_index))
     }
 }
@@ -411,12 +446,23 @@ impl Element for TextAreaElement {
     ) -> Self::PrepaintState {
         let input = self.input.read(cx);
         let content = input.content.clone();
-        let selected_range = input.selected_range.clone();
-        let cursor = input.cursor_offset();
+        // Everything below is laid out against the drawn text, so map offsets into it
+        let selected_range = input.display_offset(input.selected_range.start)
+            ..input.display_offset(input.selected_range.end);
+        let cursor = input.display_offset(input.cursor_offset());
+        let marked_range = input
+            .marked_range
+            .as_ref()
+            .map(|range| input.display_offset(range.start)..input.display_offset(range.end));
         let style = window.text_style();
 
         let (display_text, text_color) = if content.is_empty() {
             (input.placeholder.clone(), hsla(0., 0., 0.6, 0.5))
+        } else if input.masked {
+            (
+                MASK_CHAR.to_string().repeat(content.chars().count()).into(),
+                hsla(0., 0., 1.0, 1.0),
+            )
         } else {
             (content, hsla(0., 0., 1.0, 1.0)) // White text
         };
@@ -429,7 +475,7 @@ impl Element for TextAreaElement {
             underline: None,
             strikethrough: None,
         };
-        let runs = if let Some(marked_range) = input.marked_range.as_ref() {
+        let runs = if let Some(marked_range) = marked_range.as_ref() {
             vec![
                 TextRun {
                     len: marked_range.start,